@@ -121,6 +121,7 @@ pub fn derive_translate(_input: TokenStream) -> TokenStream {
                     Rule::loc_return => en::Rule::loc_return,
                     Rule::loc_break => en::Rule::loc_break,
                     Rule::loc_continue => en::Rule::loc_continue,
+                    Rule::loc_match => en::Rule::loc_match,
                     Rule::loc_function => en::Rule::loc_function,
                     Rule::loc_fn => en::Rule::loc_fn,
                     Rule::loc_na => en::Rule::loc_na,
@@ -178,8 +179,14 @@ pub fn derive_translate(_input: TokenStream) -> TokenStream {
                     Rule::and => en::Rule::and,
                     Rule::vand => en::Rule::vand,
                     Rule::assign => en::Rule::assign,
+                    Rule::superassign => en::Rule::superassign,
+                    Rule::rassign => en::Rule::rassign,
+                    Rule::rsuperassign => en::Rule::rsuperassign,
+                    Rule::eqassign => en::Rule::eqassign,
                     Rule::special => en::Rule::special,
                     Rule::pipe => en::Rule::pipe,
+                    Rule::tilde => en::Rule::tilde,
+                    Rule::formula => en::Rule::formula,
                     Rule::dollar => en::Rule::dollar,
                     Rule::colon => en::Rule::colon,
                     Rule::doublecolon => en::Rule::doublecolon,
@@ -197,6 +204,10 @@ pub fn derive_translate(_input: TokenStream) -> TokenStream {
                     Rule::atom => en::Rule::atom,
                     Rule::kw_function_or_fn => en::Rule::kw_function_or_fn,
                     Rule::kw_function => en::Rule::kw_function,
+                    Rule::params => en::Rule::params,
+                    Rule::param => en::Rule::param,
+                    Rule::param_annotated => en::Rule::param_annotated,
+                    Rule::returns => en::Rule::returns,
                     Rule::kw_if_else => en::Rule::kw_if_else,
                     Rule::kw_for => en::Rule::kw_for,
                     Rule::kw_while => en::Rule::kw_while,
@@ -204,6 +215,21 @@ pub fn derive_translate(_input: TokenStream) -> TokenStream {
                     Rule::kw_return => en::Rule::kw_return,
                     Rule::kw_break => en::Rule::kw_break,
                     Rule::kw_continue => en::Rule::kw_continue,
+                    Rule::kw_match => en::Rule::kw_match,
+                    Rule::match_arms => en::Rule::match_arms,
+                    Rule::match_sep => en::Rule::match_sep,
+                    Rule::match_arm => en::Rule::match_arm,
+                    Rule::match_guard => en::Rule::match_guard,
+                    Rule::pattern => en::Rule::pattern,
+                    Rule::pattern_list => en::Rule::pattern_list,
+                    Rule::pattern_vec => en::Rule::pattern_vec,
+                    Rule::pattern_elems => en::Rule::pattern_elems,
+                    Rule::pattern_elem => en::Rule::pattern_elem,
+                    Rule::pattern_rest => en::Rule::pattern_rest,
+                    Rule::pattern_named => en::Rule::pattern_named,
+                    Rule::pattern_class => en::Rule::pattern_class,
+                    Rule::pattern_literal => en::Rule::pattern_literal,
+                    Rule::pattern_bind => en::Rule::pattern_bind,
                     Rule::val_null => en::Rule::val_null,
                     Rule::val_na => en::Rule::val_na,
                     Rule::val_inf => en::Rule::val_inf,
@@ -225,6 +251,9 @@ pub fn derive_translate(_input: TokenStream) -> TokenStream {
                     Rule::symbol_with_backticks => en::Rule::symbol_with_backticks,
                     Rule::symbol_backticked => en::Rule::symbol_backticked,
                     Rule::symbol_ident => en::Rule::symbol_ident,
+                    Rule::symbol_namespaced => en::Rule::symbol_namespaced,
+                    Rule::symbol_ident_start => en::Rule::symbol_ident_start,
+                    Rule::symbol_ident_dot => en::Rule::symbol_ident_dot,
                     Rule::list => en::Rule::list,
                     Rule::pairs => en::Rule::pairs,
                     Rule::ellipsis => en::Rule::ellipsis,
@@ -257,7 +286,10 @@ pub fn derive_localized_parser(input: TokenStream) -> TokenStream {
 
                 // Precedence is defined lowest to highest
                 pest::pratt_parser::PrattParser::new()
-                    .op(Op::infix(assign, Right))
+                    .op(Op::infix(eqassign, Right))
+                    .op(Op::infix(assign, Right) | Op::infix(superassign, Right))
+                    .op(Op::infix(rassign, Left) | Op::infix(rsuperassign, Left))
+                    .op(Op::infix(tilde, Left))
                     .op(Op::infix(or, Left) | Op::infix(vor, Left))
                     .op(Op::infix(and, Left) | Op::infix(vand, Left))
                     .op(Op::infix(lt, Left)
@@ -1,4 +1,4 @@
-use r::cli::Cli;
+use r::cli::{Cli, Command};
 use r::lang::Signal;
 use r::repl::repl;
 
@@ -6,9 +6,36 @@ use r::repl::repl;
 fn main() {}
 
 #[cfg(not(feature = "wasm"))]
+#[allow(clippy::result_large_err)]
 fn main() -> Result<(), Signal> {
     use clap::Parser;
+    use r::profiler::{self, Sampling};
+
     let cli = Cli::parse();
+
+    if let Some(Command::Check { file }) = &cli.command {
+        let issues = r::check::check_file(file)?;
+        for issue in issues.iter() {
+            println!("{issue}");
+        }
+
+        if !issues.is_empty() {
+            std::process::exit(1);
+        }
+
+        return Ok(());
+    }
+
     let history = "/tmp/history.txt".to_string();
-    repl(cli.locale, Some(&history), cli.warranty)
+
+    if let Some(file) = &cli.profile {
+        profiler::start(file, Sampling::default(), false)?;
+    }
+
+    r::workspace::set_save_on_exit(cli.save);
+    let result = repl(cli.locale, Some(&history), cli.warranty, !cli.no_restore);
+
+    // samples are written even when the session ends with an error
+    let stopped = profiler::stop();
+    result.and(stopped)
 }
@@ -15,14 +15,21 @@ lazy_static! {
         HashMap::from([
             // automatically populated on build. see build.rs // builtins start
             ("<-", Box::new(InfixAssign) as Box<dyn Builtin>),
+            ("<<-", Box::new(InfixSuperAssign) as Box<dyn Builtin>),
+            ("=", Box::new(InfixEqAssign) as Box<dyn Builtin>),
             ("+", Box::new(InfixAdd) as Box<dyn Builtin>),
             ("-", Box::new(InfixSub) as Box<dyn Builtin>),
             ("-", Box::new(PrefixSub) as Box<dyn Builtin>),
+            ("~", Box::new(InfixTilde) as Box<dyn Builtin>),
+            ("~", Box::new(PrefixTilde) as Box<dyn Builtin>),
             ("..", Box::new(PrefixPack) as Box<dyn Builtin>),
             ("*", Box::new(InfixMul) as Box<dyn Builtin>),
             ("/", Box::new(InfixDiv) as Box<dyn Builtin>),
             ("^", Box::new(InfixPow) as Box<dyn Builtin>),
             ("%", Box::new(InfixMod) as Box<dyn Builtin>),
+            ("%in%", Box::new(InfixIn) as Box<dyn Builtin>),
+            ("%o%", Box::new(InfixOuter) as Box<dyn Builtin>),
+            ("%*%", Box::new(InfixMatMul) as Box<dyn Builtin>),
             ("||", Box::new(InfixOr) as Box<dyn Builtin>),
             ("&&", Box::new(InfixAnd) as Box<dyn Builtin>),
             ("|", Box::new(InfixVectorOr) as Box<dyn Builtin>),
@@ -41,18 +48,123 @@ lazy_static! {
             ("[", Box::new(PostfixVecIndex) as Box<dyn Builtin>),
             ("c", Box::new(PrimitiveC) as Box<dyn Builtin>),
             ("callstack", Box::new(PrimitiveCallstack) as Box<dyn Builtin>),
+            ("sys.call", Box::new(PrimitiveSysCall) as Box<dyn Builtin>),
+            ("sys.calls", Box::new(PrimitiveSysCalls) as Box<dyn Builtin>),
+            ("sys.frames", Box::new(PrimitiveSysFrames) as Box<dyn Builtin>),
+            ("parent.frame", Box::new(PrimitiveParentFrame) as Box<dyn Builtin>),
+            ("file", Box::new(PrimitiveFile) as Box<dyn Builtin>),
+            ("textConnection", Box::new(PrimitiveTextConnection) as Box<dyn Builtin>),
+            ("stdin", Box::new(PrimitiveStdin) as Box<dyn Builtin>),
+            ("stdout", Box::new(PrimitiveStdout) as Box<dyn Builtin>),
+            ("stderr", Box::new(PrimitiveStderr) as Box<dyn Builtin>),
+            ("open", Box::new(PrimitiveOpen) as Box<dyn Builtin>),
+            ("close", Box::new(PrimitiveClose) as Box<dyn Builtin>),
+            ("browser", Box::new(PrimitiveBrowser) as Box<dyn Builtin>),
+            ("debug", Box::new(PrimitiveDebug) as Box<dyn Builtin>),
+            ("debugonce", Box::new(PrimitiveDebugonce) as Box<dyn Builtin>),
+            ("undebug", Box::new(PrimitiveUndebug) as Box<dyn Builtin>),
+            ("isdebugged", Box::new(PrimitiveIsdebugged) as Box<dyn Builtin>),
+            ("setBreakpoint", Box::new(PrimitiveSetBreakpoint) as Box<dyn Builtin>),
             ("environment", Box::new(PrimitiveEnvironment) as Box<dyn Builtin>),
             ("eval", Box::new(PrimitiveEval) as Box<dyn Builtin>),
+            ("file.exists", Box::new(PrimitiveFileExists) as Box<dyn Builtin>),
+            ("file.remove", Box::new(PrimitiveFileRemove) as Box<dyn Builtin>),
+            ("dir.create", Box::new(PrimitiveDirCreate) as Box<dyn Builtin>),
+            ("list.files", Box::new(PrimitiveListFiles) as Box<dyn Builtin>),
+            ("file.path", Box::new(PrimitiveFilePath) as Box<dyn Builtin>),
+            ("basename", Box::new(PrimitiveBasename) as Box<dyn Builtin>),
+            ("dirname", Box::new(PrimitiveDirname) as Box<dyn Builtin>),
+            ("normalizePath", Box::new(PrimitiveNormalizePath) as Box<dyn Builtin>),
+            ("tempdir", Box::new(PrimitiveTempdir) as Box<dyn Builtin>),
+            ("tempfile", Box::new(PrimitiveTempfile) as Box<dyn Builtin>),
+            ("all.vars", Box::new(PrimitiveAllVars) as Box<dyn Builtin>),
+            ("terms", Box::new(PrimitiveTerms) as Box<dyn Builtin>),
+            ("future", Box::new(PrimitiveFuture) as Box<dyn Builtin>),
+            ("value", Box::new(PrimitiveValue) as Box<dyn Builtin>),
+            ("resolved", Box::new(PrimitiveResolved) as Box<dyn Builtin>),
+            ("cancel", Box::new(PrimitiveCancel) as Box<dyn Builtin>),
+            ("%<-%", Box::new(PrimitiveFutureAssign) as Box<dyn Builtin>),
+            ("summary", Box::new(PrimitiveSummary) as Box<dyn Builtin>),
+            ("predict", Box::new(PrimitivePredict) as Box<dyn Builtin>),
+            ("coef", Box::new(PrimitiveCoef) as Box<dyn Builtin>),
+            ("coef.default", Box::new(PrimitiveCoefDefault) as Box<dyn Builtin>),
+            ("residuals", Box::new(PrimitiveResiduals) as Box<dyn Builtin>),
+            ("residuals.default", Box::new(PrimitiveResidualsDefault) as Box<dyn Builtin>),
+            ("ifelse", Box::new(PrimitiveIfelse) as Box<dyn Builtin>),
+            ("readLines", Box::new(PrimitiveReadLines) as Box<dyn Builtin>),
+            ("writeLines", Box::new(PrimitiveWriteLines) as Box<dyn Builtin>),
+            ("readline", Box::new(PrimitiveReadline) as Box<dyn Builtin>),
+            ("cat", Box::new(PrimitiveCat) as Box<dyn Builtin>),
+            ("scan", Box::new(PrimitiveScan) as Box<dyn Builtin>),
+            ("toJSON", Box::new(PrimitiveToJSON) as Box<dyn Builtin>),
+            ("fromJSON", Box::new(PrimitiveFromJSON) as Box<dyn Builtin>),
             ("list", Box::new(PrimitiveList) as Box<dyn Builtin>),
+            ("lm", Box::new(PrimitiveLm) as Box<dyn Builtin>),
+            ("summary.lm", Box::new(PrimitiveSummaryLm) as Box<dyn Builtin>),
+            ("predict.lm", Box::new(PrimitivePredictLm) as Box<dyn Builtin>),
+            ("print.lm", Box::new(PrimitivePrintLm) as Box<dyn Builtin>),
+            ("print.summary.lm", Box::new(PrimitivePrintSummaryLm) as Box<dyn Builtin>),
             ("names", Box::new(PrimitiveNames) as Box<dyn Builtin>),
+            ("names<-", Box::new(PrimitiveNamesAssign) as Box<dyn Builtin>),
+            ("attr", Box::new(PrimitiveAttr) as Box<dyn Builtin>),
+            ("attr<-", Box::new(PrimitiveAttrAssign) as Box<dyn Builtin>),
+            ("options", Box::new(PrimitiveOptions) as Box<dyn Builtin>),
+            ("getOption", Box::new(PrimitiveGetOption) as Box<dyn Builtin>),
+            ("parallel::map", Box::new(PrimitiveParallelMap) as Box<dyn Builtin>),
+            ("mclapply", Box::new(PrimitiveMclapply) as Box<dyn Builtin>),
             ("parent", Box::new(PrimitiveParent) as Box<dyn Builtin>),
+            ("partial", Box::new(PrimitivePartial) as Box<dyn Builtin>),
+            ("compose", Box::new(PrimitiveCompose) as Box<dyn Builtin>),
             ("paste", Box::new(PrimitivePaste) as Box<dyn Builtin>),
             ("print", Box::new(PrimitivePrint) as Box<dyn Builtin>),
+            ("print.default", Box::new(PrimitivePrintDefault) as Box<dyn Builtin>),
             ("q", Box::new(PrimitiveQ) as Box<dyn Builtin>),
             ("quote", Box::new(PrimitiveQuote) as Box<dyn Builtin>),
             ("rnorm", Box::new(PrimitiveRnorm) as Box<dyn Builtin>),
+            ("Rprof", Box::new(PrimitiveRprof) as Box<dyn Builtin>),
             ("runif", Box::new(PrimitiveRunif) as Box<dyn Builtin>),
+            ("save", Box::new(PrimitiveSave) as Box<dyn Builtin>),
+            ("save.image", Box::new(PrimitiveSaveImage) as Box<dyn Builtin>),
+            ("load", Box::new(PrimitiveLoad) as Box<dyn Builtin>),
+            ("seq", Box::new(PrimitiveSeq) as Box<dyn Builtin>),
+            ("seq_len", Box::new(PrimitiveSeqLen) as Box<dyn Builtin>),
+            ("seq_along", Box::new(PrimitiveSeqAlong) as Box<dyn Builtin>),
+            ("saveRDS", Box::new(PrimitiveSaveRDS) as Box<dyn Builtin>),
+            ("readRDS", Box::new(PrimitiveReadRDS) as Box<dyn Builtin>),
+            ("serialize", Box::new(PrimitiveSerialize) as Box<dyn Builtin>),
+            ("unserialize", Box::new(PrimitiveUnserialize) as Box<dyn Builtin>),
+            ("source", Box::new(PrimitiveSource) as Box<dyn Builtin>),
+            ("stopifnot", Box::new(PrimitiveStopifnot) as Box<dyn Builtin>),
             ("sum", Box::new(PrimitiveSum) as Box<dyn Builtin>),
+            ("switch", Box::new(PrimitiveSwitch) as Box<dyn Builtin>),
+            ("Sys.getenv", Box::new(PrimitiveSysGetenv) as Box<dyn Builtin>),
+            ("Sys.setenv", Box::new(PrimitiveSysSetenv) as Box<dyn Builtin>),
+            ("Sys.time", Box::new(PrimitiveSysTime) as Box<dyn Builtin>),
+            ("Sys.sleep", Box::new(PrimitiveSysSleep) as Box<dyn Builtin>),
+            ("system2", Box::new(PrimitiveSystem2) as Box<dyn Builtin>),
+            ("as.Date", Box::new(PrimitiveAsDate) as Box<dyn Builtin>),
+            ("as.POSIXct", Box::new(PrimitiveAsPOSIXct) as Box<dyn Builtin>),
+            ("format", Box::new(PrimitiveFormat) as Box<dyn Builtin>),
+            ("strftime", Box::new(PrimitiveStrftime) as Box<dyn Builtin>),
+            ("strptime", Box::new(PrimitiveStrptime) as Box<dyn Builtin>),
+            ("difftime", Box::new(PrimitiveDifftime) as Box<dyn Builtin>),
+            ("Sys.Date", Box::new(PrimitiveSysDate) as Box<dyn Builtin>),
+            ("unclass", Box::new(PrimitiveUnclass) as Box<dyn Builtin>),
+            ("trace", Box::new(PrimitiveTrace) as Box<dyn Builtin>),
+            ("untrace", Box::new(PrimitiveUntrace) as Box<dyn Builtin>),
+            ("traceback", Box::new(PrimitiveTraceback) as Box<dyn Builtin>),
+            ("rep", Box::new(PrimitiveRep) as Box<dyn Builtin>),
+            ("rev", Box::new(PrimitiveRev) as Box<dyn Builtin>),
+            ("sort", Box::new(PrimitiveSort) as Box<dyn Builtin>),
+            ("order", Box::new(PrimitiveOrder) as Box<dyn Builtin>),
+            ("rank", Box::new(PrimitiveRank) as Box<dyn Builtin>),
+            ("unique", Box::new(PrimitiveUnique) as Box<dyn Builtin>),
+            ("duplicated", Box::new(PrimitiveDuplicated) as Box<dyn Builtin>),
+            ("which", Box::new(PrimitiveWhich) as Box<dyn Builtin>),
+            ("which.max", Box::new(PrimitiveWhichMax) as Box<dyn Builtin>),
+            ("which.min", Box::new(PrimitiveWhichMin) as Box<dyn Builtin>),
+            ("tabulate", Box::new(PrimitiveTabulate) as Box<dyn Builtin>),
+            ("warning", Box::new(PrimitiveWarning) as Box<dyn Builtin>),
             // builtins end
         ])
     };
@@ -3,9 +3,12 @@ extern crate r_derive;
 use crate::callable::builtins::BUILTIN;
 use crate::callable::dyncompare::*;
 use crate::context::Context;
+use crate::debugger;
 use crate::error::Error;
 use crate::object::List;
-use crate::object::{Expr, ExprList, Obj};
+use crate::object::{Expr, ExprList, Obj, Signature};
+use crate::profiler;
+use crate::trace;
 use crate::{internal_err, lang::*};
 
 impl std::fmt::Debug for Box<dyn Callable> {
@@ -35,6 +38,10 @@ pub trait Callable {
         ExprList::new()
     }
 
+    fn signature(&self) -> Option<&Signature> {
+        None
+    }
+
     fn match_args(&self, args: List, stack: &mut CallStack) -> Result<(List, List), Signal> {
         let mut formals = self.formals();
         let ellipsis: List = vec![].into();
@@ -190,7 +197,7 @@ pub trait Format {
     }
 }
 
-pub trait Builtin: Callable + CallableClone + Format + DynCompare + Sync {
+pub trait Builtin: Callable + CallableClone + Format + DynCompare + Send + Sync {
     fn is_transparent(&self) -> bool {
         false
     }
@@ -249,15 +256,27 @@ where
 
 impl Builtin for String {}
 
+/// Namespaces whose builtins may be referred to by qualified name
+const NAMESPACES: [&str; 2] = ["base", "parallel"];
+
 pub fn builtin(s: &str) -> Result<Box<dyn Builtin>, Signal> {
-    let err = Error::VariableNotFound(s.to_string());
-    <Box<dyn Builtin>>::try_from(s).or(Err(err.into()))
+    <Box<dyn Builtin>>::try_from(s).map_err(|_| match s.split_once("::") {
+        Some((ns, _)) if !NAMESPACES.contains(&ns) => Error::PackageNotFound(ns.to_string()).into(),
+        _ => Error::VariableNotFound(s.to_string()).into(),
+    })
 }
 
 impl TryFrom<&str> for Box<dyn Builtin> {
     type Error = ();
     fn try_from(s: &str) -> Result<Self, Self::Error> {
-        BUILTIN.get(s).map_or(Err(()), |b| Ok(b.clone()))
+        // namespaced builtins are found by their full name, or otherwise by
+        // their name within a known namespace, as in `base::paste`
+        let builtin = BUILTIN.get(s).or_else(|| {
+            let (ns, name) = s.split_once("::")?;
+            NAMESPACES.contains(&ns).then(|| BUILTIN.get(name))?
+        });
+
+        builtin.map_or(Err(()), |b| Ok(b.clone()))
     }
 }
 
@@ -278,6 +297,54 @@ pub fn force_closures(
         .collect()
 }
 
+/// Force arguments to annotated parameters once bound in the environment of
+/// a call, raising an error for any that do not satisfy their annotated type
+///
+/// Parameters without an argument are checked as their default is forced,
+/// such that defaults may refer to other parameters.
+fn check_arg_types(sig: &Signature, stack: &mut CallStack) -> Result<(), Signal> {
+    let env = stack.env();
+    for (param, ty) in sig.params.iter() {
+        let Some(value) = env.values.borrow().get(param).cloned() else {
+            continue;
+        };
+
+        if let Obj::Closure(Expr::Missing, _) = value {
+            continue;
+        }
+
+        let value = value.force(stack)?;
+        if !value.is_type(ty) {
+            let found = value.class().join("/");
+            return Error::ArgumentTypeMismatch(param.clone(), ty.clone(), found).into();
+        }
+
+        env.insert(param.clone(), value);
+    }
+
+    Ok(())
+}
+
+/// Raise an error for a return value which does not satisfy its annotation
+fn check_return_type(sig: &Signature, result: EvalResult) -> EvalResult {
+    let Some(ty) = &sig.returns else {
+        return result;
+    };
+
+    let value = match &result {
+        Ok(value) => value,
+        Err(Signal::Return(value, _)) => value.as_ref(),
+        _ => return result,
+    };
+
+    if !value.is_type(ty) {
+        let found = value.class().join("/");
+        return Error::ReturnTypeMismatch(ty.clone(), found).into();
+    }
+
+    result
+}
+
 impl Format for String {
     fn rfmt_call_with(&self, _state: FormatState, args: &ExprList) -> String {
         format!("{}({})", self, args)
@@ -294,7 +361,7 @@ impl Format for Obj {}
 
 impl Callable for Obj {
     fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
-        let Obj::Function(_, body, _) = self else {
+        let Obj::Function(_, body, ..) = self else {
             return internal_err!();
         };
 
@@ -309,21 +376,55 @@ impl Callable for Obj {
     }
 
     fn call_matched(&self, args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
-        let Obj::Function(_, body, _) = self else {
+        let Obj::Function(_, body, _, sig) = self else {
             return internal_err!();
         };
 
+        let logged = trace::log_entry(&args, stack);
+
         stack.env().insert("...".to_string(), Obj::List(ellipsis));
         stack.env().append(args);
-        stack.eval(body.clone())
+
+        // arguments to annotated parameters are forced to check their types
+        check_arg_types(sig, stack)?;
+
+        profiler::sample(stack);
+        trace::run_tracer(self, stack)?;
+
+        let debugged = debugger::enter_call(self, stack)?;
+        if debugged.is_none() && logged.is_none() && sig.returns.is_none() {
+            return stack.eval(body.clone());
+        }
+
+        // debugged, logged and annotated calls are evaluated in full before
+        // returning
+        let result = stack.eval_and_finalize(body.clone());
+        let result = check_return_type(sig, result);
+
+        if let Some(call) = debugged {
+            debugger::exit_call(call);
+        }
+
+        if let Some(call) = logged {
+            trace::log_exit(call, &result);
+        }
+
+        result
     }
 
     fn formals(&self) -> ExprList {
         match self {
-            Obj::Function(formals, _, _) => formals.clone(),
+            Obj::Function(formals, ..) => formals.clone(),
             _ => ExprList::new(),
         }
     }
+
+    fn signature(&self) -> Option<&Signature> {
+        match self {
+            Obj::Function(.., sig) if !sig.is_empty() => Some(sig),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -399,4 +500,50 @@ mod test {
 
         assert_eq!(r! { f <- function(a, b = a) { b }; f(a = 3) }, r! { 3 });
     }
+
+    #[test]
+    fn annotated_arguments_are_checked() {
+        assert_eq!(
+            r! { f <- function(x: numeric, n: integer = 1L) x + n; f(1) },
+            r! { 2 }
+        );
+
+        // integers satisfy numeric annotations
+        assert_eq!(r! { f <- function(x: numeric) x; f(2L) }, r! { 2L });
+
+        assert!(r! { f <- function(x: numeric) x; f("a") }.is_err());
+        assert!(r! { f <- function(x, n: integer) x; f(1, n = 2) }.is_err());
+    }
+
+    #[test]
+    fn annotated_defaults_are_checked() {
+        assert!(r! { f <- function(n: integer = 1.5) n; f() }.is_err());
+        assert_eq!(r! { f <- function(n: integer = 1.5) n; f(2L) }, r! { 2L });
+
+        // defaults may refer to other parameters
+        assert_eq!(r! { f <- function(x, n: integer = x) n; f(2L) }, r! { 2L });
+        assert!(r! { f <- function(x, n: integer = x) n; f(2.5) }.is_err());
+    }
+
+    #[test]
+    fn annotated_return_values_are_checked() {
+        assert_eq!(
+            r! { f <- function(x) -> character paste(x); f(1) },
+            r! { "1" }
+        );
+
+        assert!(r! { f <- function(x) -> character x; f(1) }.is_err());
+    }
+
+    #[test]
+    fn annotations_are_formatted() {
+        assert_eq!(
+            r! { function(x: numeric, n: integer = 1L, ...) -> character x }
+                .unwrap()
+                .to_string()
+                .lines()
+                .next(),
+            Some("function(x: numeric, n: integer = 1L, ...) -> character x")
+        );
+    }
 }
@@ -2,10 +2,14 @@ use r_derive::*;
 
 use super::core::*;
 use crate::context::Context;
+use crate::debugger;
+use crate::err;
 use crate::internal_err;
 use crate::lang::Signal::*;
 use crate::lang::*;
-use crate::object::{ExprList, Obj};
+use crate::object::*;
+use crate::parser::Srcref;
+use std::rc::Rc;
 
 #[derive(Debug, Clone, PartialEq)]
 #[builtin]
@@ -25,7 +29,7 @@ impl Callable for KeywordReturn {
     fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
         let mut args = args.values.into_iter();
         let value = stack.eval(args.next().unwrap())?;
-        Return(value, true).into()
+        Return(Box::new(value), true).into()
     }
 }
 
@@ -209,9 +213,18 @@ impl Callable for KeywordRepeat {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 #[builtin]
-pub struct KeywordBlock;
+pub struct KeywordBlock {
+    pub srcref: Srcref,
+}
+
+// blocks are equal regardless of where they were parsed
+impl PartialEq for KeywordBlock {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
 
 impl Format for KeywordBlock {
     fn rfmt_call_with(&self, _state: FormatState, args: &ExprList) -> String {
@@ -236,6 +249,7 @@ impl Callable for KeywordBlock {
         let n = args.values.len().saturating_sub(1);
 
         for (i, expr) in args.values.into_iter().enumerate() {
+            debugger::at_expr(&expr, &self.srcref, i, stack)?;
             value = match i {
                 i if i == n => return Tail(expr, true).into(),
                 _ => stack.eval_and_finalize(expr)?,
@@ -245,6 +259,291 @@ impl Callable for KeywordBlock {
         Ok(value)
     }
 }
+#[derive(Debug, Clone, PartialEq)]
+#[builtin]
+pub struct KeywordMatch;
+
+impl Format for KeywordMatch {
+    fn rfmt_call_with(&self, _state: FormatState, args: &ExprList) -> String {
+        let arms = args.values[1..]
+            .chunks(3)
+            .map(|arm| match &arm[1] {
+                Expr::Missing => format!("  {} => {}", fmt_pattern(&arm[0]), arm[2]),
+                guard => format!("  {} if {} => {}", fmt_pattern(&arm[0]), guard, arm[2]),
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        format!("match ({}) {{\n{}\n}}", args.values[0], arms)
+    }
+
+    fn rfmt_with(&self, _state: FormatState) -> String {
+        "match".to_string()
+    }
+}
+
+impl Callable for KeywordMatch {
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let mut args = args.values.into_iter();
+        let value = stack.eval(args.next().unwrap())?;
+
+        while let (Some(pattern), Some(guard), Some(body)) = (args.next(), args.next(), args.next())
+        {
+            let mut bindings = vec![];
+            if !destructure(&pattern, &value, &mut bindings)? {
+                continue;
+            }
+
+            // guards see the arm's bindings without binding them in the frame
+            if guard != Expr::Missing {
+                let env = Rc::new(Environment {
+                    parent: Some(stack.env()),
+                    ..Default::default()
+                });
+
+                for (name, value) in bindings.iter() {
+                    env.insert(name.clone(), value.clone());
+                }
+
+                if !Obj::Closure(guard, env).force(stack)?.try_into()? {
+                    continue;
+                }
+            }
+
+            let env = stack.last_frame().env();
+            for (name, value) in bindings {
+                env.insert(name, value);
+            }
+
+            return Tail(body, true).into();
+        }
+
+        err!("no pattern matched the value")
+    }
+}
+
+/// Format a pattern as it would be written in a `match` arm
+fn fmt_pattern(pattern: &Expr) -> String {
+    let elems = |args: &ExprList| {
+        args.clone()
+            .into_iter()
+            .map(|(k, v)| match (k, v) {
+                (Some(k), Expr::Missing) => format!("{k} ="),
+                (Some(k), v) => format!("{k} = {}", fmt_pattern(&v)),
+                (None, Expr::Ellipsis(Some(rest))) => format!("...{rest}"),
+                (None, v) => fmt_pattern(&v),
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+
+    match pattern {
+        Expr::List(args) => format!("({})", elems(args)),
+        Expr::Call(what, args) => match what.as_ref() {
+            Expr::String(class) => format!("{class}({})", elems(args)),
+            _ => format!("[{}]", elems(args)),
+        },
+        other => other.to_string(),
+    }
+}
+
+/// Match a value against a pattern, collecting bindings for its symbols
+///
+/// Patterns are parsed into expressions, where
+///
+/// * `_` matches anything, and any other symbol binds the value
+/// * literal values match length-1 vectors of an equal value
+/// * `(a, name = b, ...rest)` destructures lists by position and name
+/// * `[a, b, ...rest]` destructures atomic vectors by position
+/// * `class(pattern)` matches values inheriting from `class`
+///
+fn destructure(
+    pattern: &Expr,
+    value: &Obj,
+    bindings: &mut Vec<(String, Obj)>,
+) -> Result<bool, Signal> {
+    match pattern {
+        Expr::Symbol(s) if s == "_" => Ok(true),
+        Expr::Symbol(s) => {
+            bindings.push((s.clone(), value.clone()));
+            Ok(true)
+        }
+        Expr::List(patterns) => {
+            let Obj::List(list) = value else {
+                return Ok(false);
+            };
+
+            let items = list.elements();
+            let names = items.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>();
+            let Some((matched, rest)) = destructure_elems(patterns, &names) else {
+                return Ok(false);
+            };
+
+            for (pattern, i) in matched {
+                if !destructure(&pattern, &items[i].1, bindings)? {
+                    return Ok(false);
+                }
+            }
+
+            if let Some((Some(name), indices)) = rest {
+                let rest = indices
+                    .into_iter()
+                    .map(|i| items[i].clone())
+                    .collect::<Vec<_>>();
+                bindings.push((name, Obj::List(List::from(rest))));
+            }
+
+            Ok(true)
+        }
+        Expr::Call(what, patterns) => match what.as_ref() {
+            Expr::String(class) => {
+                if !value.class().contains(class) {
+                    return Ok(false);
+                }
+
+                match patterns.values.first() {
+                    Some(pattern) => destructure(pattern, value, bindings),
+                    None => Ok(true),
+                }
+            }
+            _ => {
+                let Obj::Vector(v) = value else {
+                    return Ok(false);
+                };
+
+                // elements are taken from the values a subset selects
+                let v = v.clone().materialize();
+
+                let Some((matched, rest)) = destructure_elems(patterns, &vec![None; v.len()])
+                else {
+                    return Ok(false);
+                };
+
+                for (pattern, i) in matched {
+                    let Some(x) = v.get(i) else {
+                        return Ok(false);
+                    };
+
+                    if !destructure(&pattern, &Obj::Vector(x), bindings)? {
+                        return Ok(false);
+                    }
+                }
+
+                if let Some((Some(name), indices)) = rest {
+                    let range = match (indices.first(), indices.last()) {
+                        (Some(&start), Some(&end)) => start..end + 1,
+                        _ => 0..0,
+                    };
+
+                    let rest = v.subset(Subset::Range(range)).materialize();
+                    bindings.push((name, Obj::Vector(rest)));
+                }
+
+                Ok(true)
+            }
+        },
+        literal => Ok(literal_matches(literal, value)),
+    }
+}
+
+type ElemMatches = (Vec<(Expr, usize)>, Option<(Option<String>, Vec<usize>)>);
+
+/// Pair element patterns with the indices of the elements they match
+///
+/// Named patterns take the element of the same name, with the remaining
+/// elements matched by position. Elements before and after a `...rest`
+/// pattern are matched from either end, with those in between collected as
+/// the rest.
+fn destructure_elems(patterns: &ExprList, names: &[Option<String>]) -> Option<ElemMatches> {
+    let mut matched = vec![];
+    let mut remaining = (0..names.len()).collect::<Vec<_>>();
+
+    for (k, v) in patterns.clone().into_iter() {
+        let Some(name) = k else { continue };
+        let i = remaining
+            .iter()
+            .position(|&i| names[i].as_ref() == Some(&name))?;
+        let pattern = match v {
+            Expr::Missing => Expr::Symbol(name),
+            pattern => pattern,
+        };
+
+        matched.push((pattern, remaining.remove(i)));
+    }
+
+    let positional = patterns
+        .clone()
+        .into_iter()
+        .filter(|(k, _)| k.is_none())
+        .map(|(_, v)| v)
+        .collect::<Vec<_>>();
+
+    let Some(at) = positional
+        .iter()
+        .position(|v| matches!(v, Expr::Ellipsis(_)))
+    else {
+        if positional.len() != remaining.len() {
+            return None;
+        }
+
+        matched.extend(positional.into_iter().zip(remaining));
+        return Some((matched, None));
+    };
+
+    let (before, after) = (&positional[..at], &positional[at + 1..]);
+    if before.len() + after.len() > remaining.len() {
+        return None;
+    }
+
+    let end = remaining.len() - after.len();
+    matched.extend(before.iter().cloned().zip(remaining[..at].iter().copied()));
+    matched.extend(after.iter().cloned().zip(remaining[end..].iter().copied()));
+
+    let Expr::Ellipsis(rest) = &positional[at] else {
+        unreachable!()
+    };
+
+    Some((matched, Some((rest.clone(), remaining[at..end].to_vec()))))
+}
+
+/// Test whether a literal pattern matches a length-1 value
+fn literal_matches(literal: &Expr, value: &Obj) -> bool {
+    let Obj::Vector(v) = value else {
+        return matches!((literal, value), (Expr::Null, Obj::Null));
+    };
+
+    if v.len() != 1 {
+        return false;
+    }
+
+    // numeric literals match both numeric and integer values
+    let number = match literal {
+        Expr::Number(x) => Some(*x),
+        Expr::Integer(x) => Some(*x as f64),
+        Expr::Inf => Some(f64::INFINITY),
+        _ => None,
+    };
+
+    match (literal, v.clone().materialize()) {
+        (Expr::NA, Vector::Numeric(x)) => matches!(x.inner().borrow()[0], OptionNA::NA),
+        (Expr::NA, Vector::Integer(x)) => matches!(x.inner().borrow()[0], OptionNA::NA),
+        (Expr::NA, Vector::Logical(x)) => matches!(x.inner().borrow()[0], OptionNA::NA),
+        (Expr::NA, Vector::Character(x)) => matches!(x.inner().borrow()[0], OptionNA::NA),
+        (Expr::Bool(b), Vector::Logical(x)) => x.inner().borrow()[0] == OptionNA::Some(*b),
+        (Expr::String(s), Vector::Character(x)) => {
+            x.inner().borrow()[0] == OptionNA::Some(s.clone())
+        }
+        (_, v @ (Vector::Numeric(_) | Vector::Integer(_))) if number.is_some() => {
+            let Vector::Numeric(x) = v.as_numeric() else {
+                return false;
+            };
+
+            x.inner().borrow()[0] == OptionNA::Some(number.unwrap_or_default())
+        }
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::r;
@@ -288,4 +587,121 @@ mod test {
             r! { 6 }
         );
     }
+
+    #[test]
+    fn match_literals() {
+        assert_eq!(
+            r! {{"
+               f <- function(x) match (x) {
+                   NULL => 0,
+                   1 => 1,
+                   \"a\" => 2,
+                   TRUE => 3,
+                   NA => 4
+               }
+               c(f(NULL), f(1L), f(\"a\"), f(TRUE), f(NA))
+            "}},
+            r! { c(0, 1, 2, 3, 4) }
+        );
+    }
+
+    #[test]
+    fn match_destructures_lists() {
+        assert_eq!(r! { match (list(1, 2)) { (a, b) => a + b } }, r! { 3 });
+        assert_eq!(
+            r! { match (list(x = 1, 2, y = 3)) { (y = b, x =, c) => c(x, b, c) } },
+            r! { c(1, 3, 2) }
+        );
+    }
+
+    #[test]
+    fn match_binds_rest() {
+        assert_eq!(
+            r! { match (list(1, 2, 3)) { (a, ...rest) => rest } },
+            r! { list(2, 3) }
+        );
+        assert_eq!(
+            r! { match (c(1, 2, 3)) { [...init, last] => init } },
+            r! { c(1, 2) }
+        );
+    }
+
+    #[test]
+    fn match_destructures_subsets() {
+        assert_eq!(
+            r! { x <- list(1, 2, 3); match (x[2:3]) { (p, q) => p + q, _ => 0 } },
+            r! { 5 }
+        );
+        assert_eq!(
+            r! { match (c(10, 20, 30)[2:3]) { [a, b] => c(a, b) } },
+            r! { c(20, 30) }
+        );
+        assert_eq!(
+            r! { match (c(10, 20, 30)[2:3]) { [...init, last] => init } },
+            r! { 20 }
+        );
+    }
+
+    #[test]
+    fn match_vector_lengths() {
+        assert_eq!(
+            r! {{"
+               f <- function(x) match (x) {
+                   [a] => 1,
+                   [a, b] => 2,
+                   _ => 3
+               }
+               c(f(1), f(c(1, 2)), f(1:5), f(NULL))
+            "}},
+            r! { c(1, 2, 3, 3) }
+        );
+    }
+
+    #[test]
+    fn match_class() {
+        assert_eq!(
+            r! {{"
+               f <- function(x) match (x) {
+                   character(s) => 1,
+                   numeric() => 2,
+                   list() => 3
+               }
+               c(f(\"a\"), f(1), f(list(1)))
+            "}},
+            r! { c(1, 2, 3) }
+        );
+    }
+
+    #[test]
+    fn match_guards() {
+        assert_eq!(
+            r! {{"
+               f <- function(x) match (x) {
+                   n if n < 0 => \"negative\"
+                   n if n > 0 => \"positive\"
+                   _ => \"zero\"
+               }
+               c(f(-1), f(1), f(0))
+            "}},
+            r! { c("negative", "positive", "zero") }
+        );
+    }
+
+    #[test]
+    fn match_only_evaluates_matched_arm() {
+        assert_eq!(
+            r! { match (1) { 1 => "one", _ => stop_here() } },
+            r! { "one" }
+        );
+    }
+
+    #[test]
+    fn match_without_matching_arm() {
+        assert!(r! { match (1) { 2 => 3 } }.is_err());
+    }
+
+    #[test]
+    fn match_calls_are_unaffected() {
+        assert_eq!(r! { match <- function(x, table) x; match(1, 2) }, r! { 1 });
+    }
 }
@@ -2,8 +2,9 @@ use r_derive::*;
 
 use super::core::*;
 use crate::context::Context;
+use crate::err;
 use crate::error::Error;
-use crate::lang::{CallStack, EvalResult};
+use crate::lang::{CallStack, EvalResult, Signal};
 use crate::object::types::*;
 use crate::object::*;
 
@@ -17,6 +18,26 @@ impl Callable for InfixAssign {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "<<-", kind = Infix)]
+pub struct InfixSuperAssign;
+impl Callable for InfixSuperAssign {
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let (lhs, rhs) = args.unnamed_binary_args();
+        stack.assign_lazy_super(lhs, rhs)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "=", kind = Infix)]
+pub struct InfixEqAssign;
+impl Callable for InfixEqAssign {
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let (lhs, rhs) = args.unnamed_binary_args();
+        stack.assign_lazy(lhs, rhs)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 #[builtin(sym = "+", kind = Infix)]
 pub struct InfixAdd;
@@ -47,6 +68,27 @@ impl Callable for PrefixSub {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "~", kind = Infix)]
+pub struct InfixTilde;
+impl Callable for InfixTilde {
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        // formulas are left unevaluated, capturing their environment
+        let formula = Expr::new_primitive_call(InfixTilde, args);
+        Ok(Obj::Formula(formula, stack.env()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "~", kind = Prefix)]
+pub struct PrefixTilde;
+impl Callable for PrefixTilde {
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let formula = Expr::new_primitive_call(PrefixTilde, args);
+        Ok(Obj::Formula(formula, stack.env()))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 #[builtin(sym = "..", kind = Prefix)]
 pub struct PrefixPack;
@@ -96,6 +138,85 @@ impl Callable for InfixMod {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "%in%", kind = Infix)]
+pub struct InfixIn;
+impl Callable for InfixIn {
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let (lhs, rhs) = stack.eval_binary(args.unnamed_binary_args())?;
+
+        // values are matched after coercion to a common character type
+        let Obj::Vector(Vector::Character(x)) = lhs.as_character()? else {
+            return Error::CannotBeCoercedToCharacter.into();
+        };
+
+        let Obj::Vector(Vector::Character(table)) = rhs.as_character()? else {
+            return Error::CannotBeCoercedToCharacter.into();
+        };
+
+        let table = table.inner().borrow().clone();
+        let result = x
+            .inner()
+            .borrow()
+            .iter()
+            .map(|xi| table.contains(xi))
+            .collect::<Vec<_>>();
+
+        Ok(Obj::Vector(Vector::from(result)))
+    }
+}
+
+/// Numeric values of an operand, erroring for non-numeric objects
+fn numeric_values(x: Obj) -> Result<Vec<OptionNA<f64>>, Signal> {
+    match x.as_numeric()? {
+        Obj::Vector(Vector::Numeric(v)) => Ok(v.inner().borrow().clone()),
+        _ => Error::CannotBeCoercedToNumeric.into(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "%o%", kind = Infix)]
+pub struct InfixOuter;
+impl Callable for InfixOuter {
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let (lhs, rhs) = stack.eval_binary(args.unnamed_binary_args())?;
+        let x = numeric_values(lhs)?;
+        let y = numeric_values(rhs)?;
+
+        // without dimensions, the outer product is flattened in column-major
+        // order, with each column corresponding to an element of `y`
+        let result = y
+            .iter()
+            .flat_map(|yj| x.iter().map(|xi| xi.clone() * yj.clone()))
+            .collect::<Vec<_>>();
+
+        Ok(Obj::Vector(Vector::from(result)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "%*%", kind = Infix)]
+pub struct InfixMatMul;
+impl Callable for InfixMatMul {
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let (lhs, rhs) = stack.eval_binary(args.unnamed_binary_args())?;
+        let x = numeric_values(lhs)?;
+        let y = numeric_values(rhs)?;
+
+        // only vectors are supported, for which the product is their inner product
+        if x.len() != y.len() {
+            return Error::Other("non-conformable arguments".to_string()).into();
+        }
+
+        let result = x
+            .into_iter()
+            .zip(y)
+            .fold(OptionNA::Some(0.0), |acc, (xi, yi)| acc + xi * yi);
+
+        Ok(Obj::Vector(Vector::from(vec![result])))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 #[builtin(sym = "||", kind = Infix)]
 pub struct InfixOr;
@@ -225,16 +346,35 @@ impl Callable for InfixPipe {
         use Expr::*;
         match rhs {
             Call(what, mut args) => {
-                args.insert(0, lhs);
+                let is_placeholder = |v: &Expr| matches!(v, Symbol(s) if s == "_");
+                let placeholders = args.values.iter().filter(|v| is_placeholder(v)).count();
+
+                // piped into a named placeholder argument, or as the first argument
+                match placeholders {
+                    0 => {
+                        args.insert(0, lhs);
+                    }
+                    1 => {
+                        let i = args.values.iter().position(is_placeholder).unwrap_or(0);
+                        if args.keys[i].is_none() {
+                            return err!("pipe placeholder can only be used as a named argument");
+                        }
+                        args.values[i] = lhs;
+                    }
+                    _ => return err!("pipe placeholder may only appear once"),
+                }
+
                 let new_expr = Call(what, args);
                 stack.eval(new_expr)
             }
-            s @ Symbol(..) | s @ String(..) => {
+            f @ Symbol(..) | f @ String(..) | f @ Function(..) => {
                 let args = ExprList::from(vec![(None, lhs)]);
-                let new_expr = Call(Box::new(s), args);
+                let new_expr = Call(Box::new(f), args);
                 stack.eval(new_expr)
             }
-            _ => unreachable!(),
+            other => err!(format!(
+                "the pipe operator requires a function call or function as its right-hand side, found '{other}'"
+            )),
         }
     }
 }
@@ -282,7 +422,7 @@ impl Callable for InfixColon {
             }
 
             let mut v = start;
-            return Ok(Obj::Vector(Vector::from(
+            Ok(Obj::Vector(Vector::from(
                 vec![start]
                     .into_iter()
                     .chain(std::iter::repeat_with(|| {
@@ -291,17 +431,17 @@ impl Callable for InfixColon {
                     }))
                     .take_while(|x| if start <= end { x <= &end } else { x >= &end })
                     .collect::<Vec<f64>>(),
-            )));
+            )))
 
         // binary case
         } else {
             let start: i32 = stack.eval(arg1)?.as_integer()?.try_into()?;
             let end: i32 = stack.eval(arg2)?.as_integer()?.try_into()?;
-            return Ok(Obj::Vector(Vector::from(if start <= end {
+            Ok(Obj::Vector(Vector::from(if start <= end {
                 (start..=end).map(|i| i as f64).collect::<Vec<f64>>()
             } else {
                 (end..=start).map(|i| i as f64).rev().collect::<Vec<f64>>()
-            })));
+            })))
         }
     }
 }
@@ -398,3 +538,196 @@ impl Callable for PrimVec {
         super::primitive::PrimitiveC.call(args, stack)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{r, r_expect};
+
+    #[test]
+    fn special_in() {
+        assert_eq!(
+            r! { c(1, 5, 3) %in% c(3L, 2L, 1L) },
+            r! { c(TRUE, FALSE, TRUE) }
+        );
+    }
+
+    #[test]
+    fn special_outer_and_inner_products() {
+        assert_eq!(r! { 1:2 %o% c(1, 10) }, r! { c(1, 2, 10, 20) });
+        assert_eq!(r! { c(1, 2, 3) %*% c(4, 5, 6) }, r! { 32 });
+        assert!(r! { 1:2 %*% 1:3 }.is_err());
+    }
+
+    #[test]
+    fn special_user_defined() {
+        r_expect! {{"
+            `%+%` <- function(l, r) paste(l, r)
+            'a' %+% 'b' %+% 'c' == 'a b c'
+        "}}
+    }
+
+    #[test]
+    fn special_precedence() {
+        // binds tighter than `*`, but looser than `:`
+        assert_eq!(r! { 2 * 1:3 %*% 1:3 }, r! { 28 });
+        assert_eq!(
+            r! {{"`%+%` <- function(l, r) l + r; 2 * 3 %+% 4"}},
+            r! { 14 }
+        );
+    }
+
+    #[test]
+    fn special_deparse() {
+        use crate::parser::{Localization, LocalizedParser};
+        let expr = Localization::En.parse_input("x %in% y").unwrap();
+        assert_eq!(expr.to_string(), "x %in% y");
+    }
+
+    #[test]
+    fn pipe_into_first_argument() {
+        assert_eq!(r! { 1:3 |> sum() }, r! { 6 });
+        assert_eq!(r! { "a" |> paste("b") }, r! { "a b" });
+    }
+
+    #[test]
+    fn pipe_named_placeholder() {
+        assert!(r! { "a" |> paste("b", _) }.is_err());
+        assert_eq!(
+            r! {{"f <- function(x, data) data - x; 10 |> f(1, data = _)"}},
+            r! { 9 }
+        );
+        assert!(r! {{"f <- function(x, y) x; 1 |> f(x = _, y = _)"}}.is_err());
+    }
+
+    #[test]
+    fn pipe_into_lambdas() {
+        assert_eq!(r! { 2 |> fn(x) x * 10 }, r! { 20 });
+        assert_eq!(r! {{"2 |> \\(x) x + 1"}}, r! { 3 });
+        assert_eq!(r! {{"2 |> (\\(x) x ^ 2)()"}}, r! { 4 });
+    }
+
+    #[test]
+    fn pipe_unsupported_rhs_errors() {
+        assert!(r! { 1 |> 2 }.is_err());
+    }
+
+    #[test]
+    fn super_assignment() {
+        r_expect! {{"
+            x <- 1
+            f <- function() { x <<- 2; x <- 3 }
+            f()
+            x == 2
+        "}}
+    }
+
+    #[test]
+    fn super_assignment_nearest_enclosing() {
+        r_expect! {{"
+            counter <- function() { n <- 0; function() { n <<- n + 1; n } }
+            count <- counter()
+            count(); count()
+            count() == 3
+        "}}
+    }
+
+    #[test]
+    fn super_assignment_defaults_to_global() {
+        r_expect! {{"
+            f <- function() { y <<- 10 }
+            f()
+            y == 10
+        "}}
+    }
+
+    #[test]
+    fn right_assignment() {
+        assert_eq!(r! { 1 + 2 -> x; x }, r! { 3 });
+        r_expect! {{"
+            f <- function() { 4 ->> z }
+            f()
+            z == 4
+        "}}
+    }
+
+    #[test]
+    fn equals_assignment() {
+        assert_eq!(r! { x = 3; x * 2 }, r! { 6 });
+        assert_eq!(r! { f <- function(a, b) a - b; f(b = 1, a = 3) }, r! { 2 });
+        assert_eq!(r! { x = 1; x == 1 }, r! { TRUE });
+    }
+
+    #[test]
+    fn destructuring_assignment() {
+        assert_eq!(r! { (a, b) <- (1, 2); a + b }, r! { 3 });
+        assert_eq!(r! { (a, b) <- c(3, 4); b }, r! { 4 });
+        assert_eq!(r! { (a = x, b) <- (y = 1, x = 2); c(a, b) }, r! { c(2, 1) });
+    }
+
+    #[test]
+    fn destructuring_nested() {
+        assert_eq!(
+            r! { (a, (b, c)) <- (1, (2, 3)); c(a, b, c) },
+            r! { c(1, 2, 3) }
+        );
+    }
+
+    #[test]
+    fn destructuring_defaults() {
+        assert_eq!(r! { (a, b = 10) <- list(1); c(a, b) }, r! { c(1, 10) });
+        assert_eq!(r! { (a, b = 10) <- (1, 2); c(a, b) }, r! { c(1, 2) });
+        assert_eq!(r! {{ r#"(a, b = "none") <- list(1); b"# }}, r! { "none" });
+    }
+
+    #[test]
+    fn destructuring_defaults_from_variables() {
+        // a bare symbol extracts by name, so a default taken from a variable
+        // is any other expression
+        assert_eq!(
+            r! { y <- 5; (a, b = {y}) <- list(1); c(a, b) },
+            r! { c(1, 5) }
+        );
+        assert_eq!(
+            r! { y <- 5; (a, b = {y}) <- (1, 2); c(a, b) },
+            r! { c(1, 2) }
+        );
+        assert!(r! { y <- 5; (a, b = y) <- list(1) }.is_err());
+    }
+
+    #[test]
+    fn destructuring_subsets() {
+        assert_eq!(
+            r! { x <- list(1, 2, 3); (a, b) <- x[2:3]; c(a, b) },
+            r! { c(2, 3) }
+        );
+        assert_eq!(
+            r! { (a, b) <- c(10, 20, 30)[2:3]; c(a, b) },
+            r! { c(20, 30) }
+        );
+    }
+
+    #[test]
+    fn destructuring_arity_errors() {
+        assert!(r! { (a, b) <- list(1) }.is_err());
+        assert!(r! { (a, b) <- (1, 2, 3) }.is_err());
+        assert!(r! { (a = missing) <- (x = 1) }.is_err());
+    }
+
+    #[test]
+    fn destructuring_super_assignment() {
+        r_expect! {{"
+            f <- function() { (a, b) <<- (1, 2) }
+            f()
+            a + b == 3
+        "}}
+    }
+
+    #[cfg(feature = "rest-args")]
+    #[test]
+    fn destructuring_rest() {
+        assert_eq!(r! {{"(first, ..rest) <- (1, 2, 3); rest"}}, r! { (2, 3) });
+        assert_eq!(r! {{"(first, ..rest) <- c(1, 2, 3); rest"}}, r! { c(2, 3) });
+        assert_eq!(r! {{"(..init, last) <- c(1, 2, 3); init"}}, r! { c(1, 2) });
+        assert_eq!(r! {{"(a, ..rest) <- list(1); rest"}}, r! { list() });
+    }
+}
@@ -63,88 +63,96 @@ impl Callable for PrimitiveC {
                 (v @ Vector::Logical(_), _) => v,
             });
 
+        // classed vectors, such as dates, keep the attributes of the first
+        let attrs = match vals.values.borrow().iter().find(|(_, v)| *v != Obj::Null) {
+            Some((_, Obj::Vector(v @ Vector::Numeric(_)))) if v.attributes().class().is_some() => {
+                Some(v.attributes().clone())
+            }
+            _ => None,
+        };
+
         // consume values and merge into a new collection
-        match ret {
-            Vector::Character(v) => Ok(Obj::Vector(Vector::from(
-                v.inner()
-                    .clone()
-                    .borrow_mut()
-                    .clone()
-                    .into_iter()
-                    .chain(
-                        vals.values
+        let result =
+            match ret {
+                Vector::Character(v) => {
+                    Ok(Obj::Vector(Vector::from(
+                        v.inner()
+                            .clone()
                             .borrow_mut()
                             .clone()
                             .into_iter()
-                            .flat_map(|(_, i)| match i.as_character() {
-                                Ok(Obj::Vector(Vector::Character(v))) => {
-                                    v.inner().clone().borrow().clone().into_iter()
-                                }
-                                _ => unreachable!(),
-                            }),
-                    )
-                    .collect::<Vec<Character>>(),
-            ))),
-            Vector::Numeric(v) => Ok(Obj::Vector(Vector::from(
-                v.inner()
-                    .clone()
-                    .borrow_mut()
-                    .clone()
-                    .into_iter()
-                    .chain(
-                        vals.values
+                            .chain(vals.values.borrow_mut().clone().into_iter().flat_map(
+                                |(_, i)| match i.as_character() {
+                                    Ok(Obj::Vector(Vector::Character(v))) => {
+                                        v.inner().clone().borrow().clone().into_iter()
+                                    }
+                                    _ => unreachable!(),
+                                },
+                            ))
+                            .collect::<Vec<Character>>(),
+                    )))
+                }
+                Vector::Numeric(v) => {
+                    Ok(Obj::Vector(Vector::from(
+                        v.inner()
+                            .clone()
                             .borrow_mut()
                             .clone()
                             .into_iter()
-                            .flat_map(|(_, i)| match i.as_numeric() {
-                                Ok(Obj::Vector(Vector::Numeric(v))) => {
-                                    v.inner().clone().borrow().clone().into_iter()
-                                }
-                                _ => unreachable!(),
-                            }),
-                    )
-                    .collect::<Vec<Numeric>>(),
-            ))),
-            Vector::Integer(v) => Ok(Obj::Vector(Vector::from(
-                v.inner()
-                    .clone()
-                    .borrow_mut()
-                    .clone()
-                    .into_iter()
-                    .chain(
-                        vals.values
+                            .chain(vals.values.borrow_mut().clone().into_iter().flat_map(
+                                |(_, i)| match i.as_numeric() {
+                                    Ok(Obj::Vector(Vector::Numeric(v))) => {
+                                        v.inner().clone().borrow().clone().into_iter()
+                                    }
+                                    _ => unreachable!(),
+                                },
+                            ))
+                            .collect::<Vec<Numeric>>(),
+                    )))
+                }
+                Vector::Integer(v) => {
+                    Ok(Obj::Vector(Vector::from(
+                        v.inner()
+                            .clone()
                             .borrow_mut()
                             .clone()
                             .into_iter()
-                            .flat_map(|(_, i)| match i.as_integer() {
-                                Ok(Obj::Vector(Vector::Integer(v))) => {
-                                    v.inner().clone().borrow().clone().into_iter()
-                                }
-                                _ => unreachable!(),
-                            }),
-                    )
-                    .collect::<Vec<Integer>>(),
-            ))),
-            Vector::Logical(v) => Ok(Obj::Vector(Vector::from(
-                v.inner()
-                    .clone()
-                    .borrow_mut()
-                    .clone()
-                    .into_iter()
-                    .chain(
-                        vals.values
+                            .chain(vals.values.borrow_mut().clone().into_iter().flat_map(
+                                |(_, i)| match i.as_integer() {
+                                    Ok(Obj::Vector(Vector::Integer(v))) => {
+                                        v.inner().clone().borrow().clone().into_iter()
+                                    }
+                                    _ => unreachable!(),
+                                },
+                            ))
+                            .collect::<Vec<Integer>>(),
+                    )))
+                }
+                Vector::Logical(v) => {
+                    Ok(Obj::Vector(Vector::from(
+                        v.inner()
+                            .clone()
                             .borrow_mut()
                             .clone()
                             .into_iter()
-                            .flat_map(|(_, i)| match i.as_logical() {
-                                Ok(Obj::Vector(Vector::Logical(v))) => {
-                                    v.inner().clone().borrow().clone().into_iter()
-                                }
-                                _ => unreachable!(),
-                            }),
-                    )
-                    .collect::<Vec<Logical>>(),
-            ))),
+                            .chain(vals.values.borrow_mut().clone().into_iter().flat_map(
+                                |(_, i)| match i.as_logical() {
+                                    Ok(Obj::Vector(Vector::Logical(v))) => {
+                                        v.inner().clone().borrow().clone().into_iter()
+                                    }
+                                    _ => unreachable!(),
+                                },
+                            ))
+                            .collect::<Vec<Logical>>(),
+                    )))
+                }
+            };
+
+        match (result, attrs) {
+            (Ok(Obj::Vector(v @ Vector::Numeric(_))), Some(attrs)) => {
+                Ok(Obj::Vector(v.with_attributes(attrs)))
+            }
+            (result, _) => result,
         }
     }
 }
@@ -1,7 +1,8 @@
 use r_derive::builtin;
 
 use crate::callable::core::*;
-use crate::lang::{CallStack, EvalResult};
+use crate::error::Error;
+use crate::lang::{CallStack, EvalResult, Frame, Signal};
 use crate::object::*;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -19,3 +20,157 @@ impl Callable for PrimitiveCallstack {
         )))
     }
 }
+
+/// Frames of calls that led to the current builtin call
+///
+/// Excludes both the global frame and the frame introduced by the calling
+/// builtin itself.
+fn caller_frames(stack: &CallStack) -> Vec<&Frame> {
+    let n = stack.frames.len().saturating_sub(1) as i32;
+    (1..n).filter_map(|i| stack.frame(i)).collect()
+}
+
+/// Evaluate a single integer argument, falling back to a default
+fn integer_arg(name: &str, args: List, stack: &mut CallStack) -> Result<i32, Signal> {
+    let mut args = Obj::List(args);
+    args.try_get_named(name)?.force(stack)?.try_into()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "sys.call")]
+pub struct PrimitiveSysCall;
+impl Callable for PrimitiveSysCall {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("which")), Expr::Integer(0))])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let which = integer_arg("which", args, stack)?;
+
+        // relative frames are counted from the caller, skipping this call's frame
+        let frame = match which {
+            i if i <= 0 => stack.frame(i - 1),
+            i => stack.frame(i),
+        };
+
+        match frame {
+            Some(Frame {
+                call: Expr::Null, ..
+            })
+            | None => Ok(Obj::Null),
+            Some(frame) => Ok(Obj::Expr(frame.call.clone())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "sys.calls")]
+pub struct PrimitiveSysCalls;
+impl Callable for PrimitiveSysCalls {
+    fn call(&self, _args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let calls = caller_frames(stack)
+            .into_iter()
+            .map(|f| (None, Obj::Expr(f.call.clone())))
+            .collect::<Vec<_>>();
+
+        if calls.is_empty() {
+            return Ok(Obj::Null);
+        }
+
+        Ok(Obj::List(List::from(calls)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "sys.frames")]
+pub struct PrimitiveSysFrames;
+impl Callable for PrimitiveSysFrames {
+    fn call(&self, _args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let envs = caller_frames(stack)
+            .into_iter()
+            .map(|f| (None, Obj::Environment(f.env.clone())))
+            .collect::<Vec<_>>();
+
+        if envs.is_empty() {
+            return Ok(Obj::Null);
+        }
+
+        Ok(Obj::List(List::from(envs)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "parent.frame")]
+pub struct PrimitiveParentFrame;
+impl Callable for PrimitiveParentFrame {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("n")), Expr::Integer(1))])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let n = integer_arg("n", args, stack)?;
+
+        if n < 1 {
+            return Error::ArgumentInvalid(String::from("n")).into();
+        }
+
+        // skip this call's frame and the frame of the function calling it,
+        // falling back to the global environment when we run out of frames
+        match stack.frame(-n - 1) {
+            Some(frame) => Ok(Obj::Environment(frame.env.clone())),
+            None => Ok(Obj::Environment(stack.frames[0].env.clone())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{r, r_expect};
+
+    #[test]
+    fn sys_call_returns_calling_function_call() {
+        assert_eq!(
+            r! { f <- function(x) sys.call(); f(1 + 2) },
+            r! { quote(f(1 + 2)) }
+        )
+    }
+
+    #[test]
+    fn sys_call_at_top_level() {
+        assert_eq!(r! { sys.call() }, r! { null })
+    }
+
+    #[test]
+    fn sys_calls_lists_nested_calls() {
+        assert_eq!(
+            r! {{"
+                f <- function() g()
+                g <- function() sys.calls()
+                f()
+            "}},
+            r! { list(quote(f()), quote(g())) }
+        )
+    }
+
+    #[test]
+    fn sys_frames_lists_nested_envs() {
+        r_expect! {{"
+            f <- function() { e <- environment(); g <- function() sys.frames(); x <- g(); x[[1]] == e }
+            f()
+        "}}
+    }
+
+    #[test]
+    fn parent_frame_is_calling_env() {
+        r_expect! {{"
+            f <- function() parent.frame()
+            g <- function() { e <- environment(); f() == e }
+            g()
+        "}}
+    }
+
+    #[test]
+    fn parent_frame_at_top_level_is_global() {
+        r_expect! { parent.frame() == environment() }
+    }
+}
@@ -0,0 +1,173 @@
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::connection::{self, Connection, Mode};
+use crate::err;
+use crate::handle::External;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+
+pub fn connection_obj(connection: Connection) -> Obj {
+    Obj::External(External::new(connection))
+}
+
+/// The id of a connection given as an argument
+fn connection_id(x: &Obj) -> Result<usize, Signal> {
+    match x {
+        Obj::External(x) => x
+            .downcast::<Connection>()
+            .map_or(err!("invalid connection"), |c| Ok(c.id())),
+        _ => err!("invalid connection"),
+    }
+}
+
+/// A connection given as an argument, either as a connection or the name of
+/// a file, where `""` refers to a default connection
+///
+/// Connections to files given by name are only used for a single call, and
+/// are returned such that they are closed as they are dropped afterwards.
+fn connection_arg(x: &Obj, default: usize) -> Result<(usize, Option<Connection>), Signal> {
+    match x {
+        Obj::Vector(Vector::Character(_)) => {
+            let path: String = x.clone().try_into()?;
+            match path.as_str() {
+                "" => Ok((default, None)),
+                path => {
+                    let connection = connection::file(path);
+                    Ok((connection.id(), Some(connection)))
+                }
+            }
+        }
+        x => Ok((connection_id(x)?, None)),
+    }
+}
+
+/// Use a connection given as an argument, closing it afterwards if it was
+/// only created for this use
+pub fn with_connection<T, F>(x: Obj, default: usize, f: F) -> Result<T, Signal>
+where
+    F: FnOnce(usize) -> Result<T, Signal>,
+{
+    // the argument is held until the connection is used, as it may hold the
+    // only reference to the connection
+    let (id, _temporary) = connection_arg(&x, default)?;
+    f(id)
+}
+
+fn open_arg(x: Obj) -> Result<Option<Mode>, Signal> {
+    let mode: String = x.try_into()?;
+    match mode.as_str() {
+        "" => Ok(None),
+        mode => Ok(Some(Mode::try_from(mode)?)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "file")]
+pub struct PrimitiveFile;
+impl Callable for PrimitiveFile {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("description")), Expr::Missing),
+            (Some(String::from("open")), Expr::String(String::new())),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let path: String = args
+            .try_get_named("description")?
+            .force(stack)?
+            .try_into()?;
+        let mode = open_arg(args.try_get_named("open")?.force(stack)?)?;
+
+        let connection = connection::file(&path);
+        if let Some(mode) = mode {
+            connection::open(connection.id(), mode)?;
+        }
+
+        Ok(connection_obj(connection))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "textConnection")]
+pub struct PrimitiveTextConnection;
+impl Callable for PrimitiveTextConnection {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("object")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let lines: Vec<String> = args.try_get_named("object")?.force(stack)?.try_into()?;
+
+        // text connections are opened for reading as they are created
+        let connection = connection::text(lines);
+        connection::open(connection.id(), Mode::Read)?;
+        Ok(connection_obj(connection))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "stdin")]
+pub struct PrimitiveStdin;
+impl Callable for PrimitiveStdin {
+    fn call(&self, _args: ExprList, _stack: &mut CallStack) -> EvalResult {
+        Ok(connection_obj(connection::standard(connection::STDIN)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "stdout")]
+pub struct PrimitiveStdout;
+impl Callable for PrimitiveStdout {
+    fn call(&self, _args: ExprList, _stack: &mut CallStack) -> EvalResult {
+        Ok(connection_obj(connection::standard(connection::STDOUT)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "stderr")]
+pub struct PrimitiveStderr;
+impl Callable for PrimitiveStderr {
+    fn call(&self, _args: ExprList, _stack: &mut CallStack) -> EvalResult {
+        Ok(connection_obj(connection::standard(connection::STDERR)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "open")]
+pub struct PrimitiveOpen;
+impl Callable for PrimitiveOpen {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("con")), Expr::Missing),
+            (Some(String::from("open")), Expr::String(String::from("r"))),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let id = connection_id(&args.try_get_named("con")?.force(stack)?)?;
+        let mode = open_arg(args.try_get_named("open")?.force(stack)?)?;
+        connection::open(id, mode.unwrap_or(Mode::Read))?;
+        Ok(Obj::Null)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "close")]
+pub struct PrimitiveClose;
+impl Callable for PrimitiveClose {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("con")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let id = connection_id(&args.try_get_named("con")?.force(stack)?)?;
+        connection::close(id)?;
+        Ok(Obj::Null)
+    }
+}
@@ -0,0 +1,234 @@
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::debugger;
+use crate::err;
+use crate::error::Error;
+use crate::lang::{CallStack, EvalResult};
+use crate::object::*;
+use crate::warning;
+
+/// Evaluate the `fun` argument, which must be a closure
+fn closure_arg(args: List, stack: &mut CallStack) -> EvalResult {
+    let mut args = Obj::List(args);
+    match args.try_get_named("fun")?.force(stack)? {
+        Obj::Function(_, Expr::Primitive(_), ..) => err!("argument must be a closure"),
+        f @ Obj::Function(..) => Ok(f),
+        _ => err!("argument must be a function"),
+    }
+}
+
+fn fun_formals() -> ExprList {
+    ExprList::from(vec![(Some(String::from("fun")), Expr::Missing)])
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "browser")]
+pub struct PrimitiveBrowser;
+impl Callable for PrimitiveBrowser {
+    fn call(&self, _args: ExprList, stack: &mut CallStack) -> EvalResult {
+        match stack.frame(-1) {
+            Some(frame) if stack.frames.len() > 2 => println!("Called from: {}", frame.call),
+            _ => println!("Called from: top level"),
+        }
+
+        debugger::browse(stack)?;
+        Ok(Obj::Null)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "debug")]
+pub struct PrimitiveDebug;
+impl Callable for PrimitiveDebug {
+    fn formals(&self) -> ExprList {
+        fun_formals()
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        debugger::debug(closure_arg(args, stack)?, false);
+        Ok(Obj::Null)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "debugonce")]
+pub struct PrimitiveDebugonce;
+impl Callable for PrimitiveDebugonce {
+    fn formals(&self) -> ExprList {
+        fun_formals()
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        debugger::debug(closure_arg(args, stack)?, true);
+        Ok(Obj::Null)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "undebug")]
+pub struct PrimitiveUndebug;
+impl Callable for PrimitiveUndebug {
+    fn formals(&self) -> ExprList {
+        fun_formals()
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let f = closure_arg(args, stack)?;
+        if !debugger::undebug(&f) {
+            warning::warn("argument is not being debugged")?;
+        }
+
+        Ok(Obj::Null)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "isdebugged")]
+pub struct PrimitiveIsdebugged;
+impl Callable for PrimitiveIsdebugged {
+    fn formals(&self) -> ExprList {
+        fun_formals()
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let f = closure_arg(args, stack)?;
+        Ok(Obj::Vector(Vector::from(vec![debugger::is_debugged(&f)])))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "setBreakpoint")]
+pub struct PrimitiveSetBreakpoint;
+impl Callable for PrimitiveSetBreakpoint {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("srcfile")), Expr::Missing),
+            (Some(String::from("line")), Expr::Missing),
+            (Some(String::from("clear")), Expr::Bool(false)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let srcfile: String = args.try_get_named("srcfile")?.force(stack)?.try_into()?;
+        let line: i32 = args.try_get_named("line")?.force(stack)?.try_into()?;
+        let clear: bool = args.try_get_named("clear")?.force(stack)?.try_into()?;
+
+        if line < 1 {
+            return Error::ArgumentInvalid(String::from("line")).into();
+        }
+
+        debugger::set_breakpoint(&srcfile, line as usize, clear);
+        Ok(Obj::Null)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::debugger;
+    use crate::lang::{Cond, Signal};
+    use crate::object::Obj;
+    use crate::{r, r_expect};
+
+    /// Script browser input, recording the prompts that were shown
+    fn browser_input(lines: &[&str]) -> Rc<RefCell<Vec<String>>> {
+        let prompts = Rc::new(RefCell::new(vec![]));
+        let recorded = prompts.clone();
+        let mut lines = lines.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+        lines.reverse();
+
+        debugger::set_input(Some(Box::new(move |prompt: &str| {
+            recorded.borrow_mut().push(prompt.to_string());
+            lines.pop()
+        })));
+
+        prompts
+    }
+
+    #[test]
+    fn browser_evaluates_in_calling_frame() {
+        browser_input(&["x <- x * 10", "c"]);
+        assert_eq!(
+            r! { f <- function(x) { browser(); x + 1 }; f(1) },
+            r! { 11 }
+        );
+    }
+
+    #[test]
+    fn browser_input_is_parsed_using_the_locale() {
+        browser_input(&["si (x > 0) x <- 10", "c"]);
+        crate::repl::eval("options(locale = 'es')").unwrap();
+        assert_eq!(
+            crate::repl::eval("f <- función(x) { browser(); x + 1 }; f(1)"),
+            r! { 11 }
+        );
+    }
+
+    #[test]
+    fn browser_quit_aborts_evaluation() {
+        browser_input(&["Q"]);
+        assert_eq!(
+            r! { f <- function() { browser(); 1 }; f() },
+            Err(Signal::Condition(Cond::Abort))
+        );
+    }
+
+    #[test]
+    fn debug_steps_through_each_expression() {
+        let prompts = browser_input(&["n", "n", "n", "n"]);
+        r_expect! {{"
+            f <- function() { a <- 1; b <- 2; a + b }
+            debug(f)
+            f() == 3
+        "}};
+
+        // once on entry, then before each of the three expressions
+        assert_eq!(prompts.borrow().len(), 4);
+    }
+
+    #[test]
+    fn debugonce_only_pauses_once() {
+        let prompts = browser_input(&["c", "c"]);
+        r_expect! {{"
+            f <- function(x) x
+            debugonce(f)
+            f(1) == f(1)
+        "}};
+
+        assert_eq!(prompts.borrow().len(), 1);
+    }
+
+    #[test]
+    fn undebug_removes_flag() {
+        r_expect! {{"
+            f <- function(x) x
+            debug(f)
+            a <- isdebugged(f)
+            undebug(f)
+            a && isdebugged(f) == FALSE
+        "}}
+    }
+
+    #[test]
+    fn step_enters_called_functions() {
+        let prompts = browser_input(&["s", "c", "c"]);
+        r_expect! {{"
+            g <- function() 2
+            f <- function() { browser(); g() }
+            f() == 2
+        "}};
+
+        // browser(), then on entering g
+        assert_eq!(prompts.borrow().len(), 2);
+    }
+
+    #[test]
+    fn primitives_cannot_be_debugged() {
+        assert!(r! { debug(paste) }.is_err());
+        assert_eq!(r! { debug(function(x) x) }, Ok(Obj::Null));
+    }
+}
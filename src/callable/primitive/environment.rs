@@ -30,7 +30,7 @@ impl Callable for PrimitiveEnvironment {
         // otherwise we can evaluate value and return result's environment
         match fun.force(stack)? {
             Obj::Closure(_, e) => Ok(Obj::Environment(e.clone())),
-            Obj::Function(_, _, e) => Ok(Obj::Environment(e.clone())),
+            Obj::Function(_, _, e, _) => Ok(Obj::Environment(e.clone())),
             Obj::Environment(e) => Ok(Obj::Environment(e.clone())),
             _ => Error::ArgumentInvalid(String::from("fun")).into(),
         }
@@ -0,0 +1,472 @@
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use r_derive::builtin;
+use rand::Rng;
+use regex::Regex;
+
+use crate::callable::core::*;
+use crate::err;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+use crate::warning;
+
+/// The paths given by each argument in `...`, concatenated
+fn paths(ellipsis: List, stack: &mut CallStack) -> Result<Vec<String>, Signal> {
+    let mut paths = vec![];
+    for (_, x) in ellipsis.elements() {
+        let x: Vec<String> = x.force(stack)?.try_into()?;
+        paths.extend(x);
+    }
+
+    Ok(paths)
+}
+
+/// A logical argument which may be `NA`
+fn tristate(x: Obj) -> Result<Option<bool>, Signal> {
+    let Obj::Vector(Vector::Logical(v)) = x.as_logical()? else {
+        return err!("argument must be a logical value");
+    };
+
+    match v.inner().borrow().first() {
+        Some(OptionNA::Some(x)) => Ok(Some(*x)),
+        _ => Ok(None),
+    }
+}
+
+fn basename(path: &str) -> String {
+    let path = path.trim_end_matches('/');
+    path.rsplit('/').next().unwrap_or_default().to_string()
+}
+
+fn dirname(path: &str) -> String {
+    let trimmed = path.trim_end_matches('/');
+    match trimmed.rsplit_once('/') {
+        // paths at the root, including the root itself
+        Some(("", _)) => "/".to_string(),
+        None if trimmed.is_empty() && !path.is_empty() => "/".to_string(),
+        Some((dir, _)) => dir.trim_end_matches('/').to_string(),
+        None => ".".to_string(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "file.exists")]
+pub struct PrimitiveFileExists;
+impl Callable for PrimitiveFileExists {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(None, Expr::Ellipsis(None))])
+    }
+
+    fn call_matched(&self, _args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let exists: Vec<bool> = paths(ellipsis, stack)?
+            .iter()
+            .map(|path| Path::new(path).exists())
+            .collect();
+
+        Ok(Obj::Vector(Vector::from(exists)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "file.remove")]
+pub struct PrimitiveFileRemove;
+impl Callable for PrimitiveFileRemove {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(None, Expr::Ellipsis(None))])
+    }
+
+    fn call_matched(&self, _args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut removed = vec![];
+        for path in paths(ellipsis, stack)? {
+            match fs::remove_file(&path) {
+                Ok(_) => removed.push(true),
+                Err(e) => {
+                    warning::warn(format!("cannot remove file '{path}', reason '{e}'"))?;
+                    removed.push(false);
+                }
+            }
+        }
+
+        Ok(Obj::Vector(Vector::from(removed)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "dir.create")]
+pub struct PrimitiveDirCreate;
+impl Callable for PrimitiveDirCreate {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("path")), Expr::Missing),
+            (Some(String::from("showWarnings")), Expr::Bool(true)),
+            (Some(String::from("recursive")), Expr::Bool(false)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let path: String = args.try_get_named("path")?.force(stack)?.try_into()?;
+        let show_warnings: bool = args
+            .try_get_named("showWarnings")?
+            .force(stack)?
+            .try_into()?;
+        let recursive: bool = args.try_get_named("recursive")?.force(stack)?.try_into()?;
+
+        let result = match recursive {
+            true => fs::create_dir_all(&path),
+            false => fs::create_dir(&path),
+        };
+
+        match result {
+            Ok(_) => Ok(Obj::Vector(Vector::from(vec![true]))),
+            Err(e) => {
+                if show_warnings {
+                    warning::warn(format!("cannot create dir '{path}', reason '{e}'"))?;
+                }
+
+                Ok(Obj::Vector(Vector::from(vec![false])))
+            }
+        }
+    }
+}
+
+/// Collect the entries of a directory, as paths relative to the directory
+fn list_dir(
+    dir: &Path,
+    prefix: &str,
+    recursive: bool,
+    all: bool,
+    entries: &mut Vec<String>,
+) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !all && name.starts_with('.') {
+            continue;
+        }
+
+        let relative = match prefix {
+            "" => name,
+            prefix => format!("{prefix}/{name}"),
+        };
+
+        // when listing recursively, only files are listed
+        if recursive && entry.file_type()?.is_dir() {
+            list_dir(&entry.path(), &relative, recursive, all, entries)?;
+        } else {
+            entries.push(relative);
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "list.files")]
+pub struct PrimitiveListFiles;
+impl Callable for PrimitiveListFiles {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("path")), Expr::String(String::from("."))),
+            (Some(String::from("pattern")), Expr::Null),
+            (Some(String::from("all.files")), Expr::Bool(false)),
+            (Some(String::from("full.names")), Expr::Bool(false)),
+            (Some(String::from("recursive")), Expr::Bool(false)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let dirs: Vec<String> = args.try_get_named("path")?.force(stack)?.try_into()?;
+        let pattern = match args.try_get_named("pattern")?.force(stack)? {
+            Obj::Null => None,
+            pattern => {
+                let pattern: String = pattern.try_into()?;
+                match Regex::new(&pattern) {
+                    Ok(re) => Some(re),
+                    Err(_) => return err!(format!("invalid regular expression '{pattern}'")),
+                }
+            }
+        };
+        let all: bool = args.try_get_named("all.files")?.force(stack)?.try_into()?;
+        let full_names: bool = args.try_get_named("full.names")?.force(stack)?.try_into()?;
+        let recursive: bool = args.try_get_named("recursive")?.force(stack)?.try_into()?;
+
+        let mut files = vec![];
+        for dir in dirs {
+            // directories which cannot be read contribute no files
+            let mut entries = vec![];
+            if list_dir(Path::new(&dir), "", recursive, all, &mut entries).is_err() {
+                continue;
+            }
+
+            // patterns are matched against file names only
+            entries.retain(|entry| {
+                pattern
+                    .as_ref()
+                    .is_none_or(|p| p.is_match(&basename(entry)))
+            });
+
+            files.extend(entries.into_iter().map(|entry| match full_names {
+                true => format!("{}/{entry}", dir.trim_end_matches('/')),
+                false => entry,
+            }));
+        }
+
+        files.sort();
+        Ok(Obj::Vector(Vector::from(files)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "file.path")]
+pub struct PrimitiveFilePath;
+impl Callable for PrimitiveFilePath {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (None, Expr::Ellipsis(None)),
+            (Some(String::from("fsep")), Expr::String(String::from("/"))),
+        ])
+    }
+
+    fn call_matched(&self, args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        // parameters following the ellipsis are only matched when named
+        let fsep: String = match args.get_named("fsep") {
+            Some(fsep) => fsep.force(stack)?.try_into()?,
+            None => String::from("/"),
+        };
+
+        let mut parts: Vec<Vec<String>> = vec![];
+        for (_, x) in ellipsis.elements() {
+            parts.push(x.force(stack)?.try_into()?);
+        }
+
+        // components are recycled, with any empty component giving no paths
+        let n = match parts.iter().any(|p| p.is_empty()) {
+            true => 0,
+            false => parts.iter().map(|p| p.len()).max().unwrap_or(0),
+        };
+
+        let paths: Vec<String> = (0..n)
+            .map(|i| {
+                parts
+                    .iter()
+                    .map(|p| p[i % p.len()].as_str())
+                    .collect::<Vec<_>>()
+                    .join(&fsep)
+            })
+            .collect();
+
+        Ok(Obj::Vector(Vector::from(paths)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "basename")]
+pub struct PrimitiveBasename;
+impl Callable for PrimitiveBasename {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("path")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let paths: Vec<String> = args.try_get_named("path")?.force(stack)?.try_into()?;
+        let names: Vec<String> = paths.iter().map(|p| basename(p)).collect();
+        Ok(Obj::Vector(Vector::from(names)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "dirname")]
+pub struct PrimitiveDirname;
+impl Callable for PrimitiveDirname {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("path")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let paths: Vec<String> = args.try_get_named("path")?.force(stack)?.try_into()?;
+        let dirs: Vec<String> = paths.iter().map(|p| dirname(p)).collect();
+        Ok(Obj::Vector(Vector::from(dirs)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "normalizePath")]
+pub struct PrimitiveNormalizePath;
+impl Callable for PrimitiveNormalizePath {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("path")), Expr::Missing),
+            (Some(String::from("mustWork")), Expr::NA),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let paths: Vec<String> = args.try_get_named("path")?.force(stack)?.try_into()?;
+        let must_work = tristate(args.try_get_named("mustWork")?.force(stack)?)?;
+
+        let mut normalized = vec![];
+        for path in paths {
+            // paths which cannot be resolved are returned unchanged
+            match fs::canonicalize(&path) {
+                Ok(p) => normalized.push(p.to_string_lossy().to_string()),
+                Err(e) => {
+                    let msg = format!("path[{}]=\"{path}\": {e}", normalized.len() + 1);
+                    match must_work {
+                        Some(true) => return err!(msg),
+                        None => warning::warn(msg)?,
+                        Some(false) => (),
+                    }
+
+                    normalized.push(path);
+                }
+            }
+        }
+
+        Ok(Obj::Vector(Vector::from(normalized)))
+    }
+}
+
+/// The per-session temporary directory, created on first use
+fn session_tempdir() -> Result<&'static str, Signal> {
+    static TEMPDIR: OnceLock<String> = OnceLock::new();
+    if let Some(dir) = TEMPDIR.get() {
+        return Ok(dir);
+    }
+
+    let dir = std::env::temp_dir().join(format!("Rtmp{}", std::process::id()));
+    if let Err(e) = fs::create_dir_all(&dir) {
+        return err!(format!("cannot create temporary directory: {e}"));
+    }
+
+    Ok(TEMPDIR.get_or_init(|| dir.to_string_lossy().replace('\\', "/")))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "tempdir")]
+pub struct PrimitiveTempdir;
+impl Callable for PrimitiveTempdir {
+    fn call(&self, _args: ExprList, _stack: &mut CallStack) -> EvalResult {
+        let dir = session_tempdir()?.to_string();
+        Ok(Obj::Vector(Vector::from(vec![dir])))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "tempfile")]
+pub struct PrimitiveTempfile;
+impl Callable for PrimitiveTempfile {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (
+                Some(String::from("pattern")),
+                Expr::String(String::from("file")),
+            ),
+            (
+                Some(String::from("tmpdir")),
+                Expr::Call(
+                    Box::new(Expr::Symbol("tempdir".to_string())),
+                    ExprList::new(),
+                ),
+            ),
+            (Some(String::from("fileext")), Expr::String(String::new())),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let patterns: Vec<String> = args.try_get_named("pattern")?.force(stack)?.try_into()?;
+        let tmpdir: String = args.try_get_named("tmpdir")?.force(stack)?.try_into()?;
+        let fileext: String = args.try_get_named("fileext")?.force(stack)?.try_into()?;
+
+        // files are named, but not created
+        let mut rng = rand::thread_rng();
+        let files: Vec<String> = patterns
+            .iter()
+            .map(|pattern| {
+                let id: u64 = rng.gen();
+                format!("{tmpdir}/{pattern}{id:x}{fileext}")
+            })
+            .collect();
+
+        Ok(Obj::Vector(Vector::from(files)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::r;
+    use crate::repl::eval;
+
+    #[test]
+    fn files_are_created_listed_and_removed() {
+        let result = eval(
+            "dir <- tempfile('listing')
+             dir.create(file.path(dir, 'sub'), recursive = TRUE)
+             writeLines('a', file.path(dir, 'a.csv'))
+             writeLines('b', file.path(dir, 'b.txt'))
+             writeLines('c', file.path(dir, 'sub', 'c.csv'))
+             found <- list.files(dir, pattern = 'csv$', recursive = TRUE)
+             removed <- file.remove(file.path(dir, c('a.csv', 'b.txt')))
+             list(found, list.files(dir), removed, file.exists(file.path(dir, 'sub')))",
+        );
+
+        assert_eq!(
+            result,
+            r! { list(c("a.csv", "sub/c.csv"), "sub", c(TRUE, TRUE), TRUE) }
+        );
+    }
+
+    #[test]
+    fn files_are_listed_by_pattern() {
+        let result = eval(
+            "dir <- tempfile('pattern')
+             dir.create(dir)
+             for (f in c('a1.txt', 'a_b.txt', 'aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.txt')) {
+               writeLines('x', file.path(dir, f))
+             }
+             list(
+               list.files(dir, pattern = '^[[:alnum:]]+[.]txt$'),
+               list.files(dir, pattern = '^(a*)*_b')
+             )",
+        );
+
+        assert_eq!(
+            result,
+            r! { list(c("a1.txt", "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa.txt"), "a_b.txt") }
+        );
+        assert!(r! { list.files(".", pattern = "(a") }.is_err());
+    }
+
+    #[test]
+    fn paths_are_split() {
+        assert_eq!(
+            r! { basename(c("a/b.txt", "/a/b/", "b")) },
+            r! { c("b.txt", "b", "b") }
+        );
+        assert_eq!(
+            r! { dirname(c("a/b.txt", "/a", "b", "/")) },
+            r! { c("a", "/", ".", "/") }
+        );
+        assert_eq!(
+            r! { file.path("a", c("b", "c"), "d.txt") },
+            r! { c("a/b/d.txt", "a/c/d.txt") }
+        );
+    }
+
+    #[test]
+    fn unresolved_paths_are_unchanged() {
+        assert_eq!(
+            r! { normalizePath("no/such/file", mustWork = FALSE) },
+            r! { "no/such/file" }
+        );
+        assert!(r! { normalizePath("no/such/file", mustWork = TRUE) }.is_err());
+    }
+}
@@ -0,0 +1,392 @@
+use r_derive::*;
+
+use crate::callable::core::*;
+use crate::err;
+use crate::lang::*;
+use crate::object::*;
+
+/// The decomposition of a model formula into its terms
+///
+/// Terms are built from the right-hand side of a formula, where
+///
+/// * `a + b` includes the terms of both `a` and `b`
+/// * `a - b` removes the terms of `b`
+/// * `a:b` is the interaction of the terms of `a` and `b`
+/// * `a * b` is shorthand for `a + b + a:b`
+/// * `.` includes each variable of the data not used in the response
+/// * `+ 0` or `- 1` removes the intercept
+///
+/// Any other expression, such as `log(x)`, is a single variable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Terms {
+    pub response: Option<Expr>,
+    pub variables: Vec<Expr>,
+    pub terms: Vec<Vec<String>>,
+    pub intercept: bool,
+}
+
+impl Terms {
+    /// Decompose a formula, with the names of the variables available in
+    /// the data used to expand `.`
+    pub fn new(formula: &Expr, data: Option<&[String]>) -> Result<Terms, Signal> {
+        let (response, rhs) = match formula_sides(formula) {
+            Some(sides) => sides,
+            None => return err!("argument is not a valid model formula"),
+        };
+
+        let excluded = response.as_ref().map(all_vars).unwrap_or_default();
+        let mut terms = TermsBuilder {
+            data,
+            excluded,
+            terms: vec![],
+            intercept: true,
+        };
+
+        terms.collect(&rhs, false)?;
+
+        // variables ordered by their first appearance, following the response
+        let mut variables: Vec<Expr> = response.iter().cloned().collect();
+        for var in terms.expand_factors(&rhs)? {
+            if !variables.contains(&var) {
+                variables.push(var)
+            }
+        }
+
+        let names: Vec<String> = variables.iter().map(|v| v.to_string()).collect();
+
+        // factors of each term ordered by variable, terms ordered by degree
+        let intercept = terms.intercept;
+        let mut terms = terms.terms;
+        for term in terms.iter_mut() {
+            term.sort_by_key(|v| names.iter().position(|i| i == v));
+        }
+        terms.sort_by_key(|t| t.len());
+
+        Ok(Terms {
+            response,
+            variables,
+            terms,
+            intercept,
+        })
+    }
+
+    /// Labels of each term, with interactions joined by `:`
+    pub fn labels(&self) -> Vec<String> {
+        self.terms.iter().map(|t| t.join(":")).collect()
+    }
+}
+
+struct TermsBuilder<'a> {
+    data: Option<&'a [String]>,
+    excluded: Vec<String>,
+    terms: Vec<Vec<String>>,
+    intercept: bool,
+}
+
+impl TermsBuilder<'_> {
+    /// Add (or remove) the terms of an additive expression
+    fn collect(&mut self, expr: &Expr, remove: bool) -> Result<(), Signal> {
+        match operator(expr) {
+            Some(("+", [l, r])) => {
+                self.collect(l, remove)?;
+                self.collect(r, remove)
+            }
+            Some(("-", [l, r])) => {
+                self.collect(l, remove)?;
+                self.collect(r, !remove)
+            }
+            Some(("+", [x])) => self.collect(x, remove),
+            Some(("-", [x])) => self.collect(x, !remove),
+            _ => match expr {
+                Expr::Number(n) if *n == 0.0 || *n == 1.0 => {
+                    self.intercept = (*n == 1.0) != remove;
+                    Ok(())
+                }
+                Expr::Integer(n) if *n == 0 || *n == 1 => {
+                    self.intercept = (*n == 1) != remove;
+                    Ok(())
+                }
+                _ => {
+                    for term in self.expand(expr)? {
+                        let existing = self.terms.iter().position(|t| same_term(t, &term));
+                        match (existing, remove) {
+                            (Some(i), true) => {
+                                self.terms.remove(i);
+                            }
+                            (None, false) => self.terms.push(term),
+                            _ => (),
+                        }
+                    }
+                    Ok(())
+                }
+            },
+        }
+    }
+
+    /// Variables used as factors within an expression, in order of appearance
+    fn expand_factors(&self, expr: &Expr) -> Result<Vec<Expr>, Signal> {
+        match (operator(expr), expr) {
+            (Some((_, args)), _) => Ok(args
+                .iter()
+                .map(|arg| self.expand_factors(arg))
+                .collect::<Result<Vec<_>, _>>()?
+                .concat()),
+            (None, Expr::Number(_) | Expr::Integer(_)) => Ok(vec![]),
+            (None, Expr::Symbol(s)) if s == "." => Ok(self
+                .expand(expr)?
+                .concat()
+                .into_iter()
+                .map(Expr::Symbol)
+                .collect()),
+            (None, _) => Ok(vec![expr.clone()]),
+        }
+    }
+
+    /// Expand an expression into the terms it produces
+    fn expand(&self, expr: &Expr) -> Result<Vec<Vec<String>>, Signal> {
+        match operator(expr) {
+            Some(("+", [l, r])) => Ok(union(self.expand(l)?, self.expand(r)?)),
+            Some((":", [l, r])) => Ok(interact(&self.expand(l)?, &self.expand(r)?)),
+            Some(("*", [l, r])) => {
+                let (l, r) = (self.expand(l)?, self.expand(r)?);
+                let both = interact(&l, &r);
+                Ok(union(union(l, r), both))
+            }
+            Some(("-" | "+", _)) => err!("invalid model formula"),
+            _ => match expr {
+                Expr::Symbol(s) if s == "." => match self.data {
+                    Some(data) => Ok(data
+                        .iter()
+                        .filter(|v| !self.excluded.contains(v))
+                        .map(|v| vec![v.clone()])
+                        .collect()),
+                    None => err!("'.' in formula and no 'data' argument"),
+                },
+                _ => Ok(vec![vec![expr.to_string()]]),
+            },
+        }
+    }
+}
+
+fn same_term(l: &[String], r: &[String]) -> bool {
+    l.len() == r.len() && l.iter().all(|v| r.contains(v))
+}
+
+fn union(mut l: Vec<Vec<String>>, r: Vec<Vec<String>>) -> Vec<Vec<String>> {
+    for term in r {
+        if !l.iter().any(|t| same_term(t, &term)) {
+            l.push(term)
+        }
+    }
+    l
+}
+
+fn interact(l: &[Vec<String>], r: &[Vec<String>]) -> Vec<Vec<String>> {
+    let mut terms = vec![];
+    for lt in l {
+        for rt in r {
+            let mut term = lt.clone();
+            for var in rt {
+                if !term.contains(var) {
+                    term.push(var.clone())
+                }
+            }
+            terms = union(terms, vec![term]);
+        }
+    }
+    terms
+}
+
+/// The symbol and arguments of a call to a builtin operator
+fn operator(expr: &Expr) -> Option<(&str, &[Expr])> {
+    match expr {
+        Expr::Call(what, args) => match what.as_ref() {
+            Expr::Primitive(p) => {
+                let sym = match p.rfmt().as_str() {
+                    "+" => "+",
+                    "-" => "-",
+                    "*" => "*",
+                    ":" => ":",
+                    "~" => "~",
+                    _ => return None,
+                };
+                Some((sym, &args.values[..]))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The response (if two-sided) and right-hand side of a formula
+fn formula_sides(formula: &Expr) -> Option<(Option<Expr>, Expr)> {
+    match operator(formula)? {
+        ("~", [lhs, rhs]) => Some((Some(lhs.clone()), rhs.clone())),
+        ("~", [rhs]) => Some((None, rhs.clone())),
+        _ => None,
+    }
+}
+
+/// Names of all variables used within an expression, excluding function names
+pub fn all_vars(expr: &Expr) -> Vec<String> {
+    fn collect(expr: &Expr, vars: &mut Vec<String>) {
+        match expr {
+            Expr::Symbol(s) if !vars.contains(s) => vars.push(s.clone()),
+            Expr::Call(what, args) => {
+                if !matches!(what.as_ref(), Expr::Symbol(_) | Expr::Primitive(_)) {
+                    collect(what, vars)
+                }
+                args.values.iter().for_each(|arg| collect(arg, vars))
+            }
+            Expr::List(args) => args.values.iter().for_each(|arg| collect(arg, vars)),
+            _ => (),
+        }
+    }
+
+    let mut vars = vec![];
+    collect(expr, &mut vars);
+    vars
+}
+
+/// Extract an expression from a formula or quoted expression
+fn formula_expr(x: Obj) -> Result<Expr, Signal> {
+    match x {
+        Obj::Formula(expr, _) | Obj::Expr(expr) => Ok(expr),
+        Obj::Closure(expr, _) => Ok(expr),
+        _ => err!("argument must be a formula or expression"),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "all.vars")]
+pub struct PrimitiveAllVars;
+impl Callable for PrimitiveAllVars {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("expr")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let expr = formula_expr(args.try_get_named("expr")?.force(stack)?)?;
+        Ok(Obj::Vector(Vector::from(all_vars(&expr))))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "terms")]
+pub struct PrimitiveTerms;
+impl Callable for PrimitiveTerms {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("data")), Expr::Null),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let Obj::Formula(formula, _) = args.try_get_named("x")?.force(stack)? else {
+            return err!("argument is not a valid model formula");
+        };
+
+        let data = match args.try_get_named("data")?.force(stack)? {
+            Obj::Null => None,
+            Obj::List(l) => Some(
+                l.values
+                    .borrow()
+                    .iter()
+                    .filter_map(|(k, _)| k.clone())
+                    .collect::<Vec<_>>(),
+            ),
+            _ => return err!("'data' must be a list"),
+        };
+
+        let terms = Terms::new(&formula, data.as_deref())?;
+
+        Ok(Obj::List(List::from(vec![
+            (
+                Some(String::from("variables")),
+                Obj::Vector(Vector::from(
+                    terms
+                        .variables
+                        .iter()
+                        .map(|v| v.to_string())
+                        .collect::<Vec<_>>(),
+                )),
+            ),
+            (
+                Some(String::from("term.labels")),
+                Obj::Vector(Vector::from(terms.labels())),
+            ),
+            (
+                Some(String::from("response")),
+                Obj::Vector(Vector::from(vec![terms.response.is_some() as i32])),
+            ),
+            (
+                Some(String::from("intercept")),
+                Obj::Vector(Vector::from(vec![terms.intercept as i32])),
+            ),
+        ])))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::r;
+
+    #[test]
+    fn formulas_are_unevaluated() {
+        assert_eq!(
+            r! { f <- y ~ x + undefined; all.vars(f) },
+            r! { c("y", "x", "undefined") }
+        );
+    }
+
+    #[test]
+    fn all_vars_excludes_function_names() {
+        assert_eq!(r! { all.vars(~ log(x) + sqrt(y)) }, r! { c("x", "y") });
+        assert_eq!(r! { all.vars(quote(a * f(b))) }, r! { c("a", "b") });
+    }
+
+    #[test]
+    fn terms_decomposition() {
+        assert_eq!(
+            r! { terms(y ~ a * b + c)$term.labels },
+            r! { c("a", "b", "c", "a:b") }
+        );
+        assert_eq!(
+            r! { terms(y ~ a * b - a:b)$term.labels },
+            r! { c("a", "b") }
+        );
+        assert_eq!(
+            r! { terms(~ (a + b):c)$term.labels },
+            r! { c("a:c", "b:c") }
+        );
+    }
+
+    #[test]
+    fn terms_intercept_and_response() {
+        assert_eq!(r! { terms(y ~ x - 1)$intercept }, r! { 0L });
+        assert_eq!(r! { terms(y ~ x + 0)$intercept }, r! { 0L });
+        assert_eq!(r! { terms(y ~ x)$intercept }, r! { 1L });
+        assert_eq!(r! { terms(~ x)$response }, r! { 0L });
+        assert_eq!(r! { terms(y ~ x)$response }, r! { 1L });
+    }
+
+    #[test]
+    fn terms_dot_expands_data() {
+        assert_eq!(
+            r! { terms(y ~ ., data = list(y = 1, a = 2, b = 3))$term.labels },
+            r! { c("a", "b") }
+        );
+        assert!(r! { terms(y ~ .) }.is_err());
+    }
+
+    #[test]
+    fn formula_printing() {
+        assert_eq!(
+            crate::repl::eval("y ~ x + z").map(|f| f.to_string()),
+            Ok(String::from("y ~ x + z"))
+        );
+    }
+}
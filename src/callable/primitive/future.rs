@@ -0,0 +1,206 @@
+use std::rc::Rc;
+
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::context::Context;
+use crate::err;
+use crate::future::{self, Future};
+use crate::handle::External;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+
+const PROMISED: &str = "*future*";
+
+fn future_obj(future: Future) -> Obj {
+    Obj::External(External::new(future))
+}
+
+/// The id of a future given as an argument
+fn future_arg(x: Obj, stack: &mut CallStack) -> Result<usize, Signal> {
+    match x.force(stack)? {
+        Obj::External(x) => x
+            .downcast::<Future>()
+            .map_or(err!("argument is not a future"), |f| Ok(f.id())),
+        _ => err!("argument is not a future"),
+    }
+}
+
+/// The expression passed as the first argument, unevaluated
+fn expr_arg(args: ExprList, name: &str) -> Result<Expr, Signal> {
+    match args.into_iter().next() {
+        Some((None, expr)) => Ok(expr),
+        Some((Some(k), expr)) if k == name => Ok(expr),
+        _ => err!(format!("argument '{name}' is missing")),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "future")]
+pub struct PrimitiveFuture;
+impl Callable for PrimitiveFuture {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("expr")), Expr::Missing)])
+    }
+
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let expr = expr_arg(args, "expr")?;
+        let future = future::spawn(expr, stack.parent_frame().env())?;
+        Ok(future_obj(future))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "value")]
+pub struct PrimitiveValue;
+impl Callable for PrimitiveValue {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("future")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let id = future_arg(Obj::List(args).try_get_named("future")?, stack)?;
+        future::value(id, &stack.env())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "resolved")]
+pub struct PrimitiveResolved;
+impl Callable for PrimitiveResolved {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("future")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let id = future_arg(Obj::List(args).try_get_named("future")?, stack)?;
+        Ok(Obj::Vector(Vector::from(vec![future::resolved(id)?])))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "cancel")]
+pub struct PrimitiveCancel;
+impl Callable for PrimitiveCancel {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("future")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let id = future_arg(Obj::List(args).try_get_named("future")?, stack)?;
+        Ok(Obj::Vector(Vector::from(vec![future::cancel(id)?])))
+    }
+}
+
+/// Future assignment, `x %<-% expr`, binding `x` to a promise of the value
+/// of a future evaluating `expr`
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "%<-%")]
+pub struct PrimitiveFutureAssign;
+impl Callable for PrimitiveFutureAssign {
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let (lhs, rhs) = args.unnamed_binary_args();
+        let Expr::Symbol(name) = lhs else {
+            return err!("invalid future assignment target");
+        };
+
+        let env = stack.parent_frame().env();
+        let future = future::spawn(rhs, env.clone())?;
+        let id = future.id();
+        let future = future_obj(future);
+
+        // the promise is evaluated in an environment holding the future, such
+        // that the future remains reachable until the promise is forced
+        let promise_env = Rc::new(Environment {
+            parent: Some(env.clone()),
+            ..Default::default()
+        });
+        promise_env.insert(PROMISED.to_string(), future.clone());
+
+        let promise = Expr::new_primitive_call(FutureValue { id }, ExprList::new());
+        env.insert(name, Obj::Closure(promise, promise_env));
+        Ok(future)
+    }
+}
+
+/// The value of a future, as forced by a future assignment's promise
+#[derive(Debug, Clone, PartialEq)]
+#[builtin]
+pub struct FutureValue {
+    id: usize,
+}
+
+impl Format for FutureValue {
+    fn rfmt_call_with(&self, _state: FormatState, _args: &ExprList) -> String {
+        format!("value(<future {}>)", self.id)
+    }
+
+    fn rfmt_with(&self, _state: FormatState) -> String {
+        "value".to_string()
+    }
+}
+
+impl Callable for FutureValue {
+    fn call(&self, _args: ExprList, stack: &mut CallStack) -> EvalResult {
+        future::value(self.id, &stack.env())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{r, r_expect};
+
+    #[test]
+    fn futures_evaluate_snapshots_of_their_environment() {
+        r_expect! {{"
+            x <- 1
+            f <- future({ y <- x + 1; y * 10 })
+            x <- 100
+            value(f) == 20 && value(f) == 20
+        "}}
+    }
+
+    #[test]
+    fn futures_resolve() {
+        r_expect! {{"
+            f <- future(1)
+            v <- value(f)
+            resolved(f) && v == 1
+        "}}
+    }
+
+    #[test]
+    fn future_errors_are_raised_by_value() {
+        assert!(r! { f <- future(undefined); value(f) }.is_err());
+    }
+
+    #[test]
+    fn cancelled_futures_have_no_value() {
+        r_expect! {{"
+            f <- future(repeat { 1 })
+            cancel(f) && resolved(f) && cancel(f) == FALSE
+        "}}
+
+        assert!(r! { f <- future(repeat { 1 }); cancel(f); value(f) }.is_err());
+    }
+
+    #[test]
+    fn futures_cannot_be_forged() {
+        assert!(r! {{"
+            f <- future(1)
+            x <- list(id = 1L)
+            attr(x, 'class') <- 'future'
+            value(x)
+        "}}
+        .is_err());
+    }
+
+    #[test]
+    fn future_assignment_is_lazy() {
+        r_expect! {{"
+            n <- 2
+            x %<-% { n * 21 }
+            x == 42
+        "}}
+    }
+}
@@ -0,0 +1,178 @@
+use r_derive::builtin;
+
+use super::partial::call_with;
+use crate::callable::core::*;
+use crate::context::Context;
+use crate::err;
+use crate::error::Error;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+
+fn generic_formals() -> ExprList {
+    ExprList::from(vec![
+        (Some(String::from("object")), Expr::Missing),
+        (None, Expr::Ellipsis(None)),
+    ])
+}
+
+/// Call the method of a generic function for the class of its `object`
+///
+/// Methods are functions named `<generic>.<class>`, found from the calling
+/// environment. Each class of the object is tried in turn, before falling
+/// back to a `<generic>.default` method. Methods are called with the object
+/// followed by any further arguments.
+fn dispatch(generic: &str, args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+    let object = Obj::List(args).try_get_named("object")?.force(stack)?;
+    dispatch_on(generic, object, ellipsis, stack)
+}
+
+/// Call the method of a generic function for the class of an evaluated
+/// object, as [dispatch] does for its `object` argument
+pub fn dispatch_on(
+    generic: &str,
+    object: Obj,
+    ellipsis: List,
+    stack: &mut CallStack,
+) -> EvalResult {
+    let env = stack.parent_frame().env();
+    let classes = object.class();
+
+    for class in classes.iter().map(String::as_str).chain(["default"]) {
+        let method = match stack.get_in(format!("{generic}.{class}"), env.clone()) {
+            Ok(method @ Obj::Function(..)) => method,
+            Ok(_) | Err(Signal::Error(Error::VariableNotFound(_))) => continue,
+            Err(e) => return Err(e),
+        };
+
+        let mut method_args = vec![(None, object)];
+        method_args.extend(ellipsis.elements());
+        return call_with(method, List::from(method_args), stack);
+    }
+
+    let class = classes.first().map_or("NULL", String::as_str);
+    err!(format!(
+        "no applicable method for '{generic}' applied to an object of class \"{class}\""
+    ))
+}
+
+/// An element of a list `object`, as used by default methods
+fn element(args: List, name: &str, stack: &mut CallStack) -> EvalResult {
+    match Obj::List(args).try_get_named("object")?.force(stack)? {
+        Obj::List(l) => Ok(Obj::List(l).get_named(name).unwrap_or(Obj::Null)),
+        _ => err!(format!("'object' has no '{name}'")),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "summary")]
+pub struct PrimitiveSummary;
+impl Callable for PrimitiveSummary {
+    fn formals(&self) -> ExprList {
+        generic_formals()
+    }
+
+    fn call_matched(&self, args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        dispatch("summary", args, ellipsis, stack)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "predict")]
+pub struct PrimitivePredict;
+impl Callable for PrimitivePredict {
+    fn formals(&self) -> ExprList {
+        generic_formals()
+    }
+
+    fn call_matched(&self, args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        dispatch("predict", args, ellipsis, stack)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "coef")]
+pub struct PrimitiveCoef;
+impl Callable for PrimitiveCoef {
+    fn formals(&self) -> ExprList {
+        generic_formals()
+    }
+
+    fn call_matched(&self, args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        dispatch("coef", args, ellipsis, stack)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "coef.default")]
+pub struct PrimitiveCoefDefault;
+impl Callable for PrimitiveCoefDefault {
+    fn formals(&self) -> ExprList {
+        generic_formals()
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        element(args, "coefficients", stack)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "residuals")]
+pub struct PrimitiveResiduals;
+impl Callable for PrimitiveResiduals {
+    fn formals(&self) -> ExprList {
+        generic_formals()
+    }
+
+    fn call_matched(&self, args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        dispatch("residuals", args, ellipsis, stack)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "residuals.default")]
+pub struct PrimitiveResidualsDefault;
+impl Callable for PrimitiveResidualsDefault {
+    fn formals(&self) -> ExprList {
+        generic_formals()
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        element(args, "residuals", stack)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{r, r_expect};
+
+    #[test]
+    fn methods_are_dispatched_by_class() {
+        r_expect! {{"
+            summary.numeric <- function(object, ...) object + 1
+            summary.character <- function(object, ...) paste(object, \"!\")
+            summary(1) == 2 && summary(\"a\") == \"a !\"
+        "}}
+    }
+
+    #[test]
+    fn arguments_are_passed_to_methods() {
+        r_expect! {{"
+            predict.numeric <- function(object, scale = 1) object * scale
+            predict(2, scale = 3) == 6
+        "}}
+    }
+
+    #[test]
+    fn default_methods_extract_elements() {
+        r_expect! {{"
+            fit <- list(coefficients = c(1, 2), residuals = 0.5)
+            sum(coef(fit)) == 3 && residuals(fit) == 0.5
+        "}}
+    }
+
+    #[test]
+    fn objects_without_methods_are_an_error() {
+        assert!(r! { summary(1) }.is_err());
+        assert!(r! { predict(list(1)) }.is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use r_derive::*;
+
+use crate::callable::core::*;
+use crate::err;
+use crate::lang::*;
+use crate::object::*;
+
+/// Select elements from `yes` or `no` by `test`, recycling each
+fn select<T: Clone>(
+    test: &[OptionNA<bool>],
+    yes: &[OptionNA<T>],
+    no: &[OptionNA<T>],
+) -> Vec<OptionNA<T>> {
+    let recycled = |x: &[OptionNA<T>], i: usize| match x.len() {
+        0 => OptionNA::NA,
+        n => x[i % n].clone(),
+    };
+
+    test.iter()
+        .enumerate()
+        .map(|(i, t)| match t {
+            OptionNA::Some(true) => recycled(yes, i),
+            OptionNA::Some(false) => recycled(no, i),
+            OptionNA::NA => OptionNA::NA,
+        })
+        .collect()
+}
+
+/// Rank vector types by coercion, such that mixed results take the highest
+fn mode(x: &Obj) -> Result<usize, Signal> {
+    match x {
+        Obj::Null | Obj::Vector(Vector::Logical(_)) => Ok(0),
+        Obj::Vector(Vector::Integer(_)) => Ok(1),
+        Obj::Vector(Vector::Numeric(_)) => Ok(2),
+        Obj::Vector(Vector::Character(_)) => Ok(3),
+        _ => err!("'yes' and 'no' must be vectors"),
+    }
+}
+
+fn coerce(x: Obj, mode: usize) -> EvalResult {
+    match mode {
+        0 => x.as_logical(),
+        1 => x.as_integer(),
+        2 => x.as_numeric(),
+        _ => x.as_character(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "ifelse")]
+pub struct PrimitiveIfelse;
+impl Callable for PrimitiveIfelse {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("test")), Expr::Missing),
+            (Some(String::from("yes")), Expr::Missing),
+            (Some(String::from("no")), Expr::Missing),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let Obj::Vector(Vector::Logical(test)) =
+            args.try_get_named("test")?.force(stack)?.as_logical()?
+        else {
+            return err!("'test' must be coercible to logical");
+        };
+
+        let test = test.inner().borrow().clone();
+
+        // branches are only evaluated when selected by some element
+        let yes = match test.contains(&OptionNA::Some(true)) {
+            true => args.try_get_named("yes")?.force(stack)?,
+            false => Obj::Null,
+        };
+
+        let no = match test.contains(&OptionNA::Some(false)) {
+            true => args.try_get_named("no")?.force(stack)?,
+            false => Obj::Null,
+        };
+
+        let mode = mode(&yes)?.max(mode(&no)?);
+        let result = match (coerce(yes, mode)?, coerce(no, mode)?) {
+            (Obj::Vector(Vector::Logical(y)), Obj::Vector(Vector::Logical(n))) => {
+                Vector::from(select(&test, &y.inner().borrow(), &n.inner().borrow()))
+            }
+            (Obj::Vector(Vector::Integer(y)), Obj::Vector(Vector::Integer(n))) => {
+                Vector::from(select(&test, &y.inner().borrow(), &n.inner().borrow()))
+            }
+            (Obj::Vector(Vector::Numeric(y)), Obj::Vector(Vector::Numeric(n))) => {
+                Vector::from(select(&test, &y.inner().borrow(), &n.inner().borrow()))
+            }
+            (Obj::Vector(Vector::Character(y)), Obj::Vector(Vector::Character(n))) => {
+                Vector::from(select(&test, &y.inner().borrow(), &n.inner().borrow()))
+            }
+            _ => unreachable!(),
+        };
+
+        Ok(Obj::Vector(result))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::r;
+
+    #[test]
+    fn vectorized_selection() {
+        assert_eq!(
+            r! { ifelse(c(1, 5, 10) > 4, "big", "small") },
+            r! { c("small", "big", "big") }
+        );
+        assert_eq!(
+            r! { ifelse(c(TRUE, FALSE, TRUE, FALSE), c(1, 2, 3, 4), 0) },
+            r! { c(1, 0, 3, 0) }
+        );
+    }
+
+    #[test]
+    fn preserves_na() {
+        assert_eq!(r! { ifelse(c(TRUE, NA, FALSE), 1, 2) }, r! { c(1, NA, 2) });
+    }
+
+    #[test]
+    fn coerces_to_common_type() {
+        assert_eq!(r! { ifelse(c(TRUE, FALSE), 1L, "no") }, r! { c("1", "no") });
+    }
+
+    #[test]
+    fn only_selected_branches_are_evaluated() {
+        assert_eq!(r! { ifelse(c(TRUE, TRUE), 1, stop_here()) }, r! { c(1, 1) });
+        assert_eq!(r! { ifelse(FALSE, stop_here(), 2) }, r! { 2 });
+    }
+}
@@ -0,0 +1,361 @@
+use r_derive::builtin;
+
+use super::connection::{connection_obj, with_connection};
+use crate::callable::core::*;
+use crate::connection::{self, Mode};
+use crate::debugger;
+use crate::err;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+
+fn call(name: &str) -> Expr {
+    Expr::Call(Box::new(Expr::Symbol(name.to_string())), ExprList::new())
+}
+
+/// The number of items to read, where negative numbers read all items
+fn limit(n: Obj) -> Result<Option<usize>, Signal> {
+    let n: i32 = n.try_into()?;
+    Ok((n >= 0).then_some(n as usize))
+}
+
+/// The elements of an object as strings, as they are written by `cat`
+fn strings(x: Obj, i: usize) -> Result<Vec<String>, Signal> {
+    match x {
+        Obj::Null => Ok(vec![]),
+        Obj::Vector(v) => {
+            let Obj::Vector(Vector::Character(v)) = Obj::Vector(v).as_character()? else {
+                return err!("cannot coerce to character");
+            };
+
+            let v = v.materialize().inner().borrow().clone();
+            Ok(v.into_iter()
+                .map(|vi| match vi {
+                    OptionNA::Some(s) => s,
+                    OptionNA::NA => "NA".to_string(),
+                })
+                .collect())
+        }
+        Obj::List(l) => Ok(l
+            .elements()
+            .into_iter()
+            .map(|(_, v)| strings(v, i))
+            .collect::<Result<Vec<_>, _>>()?
+            .concat()),
+        x => {
+            let class = x.class().join(", ");
+            err!(format!(
+                "argument {i} (class '{class}') cannot be handled by 'cat'"
+            ))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "readLines")]
+pub struct PrimitiveReadLines;
+impl Callable for PrimitiveReadLines {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("con")), call("stdin")),
+            (Some(String::from("n")), Expr::Number(-1.0)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let con = args.try_get_named("con")?.force(stack)?;
+        let n = limit(args.try_get_named("n")?.force(stack)?)?;
+
+        let lines = with_connection(con, connection::STDIN, |id| {
+            connection::with_reader(id, |reader| {
+                let mut lines = vec![];
+                while n.is_none_or(|n| lines.len() < n) {
+                    match connection::read_line(reader)? {
+                        Some(line) => lines.push(line),
+                        None => break,
+                    }
+                }
+
+                Ok(lines)
+            })
+        })?;
+
+        Ok(Obj::Vector(Vector::from(lines)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "writeLines")]
+pub struct PrimitiveWriteLines;
+impl Callable for PrimitiveWriteLines {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("text")), Expr::Missing),
+            (Some(String::from("con")), call("stdout")),
+            (Some(String::from("sep")), Expr::String(String::from("\n"))),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let text: Vec<String> = args.try_get_named("text")?.force(stack)?.try_into()?;
+        let con = args.try_get_named("con")?.force(stack)?;
+        let sep: String = args.try_get_named("sep")?.force(stack)?.try_into()?;
+
+        with_connection(con, connection::STDOUT, |id| {
+            connection::with_writer(id, Mode::Write, |writer| {
+                text.iter()
+                    .try_for_each(|line| write!(writer, "{line}{sep}"))
+            })
+        })?;
+
+        Ok(Obj::Null)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "readline")]
+pub struct PrimitiveReadline;
+impl Callable for PrimitiveReadline {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(
+            Some(String::from("prompt")),
+            Expr::String(String::new()),
+        )])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let prompt: String = args.try_get_named("prompt")?.force(stack)?.try_into()?;
+
+        // read interactively, as the browser reads its commands
+        let line = debugger::read_line(&prompt).unwrap_or_default();
+        Ok(Obj::Vector(Vector::from(vec![line])))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "cat")]
+pub struct PrimitiveCat;
+impl Callable for PrimitiveCat {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (None, Expr::Ellipsis(None)),
+            (Some(String::from("file")), Expr::String(String::new())),
+            (Some(String::from("sep")), Expr::String(String::from(" "))),
+            (Some(String::from("append")), Expr::Bool(false)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        // parameters following the ellipsis are only matched when named
+        let file = args.get_named("file").unwrap_or_default().force(stack)?;
+        let file = match file {
+            Obj::Null => Obj::Vector(Vector::from(vec![String::new()])),
+            file => file,
+        };
+
+        let sep: String = match args.get_named("sep") {
+            Some(sep) => sep.force(stack)?.try_into()?,
+            None => " ".to_string(),
+        };
+
+        let append: bool = match args.get_named("append") {
+            Some(append) => append.force(stack)?.try_into()?,
+            None => false,
+        };
+
+        let mut items = vec![];
+        for (i, (_, x)) in ellipsis.elements().into_iter().enumerate() {
+            items.extend(strings(x.force(stack)?, i + 1)?);
+        }
+
+        let mode = if append { Mode::Append } else { Mode::Write };
+        let text = items.join(&sep);
+        with_connection(file, connection::STDOUT, |id| {
+            connection::with_writer(id, mode, |writer| write!(writer, "{text}"))
+        })?;
+
+        Ok(Obj::Null)
+    }
+}
+
+/// Parse fields read by `scan` as the mode of `what`
+fn scan_fields(fields: &[String], what: &Vector) -> Result<Vector, Signal> {
+    fn parse<T>(
+        fields: &[String],
+        expected: &str,
+        f: impl Fn(&str) -> Option<T>,
+    ) -> Result<Vec<OptionNA<T>>, Signal> {
+        fields
+            .iter()
+            .map(|field| match field.as_str() {
+                "NA" => Ok(OptionNA::NA),
+                field => match f(field) {
+                    Some(x) => Ok(OptionNA::Some(x)),
+                    None => err!(format!("scan() expected '{expected}', got '{field}'")),
+                },
+            })
+            .collect()
+    }
+
+    let value = match what {
+        Vector::Numeric(_) => Vector::from(parse(fields, "a real", |f| f.parse::<f64>().ok())?),
+        Vector::Integer(_) => Vector::from(parse(fields, "an integer", |f| f.parse::<i32>().ok())?),
+        Vector::Logical(_) => Vector::from(parse(fields, "a logical", |f| match f {
+            "TRUE" | "T" | "true" => Some(true),
+            "FALSE" | "F" | "false" => Some(false),
+            _ => None,
+        })?),
+        Vector::Character(_) => Vector::from(fields.to_vec()),
+    };
+
+    Ok(value)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "scan")]
+pub struct PrimitiveScan;
+impl Callable for PrimitiveScan {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("file")), Expr::String(String::new())),
+            (Some(String::from("what")), Expr::Number(0.0)),
+            (Some(String::from("n")), Expr::Number(-1.0)),
+            (Some(String::from("sep")), Expr::String(String::new())),
+            (Some(String::from("text")), Expr::Null),
+            (Some(String::from("quiet")), Expr::Bool(false)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let file = args.try_get_named("file")?.force(stack)?;
+        let Obj::Vector(what) = args.try_get_named("what")?.force(stack)? else {
+            return err!("argument 'what' must be a vector");
+        };
+        let n = limit(args.try_get_named("n")?.force(stack)?)?;
+        let sep: String = args.try_get_named("sep")?.force(stack)?.try_into()?;
+        let quiet: bool = args.try_get_named("quiet")?.force(stack)?.try_into()?;
+
+        let text = args.try_get_named("text")?.force(stack)?;
+        // a connection reading text is closed as it is dropped
+        let file = match text {
+            Obj::Null => file,
+            text => connection_obj(connection::text(text.try_into()?)),
+        };
+
+        let scan = |id| {
+            connection::with_reader(id, |reader| {
+                let mut items = vec![];
+                while n.is_none_or(|n| items.len() < n) {
+                    let Some(line) = connection::read_line(reader)? else {
+                        break;
+                    };
+
+                    // input from the terminal ends with an empty line
+                    if id == connection::STDIN && line.is_empty() {
+                        break;
+                    }
+
+                    let fields: Vec<&str> = match sep.as_str() {
+                        "" => line.split_whitespace().collect(),
+                        sep => line.split(sep).map(str::trim).collect(),
+                    };
+
+                    let remaining = n.map_or(fields.len(), |n| n - items.len());
+                    items.extend(fields.into_iter().take(remaining).map(String::from));
+                }
+
+                Ok(items)
+            })
+        };
+
+        let items = with_connection(file, connection::STDIN, scan)?;
+
+        if !quiet {
+            let s = if items.len() == 1 { "" } else { "s" };
+            eprintln!("Read {} item{s}", items.len());
+        }
+
+        Ok(Obj::Vector(scan_fields(&items, &what)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::r;
+    use crate::repl::eval;
+    use crate::utils::temp_file;
+
+    #[test]
+    fn lines_are_written_and_read() {
+        let path = temp_file(".txt");
+        let result = eval(&format!(
+            "writeLines(c('a', 'b'), '{path}')
+             cat('c', 1, 2, file = '{path}', append = TRUE)
+             readLines('{path}')"
+        ));
+
+        assert_eq!(result, r! { c("a", "b", "c 1 2") });
+    }
+
+    #[test]
+    fn open_connections_are_read_incrementally() {
+        let path = temp_file(".txt");
+        let result = eval(&format!(
+            "con <- file('{path}', 'w')
+             writeLines('one', con)
+             writeLines(c('two', 'three'), con)
+             close(con)
+             con <- file('{path}')
+             open(con)
+             first <- readLines(con, n = 1)
+             rest <- readLines(con)
+             close(con)
+             c(first, rest)"
+        ));
+
+        assert_eq!(result, r! { c("one", "two", "three") });
+    }
+
+    #[test]
+    fn text_connections() {
+        assert_eq!(
+            r! {{ "con <- textConnection(c('x', 'y', 'z')); readLines(con, 2)" }},
+            r! { c("x", "y") }
+        );
+    }
+
+    #[test]
+    fn scanning() {
+        assert_eq!(
+            r! {{ "scan(text = c('1 2', '3 NA'), quiet = TRUE)" }},
+            r! {{ "c(1, 2, 3, NA)" }}
+        );
+
+        assert_eq!(
+            r! {{ "scan(text = 'a,b,c', what = '', sep = ',', n = 2, quiet = TRUE)" }},
+            r! { c("a", "b") }
+        );
+
+        assert!(r! {{ "scan(text = 'a', quiet = TRUE)" }}.is_err());
+    }
+
+    #[test]
+    fn closed_connections_are_invalid() {
+        assert!(r! {{ "con <- textConnection('x'); close(con); readLines(con)" }}.is_err());
+        assert!(r! {{ "close(stdout())" }}.is_err());
+    }
+
+    #[test]
+    fn connections_cannot_be_forged() {
+        assert!(r! {{"
+            con <- list(id = 0L)
+            attr(con, 'class') <- c('terminal', 'connection')
+            readLines(con)
+        "}}
+        .is_err());
+    }
+}
@@ -0,0 +1,580 @@
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::err;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+
+/// How values without a JSON representation, `NA`s and non-finite numbers,
+/// are written
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Missing {
+    Null,
+    String,
+}
+
+impl TryFrom<(&str, String)> for Missing {
+    type Error = Signal;
+    fn try_from((name, value): (&str, String)) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "null" => Ok(Missing::Null),
+            "string" => Ok(Missing::String),
+            _ => err!(format!(
+                "argument '{name}' must be one of 'null' or 'string'"
+            )),
+        }
+    }
+}
+
+struct Writer {
+    auto_unbox: bool,
+    pretty: bool,
+    na: Missing,
+    inf: Missing,
+    out: String,
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\u{08}' => escaped.push_str("\\b"),
+            '\u{0c}' => escaped.push_str("\\f"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
+
+impl Writer {
+    fn missing(&self, how: Missing, label: &str) -> String {
+        match how {
+            Missing::Null => "null".to_string(),
+            Missing::String => escape(label),
+        }
+    }
+
+    fn number(&self, x: &OptionNA<f64>) -> String {
+        match x {
+            OptionNA::NA => self.missing(self.na, "NA"),
+            OptionNA::Some(x) if x.is_nan() => self.missing(self.inf, "NaN"),
+            OptionNA::Some(x) if x.is_infinite() => {
+                self.missing(self.inf, if *x > 0.0 { "Inf" } else { "-Inf" })
+            }
+            OptionNA::Some(x) => x.to_string(),
+        }
+    }
+
+    fn scalars(&self, v: Vector) -> Vec<String> {
+        fn each<T>(x: &[OptionNA<T>], f: impl Fn(&T) -> String, na: String) -> Vec<String> {
+            x.iter()
+                .map(|xi| match xi {
+                    OptionNA::Some(xi) => f(xi),
+                    OptionNA::NA => na.clone(),
+                })
+                .collect()
+        }
+
+        let na = self.missing(self.na, "NA");
+        match v {
+            Vector::Numeric(x) => {
+                let x = x.materialize().inner().borrow().clone();
+                x.iter().map(|xi| self.number(xi)).collect()
+            }
+            Vector::Integer(x) => each(&x.materialize().inner().borrow(), i32::to_string, na),
+            Vector::Logical(x) => each(&x.materialize().inner().borrow(), bool::to_string, na),
+            Vector::Character(x) => each(&x.materialize().inner().borrow(), |s| escape(s), na),
+        }
+    }
+
+    fn newline(&mut self, indent: usize) {
+        if self.pretty {
+            self.out.push('\n');
+            self.out.push_str(&"  ".repeat(indent));
+        }
+    }
+
+    fn items(
+        &mut self,
+        open: char,
+        close: char,
+        items: Vec<(Option<String>, Obj)>,
+        indent: usize,
+    ) -> Result<(), Signal> {
+        if items.is_empty() {
+            self.out.push(open);
+            self.out.push(close);
+            return Ok(());
+        }
+
+        self.out.push(open);
+        let n = items.len();
+        for (i, (k, v)) in items.into_iter().enumerate() {
+            self.newline(indent + 1);
+            if let Some(k) = k {
+                self.out.push_str(&escape(&k));
+                self.out.push_str(if self.pretty { ": " } else { ":" });
+            }
+
+            self.write(v, indent + 1)?;
+            if i + 1 < n {
+                self.out.push(',');
+            }
+        }
+
+        self.newline(indent);
+        self.out.push(close);
+        Ok(())
+    }
+
+    fn write(&mut self, x: Obj, indent: usize) -> Result<(), Signal> {
+        match x {
+            Obj::Null => self.out.push_str("{}"),
+            Obj::Vector(v) => {
+                let scalars = self.scalars(v);
+                match scalars.as_slice() {
+                    [x] if self.auto_unbox => self.out.push_str(x),
+                    _ => {
+                        let sep = if self.pretty { ", " } else { "," };
+                        self.out.push_str(&format!("[{}]", scalars.join(sep)));
+                    }
+                }
+            }
+            Obj::List(l) => {
+                let items = l.elements();
+                // lists are written as objects when every element is named
+                let named = !items.is_empty()
+                    && items
+                        .iter()
+                        .all(|(k, _)| k.as_ref().is_some_and(|k| !k.is_empty()));
+
+                if named {
+                    self.items('{', '}', items, indent)?
+                } else {
+                    let items = items.into_iter().map(|(_, v)| (None, v)).collect();
+                    self.items('[', ']', items, indent)?
+                }
+            }
+            x => {
+                let class = x.class().join(", ");
+                return err!(format!("cannot convert object of class '{class}' to JSON"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A parsed JSON value
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    len: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Parser {
+            chars: text.char_indices().peekable(),
+            len: text.len(),
+        }
+    }
+
+    fn pos(&mut self) -> usize {
+        self.chars.peek().map_or(self.len, |&(i, _)| i)
+    }
+
+    fn error<T>(&mut self, msg: &str) -> Result<T, Signal> {
+        let pos = self.pos();
+        err!(format!("invalid JSON at position {pos}: {msg}"))
+    }
+
+    fn whitespace(&mut self) {
+        while self
+            .chars
+            .next_if(|(_, c)| c.is_ascii_whitespace())
+            .is_some()
+        {}
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Signal> {
+        match self.chars.next_if(|&(_, c)| c == expected) {
+            Some(_) => Ok(()),
+            None => self.error(&format!("expected '{expected}'")),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, Signal> {
+        for c in word.chars() {
+            self.expect(c)?
+        }
+
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, Signal> {
+        self.whitespace();
+        let value = match self.chars.peek().map(|&(_, c)| c) {
+            Some('n') => self.literal("null", Json::Null)?,
+            Some('t') => self.literal("true", Json::Bool(true))?,
+            Some('f') => self.literal("false", Json::Bool(false))?,
+            Some('"') => Json::String(self.string()?),
+            Some('[') => self.array()?,
+            Some('{') => self.object()?,
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number()?,
+            Some(_) => return self.error("unexpected character"),
+            None => return self.error("unexpected end of input"),
+        };
+
+        self.whitespace();
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Json, Signal> {
+        let mut number = String::new();
+        while let Some((_, c)) = self
+            .chars
+            .next_if(|(_, c)| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            number.push(c)
+        }
+
+        match number.parse::<f64>() {
+            Ok(x) => Ok(Json::Number(x)),
+            Err(_) => self.error(&format!("invalid number '{number}'")),
+        }
+    }
+
+    fn hex(&mut self) -> Result<u32, Signal> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.chars.next().and_then(|(_, c)| c.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => return self.error("invalid unicode escape"),
+            }
+        }
+
+        Ok(code)
+    }
+
+    fn string(&mut self) -> Result<String, Signal> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '"')) => return Ok(s),
+                Some((_, '\\')) => match self.chars.next() {
+                    Some((_, '"')) => s.push('"'),
+                    Some((_, '\\')) => s.push('\\'),
+                    Some((_, '/')) => s.push('/'),
+                    Some((_, 'b')) => s.push('\u{08}'),
+                    Some((_, 'f')) => s.push('\u{0c}'),
+                    Some((_, 'n')) => s.push('\n'),
+                    Some((_, 'r')) => s.push('\r'),
+                    Some((_, 't')) => s.push('\t'),
+                    Some((_, 'u')) => {
+                        let mut code = self.hex()?;
+                        // characters outside the basic plane are escaped as
+                        // surrogate pairs
+                        if (0xd800..0xdc00).contains(&code) {
+                            self.expect('\\')?;
+                            self.expect('u')?;
+                            let low = self.hex()?;
+                            code = 0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00));
+                        }
+
+                        match char::from_u32(code) {
+                            Some(c) => s.push(c),
+                            None => return self.error("invalid unicode escape"),
+                        }
+                    }
+                    _ => return self.error("invalid escape"),
+                },
+                Some((_, c)) => s.push(c),
+                None => return self.error("unterminated string"),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, Signal> {
+        self.expect('[')?;
+        self.whitespace();
+
+        let mut values = vec![];
+        if self.chars.next_if(|&(_, c)| c == ']').is_some() {
+            return Ok(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+            match self.chars.next() {
+                Some((_, ',')) => continue,
+                Some((_, ']')) => return Ok(Json::Array(values)),
+                _ => return self.error("expected ',' or ']'"),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, Signal> {
+        self.expect('{')?;
+        self.whitespace();
+
+        let mut values = vec![];
+        if self.chars.next_if(|&(_, c)| c == '}').is_some() {
+            return Ok(Json::Object(values));
+        }
+
+        loop {
+            self.whitespace();
+            let key = self.string()?;
+            self.whitespace();
+            self.expect(':')?;
+            values.push((key, self.value()?));
+            match self.chars.next() {
+                Some((_, ',')) => continue,
+                Some((_, '}')) => return Ok(Json::Object(values)),
+                _ => return self.error("expected ',' or '}'"),
+            }
+        }
+    }
+
+    fn parse(mut self) -> Result<Json, Signal> {
+        let value = self.value()?;
+        match self.chars.peek() {
+            None => Ok(value),
+            Some(_) => self.error("unexpected trailing characters"),
+        }
+    }
+}
+
+/// The value of a string written for a missing or non-finite number
+fn special_number(s: &str) -> Option<OptionNA<f64>> {
+    match s {
+        "NA" => Some(OptionNA::NA),
+        "NaN" => Some(OptionNA::Some(f64::NAN)),
+        "Inf" => Some(OptionNA::Some(f64::INFINITY)),
+        "-Inf" => Some(OptionNA::Some(f64::NEG_INFINITY)),
+        _ => None,
+    }
+}
+
+/// Simplify an array of scalars into a vector of a single mode, with `null`s
+/// as `NA`s
+fn simplify(values: &[Json]) -> Option<Vector> {
+    if values
+        .iter()
+        .all(|v| matches!(v, Json::Null | Json::Bool(_)))
+    {
+        let values = values.iter().map(|v| match v {
+            Json::Bool(x) => OptionNA::Some(*x),
+            _ => OptionNA::NA,
+        });
+        return Some(Vector::from(values.collect::<Vec<_>>()));
+    }
+
+    if values.iter().any(|v| matches!(v, Json::Number(_))) {
+        let values = values.iter().map(|v| match v {
+            Json::Number(x) => Some(OptionNA::Some(*x)),
+            Json::String(s) => special_number(s),
+            Json::Null => Some(OptionNA::NA),
+            _ => None,
+        });
+        if let Some(values) = values.collect::<Option<Vec<_>>>() {
+            return Some(Vector::from(values));
+        }
+    }
+
+    if values
+        .iter()
+        .all(|v| matches!(v, Json::Null | Json::String(_)))
+    {
+        let values = values.iter().map(|v| match v {
+            Json::String(s) => OptionNA::Some(s.clone()),
+            _ => OptionNA::NA,
+        });
+        return Some(Vector::from(values.collect::<Vec<_>>()));
+    }
+
+    None
+}
+
+fn from_json(x: Json, simplify_arrays: bool) -> Obj {
+    match x {
+        Json::Null => Obj::Vector(Vector::from(vec![OptionNA::<bool>::NA])),
+        Json::Bool(x) => Obj::Vector(Vector::from(vec![x])),
+        Json::Number(x) => Obj::Vector(Vector::from(vec![x])),
+        Json::String(x) => Obj::Vector(Vector::from(vec![x])),
+        Json::Array(values) => match simplify_arrays.then(|| simplify(&values)).flatten() {
+            Some(v) => Obj::Vector(v),
+            None => Obj::List(List::from(
+                values
+                    .into_iter()
+                    .map(|v| (None, from_json(v, simplify_arrays)))
+                    .collect::<Vec<_>>(),
+            )),
+        },
+        Json::Object(values) => Obj::List(List::from(
+            values
+                .into_iter()
+                .map(|(k, v)| (Some(k), from_json(v, simplify_arrays)))
+                .collect::<Vec<_>>(),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "toJSON")]
+pub struct PrimitiveToJSON;
+impl Callable for PrimitiveToJSON {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("auto_unbox")), Expr::Bool(false)),
+            (Some(String::from("pretty")), Expr::Bool(false)),
+            (Some(String::from("na")), Expr::String(String::from("null"))),
+            (
+                Some(String::from("inf")),
+                Expr::String(String::from("null")),
+            ),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = args.try_get_named("x")?.force(stack)?;
+        let auto_unbox = args.try_get_named("auto_unbox")?.force(stack)?.try_into()?;
+        let pretty = args.try_get_named("pretty")?.force(stack)?.try_into()?;
+        let na: String = args.try_get_named("na")?.force(stack)?.try_into()?;
+        let inf: String = args.try_get_named("inf")?.force(stack)?.try_into()?;
+
+        let mut writer = Writer {
+            auto_unbox,
+            pretty,
+            na: ("na", na).try_into()?,
+            inf: ("inf", inf).try_into()?,
+            out: String::new(),
+        };
+
+        writer.write(x, 0)?;
+        Ok(Obj::Vector(Vector::from(vec![writer.out])))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "fromJSON")]
+pub struct PrimitiveFromJSON;
+impl Callable for PrimitiveFromJSON {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("text")), Expr::Missing),
+            (Some(String::from("simplify")), Expr::Bool(true)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let text: String = args.try_get_named("text")?.force(stack)?.try_into()?;
+        let simplify = args.try_get_named("simplify")?.force(stack)?.try_into()?;
+
+        let json = Parser::new(&text).parse()?;
+        Ok(from_json(json, simplify))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Json, Parser};
+    use crate::{r, r_expect};
+
+    #[test]
+    fn vectors_and_lists_are_written() {
+        assert_eq!(
+            r! {{ r#"toJSON(list(a = c(1, 2.5), b = "x", c = list(TRUE, NULL)))"# }},
+            r! {{ r#"'{"a":[1,2.5],"b":["x"],"c":[[true],{}]}'"# }}
+        );
+
+        assert_eq!(
+            r! {{ r#"toJSON(list(a = 1, b = "x\n"), auto_unbox = TRUE)"# }},
+            r! {{ r#"'{"a":1,"b":"x\\n"}'"# }}
+        );
+    }
+
+    #[test]
+    fn pretty_output_is_indented() {
+        assert_eq!(
+            r! {{ r#"toJSON(list(a = [1, 2], b = list(c = 3)), pretty = TRUE)"# }},
+            r! {{ "'{\n  \"a\": [1, 2],\n  \"b\": {\n    \"c\": [3]\n  }\n}'" }}
+        );
+    }
+
+    #[test]
+    fn missing_and_non_finite_values() {
+        assert_eq!(r! {{ "toJSON(c(1, NA, Inf))" }}, r! {{ "'[1,null,null]'" }});
+
+        assert_eq!(
+            r! {{ "toJSON(c(1, NA, -Inf), na = 'string', inf = 'string')" }},
+            r! {{ r#"'[1,"NA","-Inf"]'"# }}
+        );
+
+        assert!(r! {{ "toJSON(1, na = 'zero')" }}.is_err());
+    }
+
+    #[test]
+    fn arrays_are_simplified() {
+        assert_eq!(r! {{ "fromJSON('[1, 2, null]')" }}, r! {{ "c(1, 2, NA)" }});
+
+        assert_eq!(
+            r! {{ r#"fromJSON('[true, null]')"# }},
+            r! {{ "c(TRUE, NA)" }}
+        );
+
+        assert_eq!(
+            r! {{ r#"fromJSON('{"a": ["x", null], "b": [1, "two"]}')"# }},
+            r! {{ r#"list(a = c("x", NA), b = list(1, "two"))"# }}
+        );
+
+        assert_eq!(
+            r! {{ "fromJSON('[1, 2]', simplify = FALSE)" }},
+            r! {{ "list(1, 2)" }}
+        );
+    }
+
+    #[test]
+    fn values_round_trip() {
+        r_expect! {{ r#"
+            x <- list(a = c(1.5, NA, Inf), b = c("p", "q"), c = list(d = TRUE))
+            y <- fromJSON(toJSON(x, na = "string", inf = "string"))
+            toJSON(y, na = "string", inf = "string") == toJSON(x, na = "string", inf = "string")
+        "# }}
+    }
+
+    #[test]
+    fn unicode_escapes_are_parsed() {
+        let json = Parser::new(r#""caf\u00e9 \ud83d\ude00""#).parse().unwrap();
+        assert_eq!(json, Json::String("café 😀".to_string()));
+    }
+
+    #[test]
+    fn invalid_json_is_an_error() {
+        assert!(r! {{ "fromJSON('[1, 2')" }}.is_err());
+        assert!(r! {{ "fromJSON('{\"a\" 1}')" }}.is_err());
+        assert!(r! {{ "fromJSON('1 2')" }}.is_err());
+    }
+}
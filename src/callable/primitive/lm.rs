@@ -0,0 +1,818 @@
+use std::fmt;
+use std::rc::Rc;
+
+use r_derive::*;
+
+use super::formula::Terms;
+use crate::callable::core::*;
+use crate::err;
+use crate::lang::*;
+use crate::object::attributes::Attributes;
+use crate::object::*;
+
+const LM: &str = "lm";
+const SUMMARY_LM: &str = "summary.lm";
+
+/// Evaluate the variables of a formula, with data taking precedence over
+/// the formula's environment
+///
+/// Missing values are given as `NaN`s.
+fn eval_variable(
+    var: &Expr,
+    data: &Rc<Environment>,
+    stack: &mut CallStack,
+) -> Result<Vec<f64>, Signal> {
+    match Obj::Closure(var.clone(), data.clone()).force(stack)? {
+        Obj::Vector(Vector::Character(_)) => {
+            err!(format!("non-numeric values in variable '{var}'"))
+        }
+        values => values.try_into(),
+    }
+}
+
+fn data_env(data: Obj, env: Rc<Environment>) -> Result<Rc<Environment>, Signal> {
+    let data_env = Rc::new(Environment {
+        parent: Some(env),
+        ..Default::default()
+    });
+
+    match data {
+        Obj::Null => (),
+        Obj::List(l) => data_env.append(l),
+        _ => return err!("'data' must be a list"),
+    }
+
+    Ok(data_env)
+}
+
+/// Build the columns of a model matrix, along with the name of each column
+///
+/// Without any variables, as when only fitting an intercept, the matrix has
+/// the given number of rows.
+fn model_matrix(
+    terms: &Terms,
+    data: &Rc<Environment>,
+    rows: usize,
+    stack: &mut CallStack,
+) -> Result<(Vec<String>, Vec<Vec<f64>>), Signal> {
+    let mut variables = vec![];
+    for var in terms.variables.iter() {
+        if Some(var) != terms.response.as_ref() {
+            variables.push((var.to_string(), eval_variable(var, data, stack)?));
+        }
+    }
+
+    let n = variables.iter().map(|(_, v)| v.len()).max().unwrap_or(rows);
+    if variables.iter().any(|(_, v)| v.len() != n) {
+        return err!("variable lengths differ");
+    }
+
+    let mut names = vec![];
+    let mut columns = vec![];
+
+    if terms.intercept {
+        names.push(String::from("(Intercept)"));
+        columns.push(vec![1.0; n]);
+    }
+
+    // interactions are the product of their variables' columns
+    for (label, term) in terms.labels().into_iter().zip(terms.terms.iter()) {
+        let mut column = vec![1.0; n];
+        for factor in term {
+            let Some((_, values)) = variables.iter().find(|(name, _)| name == factor) else {
+                return err!(format!("variable '{factor}' not found"));
+            };
+            column.iter_mut().zip(values).for_each(|(c, v)| *c *= v);
+        }
+
+        names.push(label);
+        columns.push(column);
+    }
+
+    Ok((names, columns))
+}
+
+/// Solve least squares by Householder QR decomposition
+///
+/// Returns the coefficients, and their standard errors for a unit residual
+/// variance.
+fn qr_solve(columns: &[Vec<f64>], y: &[f64]) -> Result<(Vec<f64>, Vec<f64>), Signal> {
+    let n = y.len();
+    let p = columns.len();
+    if n <= p {
+        return err!("more observations than coefficients are required");
+    }
+
+    let mut a = columns.to_vec();
+    let mut qty = y.to_vec();
+
+    for j in 0..p {
+        let norm = (j..n).map(|i| a[j][i] * a[j][i]).sum::<f64>().sqrt();
+        let scale = columns[j].iter().map(|v| v.abs()).fold(0.0, f64::max);
+        if norm <= 1e-10 * scale.max(1.0) {
+            return err!("model matrix is rank deficient");
+        }
+
+        // reflect column j onto the axis, applying the same reflection to
+        // the remaining columns and the response
+        let alpha = if a[j][j] > 0.0 { -norm } else { norm };
+        let mut v = (0..n)
+            .map(|i| if i < j { 0.0 } else { a[j][i] })
+            .collect::<Vec<_>>();
+        v[j] -= alpha;
+        let vnorm = v.iter().map(|x| x * x).sum::<f64>();
+
+        let reflect = |x: &mut Vec<f64>| {
+            let d = (j..n).map(|i| v[i] * x[i]).sum::<f64>() * 2.0 / vnorm;
+            (j..n).for_each(|i| x[i] -= d * v[i]);
+        };
+
+        a.iter_mut().skip(j).for_each(reflect);
+        reflect(&mut qty);
+    }
+
+    // back substitution for coefficients
+    let r = |i: usize, j: usize| a[j][i];
+    let mut coef = vec![0.0; p];
+    for i in (0..p).rev() {
+        let s = (i + 1..p).map(|j| r(i, j) * coef[j]).sum::<f64>();
+        coef[i] = (qty[i] - s) / r(i, i);
+    }
+
+    // the squared row sums of R's inverse are the diagonal of (X'X)^-1
+    let mut unscaled = vec![0.0; p];
+    for k in 0..p {
+        let mut column = vec![0.0; k + 1];
+        for i in (0..=k).rev() {
+            let e = if i == k { 1.0 } else { 0.0 };
+            let s = (i + 1..=k).map(|j| r(i, j) * column[j]).sum::<f64>();
+            column[i] = (e - s) / r(i, i);
+            unscaled[i] += column[i] * column[i];
+        }
+    }
+
+    Ok((coef, unscaled.into_iter().map(f64::sqrt).collect()))
+}
+
+fn numeric(x: Vec<f64>) -> Obj {
+    Obj::Vector(Vector::from(x))
+}
+
+/// A numeric vector named by `names`, with `NaN`s as missing values
+fn named(x: Vec<f64>, names: Vec<String>) -> Obj {
+    let x = x
+        .into_iter()
+        .map(|x| match x.is_nan() {
+            true => OptionNA::NA,
+            false => OptionNA::Some(x),
+        })
+        .collect::<Vec<_>>();
+
+    let attrs = Attributes::new().with("names", names);
+    Obj::Vector(Vector::from(x).with_attributes(attrs))
+}
+
+fn character(x: Vec<String>) -> Obj {
+    Obj::Vector(Vector::from(x))
+}
+
+/// Fit a linear model by least squares
+///
+/// As with R's default `na.action = na.omit`, observations with a missing
+/// value in any variable are dropped. Residuals and fitted values are named
+/// by the position of each remaining observation.
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "lm")]
+pub struct PrimitiveLm;
+impl Callable for PrimitiveLm {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("formula")), Expr::Missing),
+            (Some(String::from("data")), Expr::Null),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let formula = args.try_get_named("formula")?.force(stack)?;
+        let Obj::Formula(expr, env) = formula.clone() else {
+            return err!("'formula' must be a formula");
+        };
+
+        let data = args.try_get_named("data")?.force(stack)?;
+        let names = match &data {
+            Obj::List(l) => Some(
+                l.values
+                    .borrow()
+                    .iter()
+                    .filter_map(|(k, _)| k.clone())
+                    .collect::<Vec<_>>(),
+            ),
+            _ => None,
+        };
+
+        let terms = Terms::new(&expr, names.as_deref())?;
+        let Some(response) = &terms.response else {
+            return err!("formula must have a response");
+        };
+
+        let data = data_env(data, env)?;
+        let y = eval_variable(response, &data, stack)?;
+        let (coef_names, columns) = model_matrix(&terms, &data, y.len(), stack)?;
+        if columns.iter().any(|c| c.len() != y.len()) {
+            return err!("variable lengths differ");
+        }
+
+        // observations with missing values are omitted
+        let kept = (0..y.len())
+            .filter(|i| !y[*i].is_nan() && columns.iter().all(|c| !c[*i].is_nan()))
+            .collect::<Vec<_>>();
+        let obs_names = kept.iter().map(|i| (i + 1).to_string()).collect::<Vec<_>>();
+        let y = kept.iter().map(|i| y[*i]).collect::<Vec<_>>();
+        let columns = columns
+            .iter()
+            .map(|c| kept.iter().map(|i| c[*i]).collect())
+            .collect::<Vec<Vec<f64>>>();
+
+        let (coef, unscaled) = qr_solve(&columns, &y)?;
+
+        let n = y.len();
+        let fitted = (0..n)
+            .map(|i| columns.iter().zip(&coef).map(|(c, b)| c[i] * b).sum())
+            .collect::<Vec<f64>>();
+        let residuals = y
+            .iter()
+            .zip(&fitted)
+            .map(|(y, f)| y - f)
+            .collect::<Vec<_>>();
+
+        let df = (n - coef.len()) as f64;
+        let rss = residuals.iter().map(|r| r * r).sum::<f64>();
+        let sigma = (rss / df).sqrt();
+
+        // total sum of squares is about the mean only when fit with an intercept
+        let center = if terms.intercept {
+            y.iter().sum::<f64>() / n as f64
+        } else {
+            0.0
+        };
+        let tss = y.iter().map(|y| (y - center).powi(2)).sum::<f64>();
+        let r_squared = 1.0 - rss / tss;
+        let df_int = if terms.intercept { 1.0 } else { 0.0 };
+        let adj_r_squared = 1.0 - (1.0 - r_squared) * (n as f64 - df_int) / df;
+
+        let std_errors = unscaled.iter().map(|se| sigma * se).collect::<Vec<_>>();
+        let t_values = coef
+            .iter()
+            .zip(&std_errors)
+            .map(|(b, se)| b / se)
+            .collect::<Vec<_>>();
+        let p_values = t_values.iter().map(|t| t_test(*t, df)).collect::<Vec<_>>();
+
+        let fit = vec![
+            (
+                Some(String::from("coefficients")),
+                named(coef, coef_names.clone()),
+            ),
+            (
+                Some(String::from("residuals")),
+                named(residuals, obs_names.clone()),
+            ),
+            (
+                Some(String::from("fitted.values")),
+                named(fitted, obs_names),
+            ),
+            (
+                Some(String::from("std.errors")),
+                named(std_errors, coef_names.clone()),
+            ),
+            (
+                Some(String::from("t.values")),
+                named(t_values, coef_names.clone()),
+            ),
+            (Some(String::from("p.values")), named(p_values, coef_names)),
+            (Some(String::from("sigma")), numeric(vec![sigma])),
+            (Some(String::from("df.residual")), numeric(vec![df])),
+            (Some(String::from("r.squared")), numeric(vec![r_squared])),
+            (
+                Some(String::from("adj.r.squared")),
+                numeric(vec![adj_r_squared]),
+            ),
+            (Some(String::from("formula")), formula),
+            // names of the fitted data, used to expand `.` when predicting
+            (
+                Some(String::from("data.names")),
+                character(names.unwrap_or_default()),
+            ),
+        ];
+
+        Ok(Obj::List(List::classed(fit, &[LM])))
+    }
+}
+
+/// Evaluate the `object` argument, which must be a fit linear model
+fn lm_arg(args: &mut Obj, stack: &mut CallStack) -> Result<List, Signal> {
+    match args.try_get_named("object")?.force(stack)? {
+        Obj::List(l) if l.inherits(LM) => Ok(l),
+        _ => err!("'object' must be a linear model fit by 'lm'"),
+    }
+}
+
+fn element(x: &List, name: &str) -> EvalResult {
+    Obj::List(x.clone()).try_get_named(name)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "summary.lm")]
+pub struct PrimitiveSummaryLm;
+impl Callable for PrimitiveSummaryLm {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("object")), Expr::Missing),
+            (None, Expr::Ellipsis(None)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let fit = lm_arg(&mut Obj::List(args), stack)?;
+        let values = fit.values.borrow().clone();
+        Ok(Obj::List(List::classed(values, &[SUMMARY_LM])))
+    }
+}
+
+/// Predict from a linear model fit
+///
+/// Predictions for new data with missing values are `NA`.
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "predict.lm")]
+pub struct PrimitivePredictLm;
+impl Callable for PrimitivePredictLm {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("object")), Expr::Missing),
+            (Some(String::from("newdata")), Expr::Null),
+            (None, Expr::Ellipsis(None)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let fit = lm_arg(&mut args, stack)?;
+        let newdata = args.try_get_named("newdata")?.force(stack)?;
+        if newdata == Obj::Null {
+            return element(&fit, "fitted.values");
+        }
+
+        let Obj::Formula(expr, env) = element(&fit, "formula")? else {
+            return err!("'object' has no formula");
+        };
+
+        let coef: Vec<f64> = element(&fit, "coefficients")?.try_into()?;
+        let names: Vec<String> = element(&fit, "data.names")?.try_into()?;
+        let terms = Terms::new(&expr, Some(&names))?;
+        let data = data_env(newdata, env)?;
+        let (_, columns) = model_matrix(&terms, &data, 1, stack)?;
+
+        let n = columns.iter().map(|c| c.len()).max().unwrap_or(0);
+        let predicted = (0..n)
+            .map(|i| columns.iter().zip(&coef).map(|(c, b)| c[i] * b).sum())
+            .collect::<Vec<f64>>();
+        let names = (1..=n).map(|i| i.to_string()).collect();
+
+        Ok(named(predicted, names))
+    }
+}
+
+/// Two-sided p-value of a t statistic
+fn t_test(t: f64, df: f64) -> f64 {
+    incomplete_beta(df / 2.0, 0.5, df / (df + t * t))
+}
+
+/// Upper tail p-value of an F statistic
+fn f_test(f: f64, df1: f64, df2: f64) -> f64 {
+    incomplete_beta(df2 / 2.0, df1 / 2.0, df2 / (df2 + df1 * f))
+}
+
+fn ln_gamma(x: f64) -> f64 {
+    // Lanczos approximation
+    const G: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let series = G
+        .iter()
+        .enumerate()
+        .fold(1.000000000190015, |s, (i, g)| s + g / (x + 1.0 + i as f64));
+
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// Regularized incomplete beta function, `I_x(a, b)`
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    } else if x >= 1.0 {
+        return 1.0;
+    }
+
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+
+    // continued fraction converges quickly on this side of the mean
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+fn beta_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    d = 1.0 / if d.abs() < TINY { TINY } else { d };
+    let mut h = d;
+
+    for m in 1..300 {
+        let m = m as f64;
+        for num in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + num * d;
+            d = 1.0 / if d.abs() < TINY { TINY } else { d };
+            c = 1.0 + num / c;
+            c = if c.abs() < TINY { TINY } else { c };
+            h *= d * c;
+        }
+
+        if (d * c - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+
+    h
+}
+
+/// Format a number to a number of significant digits
+fn signif(x: f64, digits: usize) -> String {
+    DefaultDebug::fmt_digits(&x, digits)
+}
+
+fn format_p(p: f64) -> String {
+    match p {
+        p if p < 2e-16 => String::from("<2e-16"),
+        p if p < 1e-4 => {
+            // exponents are written with at least two digits, as in `1e-05`
+            let s = format!("{:.2e}", p);
+            match s.split_once("e-") {
+                Some((m, e)) if e.len() < 2 => format!("{m}e-0{e}"),
+                _ => s,
+            }
+        }
+        p => signif(p, 3),
+    }
+}
+
+fn signif_stars(p: f64) -> &'static str {
+    match p {
+        p if p < 0.001 => "***",
+        p if p < 0.01 => "**",
+        p if p < 0.05 => "*",
+        p if p < 0.1 => ".",
+        _ => "",
+    }
+}
+
+/// Write rows of cells, right-aligned by column unless the first column
+/// holds row names
+fn write_table(f: &mut fmt::Formatter<'_>, rows: &[Vec<String>], row_names: bool) -> fmt::Result {
+    let ncol = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let widths = (0..ncol)
+        .map(|j| rows.iter().filter_map(|r| r.get(j)).map(|c| c.len()).max())
+        .collect::<Vec<_>>();
+
+    for row in rows {
+        let cells = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(j, (c, w))| match w.unwrap_or(0) {
+                w if j == 0 && row_names => format!("{c:<w$}"),
+                w => format!("{c:>w$}"),
+            })
+            .collect::<Vec<_>>();
+        writeln!(f, "{}", cells.join(" ").trim_end())?;
+    }
+
+    Ok(())
+}
+
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let h = (sorted.len() - 1) as f64 * q;
+    let (lo, hi) = (h.floor() as usize, h.ceil() as usize);
+    sorted[lo] + (h - lo as f64) * (sorted[hi] - sorted[lo])
+}
+
+/// A linear model fit or its summary, as printed
+struct Lm<'a>(&'a List);
+
+impl fmt::Display for Lm<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // lists missing the elements of a fit are printed as lists
+        fmt_lm(self.0, f).unwrap_or_else(|| write!(f, "{}", Obj::List(self.0.clone())))
+    }
+}
+
+/// Print the `x` argument of a print method
+fn print_lm(args: List, stack: &mut CallStack) -> EvalResult {
+    let x = Obj::List(args).try_get_named("x")?.force(stack)?;
+    match &x {
+        Obj::List(l) => println!("{}", Lm(l)),
+        x => println!("{x}"),
+    }
+
+    Ok(x)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "print.lm")]
+pub struct PrimitivePrintLm;
+impl Callable for PrimitivePrintLm {
+    fn formals(&self) -> ExprList {
+        super::print::FORMALS.clone()
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        print_lm(args, stack)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "print.summary.lm")]
+pub struct PrimitivePrintSummaryLm;
+impl Callable for PrimitivePrintSummaryLm {
+    fn formals(&self) -> ExprList {
+        super::print::FORMALS.clone()
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        print_lm(args, stack)
+    }
+}
+
+/// Format linear model fits and their summaries, returning `None` for a
+/// list missing the elements of a fit
+fn fmt_lm(x: &List, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+    let numbers = |name: &str| -> Vec<f64> {
+        element(x, name)
+            .and_then(|v| v.try_into())
+            .unwrap_or_default()
+    };
+
+    let formula = element(x, "formula").ok()?;
+    let names = match element(x, "coefficients").ok()? {
+        Obj::Vector(v) => v.attributes().get("names")?.to_vec(),
+        _ => return None,
+    };
+    let coef = numbers("coefficients");
+
+    let result = (|| {
+        writeln!(f, "\nCall:\nlm(formula = {formula})\n")?;
+
+        if !x.inherits(SUMMARY_LM) {
+            writeln!(f, "Coefficients:")?;
+            let values = coef.iter().map(|c| signif(*c, 5)).collect();
+            return write_table(f, &[names.clone(), values], false);
+        }
+
+        let mut residuals = numbers("residuals");
+        residuals.sort_by(|a, b| a.total_cmp(b));
+        writeln!(f, "Residuals:")?;
+        let labels = ["Min", "1Q", "Median", "3Q", "Max"]
+            .map(String::from)
+            .to_vec();
+        let quantiles = [0.0, 0.25, 0.5, 0.75, 1.0]
+            .iter()
+            .map(|q| signif(quantile(&residuals, *q), 4))
+            .collect();
+        write_table(f, &[labels, quantiles], false)?;
+
+        writeln!(f, "\nCoefficients:")?;
+        let (se, t, p) = (
+            numbers("std.errors"),
+            numbers("t.values"),
+            numbers("p.values"),
+        );
+        let header = ["", "Estimate", "Std. Error", "t value", "Pr(>|t|)", ""];
+        let mut rows = vec![header.map(String::from).to_vec()];
+        for i in 0..coef.len() {
+            rows.push(vec![
+                names[i].clone(),
+                signif(coef[i], 5),
+                signif(se[i], 5),
+                signif(t[i], 4),
+                format_p(p[i]),
+                signif_stars(p[i]).to_string(),
+            ]);
+        }
+        write_table(f, &rows, true)?;
+        writeln!(f, "---")?;
+        writeln!(
+            f,
+            "Signif. codes:  0 '***' 0.001 '**' 0.01 '*' 0.05 '.' 0.1 ' ' 1\n"
+        )?;
+
+        let df = numbers("df.residual").first().copied().unwrap_or(0.0);
+        let sigma = numbers("sigma").first().copied().unwrap_or(0.0);
+        let r2 = numbers("r.squared").first().copied().unwrap_or(0.0);
+        let adj_r2 = numbers("adj.r.squared").first().copied().unwrap_or(0.0);
+        writeln!(
+            f,
+            "Residual standard error: {} on {df} degrees of freedom",
+            signif(sigma, 4)
+        )?;
+        writeln!(
+            f,
+            "Multiple R-squared:  {},\tAdjusted R-squared:  {}",
+            signif(r2, 4),
+            signif(adj_r2, 4)
+        )?;
+
+        // overall F test, against a model of only the intercept
+        let intercept = names.first().is_some_and(|n| n == "(Intercept)");
+        let df1 = (coef.len() - intercept as usize) as f64;
+        if df1 > 0.0 {
+            let fstat = (r2 / df1) / ((1.0 - r2) / df);
+            writeln!(
+                f,
+                "F-statistic: {} on {df1} and {df} DF,  p-value: {}",
+                signif(fstat, 4),
+                format_p(f_test(fstat, df1, df))
+            )?;
+        }
+
+        Ok(())
+    })();
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{f_test, t_test, Lm};
+    use crate::object::Obj;
+    use crate::r;
+    use crate::repl::eval;
+
+    const DATA: &str = "d <- list(x = c(1, 2, 3, 4, 5), y = c(2.1, 3.9, 6.2, 7.8, 10.1))";
+
+    fn numbers(input: &str) -> Vec<f64> {
+        eval(&format!("{DATA}; {input}"))
+            .and_then(|x| x.try_into())
+            .unwrap()
+    }
+
+    fn assert_near(x: Vec<f64>, expected: &[f64]) {
+        assert_eq!(x.len(), expected.len());
+        for (x, e) in x.iter().zip(expected) {
+            assert!((x - e).abs() < 1e-4, "{x} != {e}");
+        }
+    }
+
+    #[test]
+    fn fits_least_squares() {
+        // as computed by R
+        let fit = "fit <- lm(y ~ x, data = d)";
+        assert_near(numbers(&format!("{fit}; coef(fit)")), &[0.05, 1.99]);
+        assert_near(
+            numbers(&format!("{fit}; fit$std.errors")),
+            &[0.19807, 0.05972],
+        );
+        assert_near(numbers(&format!("{fit}; fit$r.squared")), &[0.99731]);
+    }
+
+    #[test]
+    fn coefficients_are_named() {
+        let names = eval(&format!("{DATA}; names(coef(lm(y ~ x, data = d)))"));
+        assert_eq!(names, r! { c("(Intercept)", "x") });
+
+        let names = eval(&format!("{DATA}; names(residuals(lm(y ~ x, data = d)))"));
+        assert_eq!(names, r! { c("1", "2", "3", "4", "5") });
+    }
+
+    #[test]
+    fn observations_with_missing_values_are_omitted() {
+        let fit =
+            "d <- list(x = c(1, 2, NA, 3, 4, 7, 5), y = c(2.1, 3.9, 12, 6.2, 7.8, NA, 10.1)); \
+            fit <- lm(y ~ x, data = d)";
+        assert_near(numbers(&format!("{fit}; coef(fit)")), &[0.05, 1.99]);
+        assert_eq!(
+            eval(&format!("{fit}; names(fit$fitted.values)")),
+            r! { c("1", "2", "4", "5", "7") }
+        );
+        assert!(r! { lm(y ~ x, data = list(x = c("a", "b", "c"), y = c(1, 2, 3))) }.is_err());
+    }
+
+    #[test]
+    fn fits_without_intercept() {
+        assert_near(numbers("coef(lm(y ~ x - 1, data = d))"), &[2.00364]);
+    }
+
+    #[test]
+    fn fits_interactions() {
+        let coef = numbers("d$z <- c(1, 0, 1, 0, 1); coef(lm(y ~ x * z, data = d))");
+        assert_eq!(coef.len(), 4);
+    }
+
+    #[test]
+    fn residuals_and_predictions() {
+        let fit = "fit <- lm(y ~ x, data = d)";
+        assert_near(
+            numbers(&format!("{fit}; residuals(fit)")),
+            &[0.06, -0.13, 0.18, -0.21, 0.1],
+        );
+        assert_near(
+            numbers(&format!("{fit}; predict(fit, newdata = list(x = c(6, 7)))")),
+            &[11.99, 13.98],
+        );
+        assert_near(
+            numbers(&format!("{fit}; predict(fit)")),
+            &[2.04, 4.03, 6.02, 8.01, 10.0],
+        );
+    }
+
+    #[test]
+    fn predictions_expand_dot_with_fitted_data() {
+        let fit = "fit <- lm(y ~ ., data = d)";
+        assert_near(
+            numbers(&format!(
+                "{fit}; predict(fit, newdata = list(x = c(6, 7), z = c(0, 0)))"
+            )),
+            &[11.99, 13.98],
+        );
+    }
+
+    fn class(input: &str) -> Vec<String> {
+        eval(&format!("{DATA}; fit <- lm(y ~ x, data = d); {input}"))
+            .unwrap()
+            .class()
+    }
+
+    #[test]
+    fn fits_are_classed_lists() {
+        assert_eq!(class("fit"), ["lm"]);
+        assert_eq!(class("summary(fit)"), ["summary.lm"]);
+        assert_eq!(class("unclass(fit)"), ["list"]);
+        assert_eq!(class("unserialize(serialize(fit))"), ["lm"]);
+        assert!(
+            r! { fit <- lm(y ~ x, data = list(x = c(1, 2, 3), y = c(1, 3, 2))); fit$class }
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn methods_can_be_replaced() {
+        let summary = eval(&format!(
+            "{DATA}; summary.lm <- function(object, ...) 42; summary(lm(y ~ x, data = d))"
+        ));
+        assert_eq!(summary, r! { 42 });
+    }
+
+    #[test]
+    fn rank_deficient_fits_error() {
+        assert!(r! { lm(y ~ x, data = list(x = c(1, 1, 1), y = c(1, 2, 3))) }.is_err());
+    }
+
+    fn printed(input: &str) -> String {
+        match eval(&format!("{DATA}; {input}")) {
+            Ok(Obj::List(x)) => Lm(&x).to_string(),
+            x => panic!("expected a list, found {x:?}"),
+        }
+    }
+
+    #[test]
+    fn printing() {
+        let fit = printed("lm(y ~ x, data = d)");
+        assert!(fit.contains("lm(formula = y ~ x)"));
+        assert!(fit.contains("(Intercept)"));
+
+        let summary = printed("summary(lm(y ~ x, data = d))");
+        assert!(summary.contains("Std. Error"));
+        assert!(summary.contains("on 3 degrees of freedom"));
+    }
+
+    #[test]
+    fn distributions() {
+        // p-values as computed by R's pt() and pf()
+        assert!((t_test(2.0, 10.0) - 0.07338803).abs() < 1e-6);
+        assert!((f_test(4.0, 2.0, 10.0) - 0.05292214).abs() < 1e-6);
+    }
+}
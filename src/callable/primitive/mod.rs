@@ -1,28 +1,111 @@
 mod c;
 pub use c::PrimitiveC;
 mod callstack;
-pub use callstack::PrimitiveCallstack;
+pub use callstack::{
+    PrimitiveCallstack, PrimitiveParentFrame, PrimitiveSysCall, PrimitiveSysCalls,
+    PrimitiveSysFrames,
+};
+mod connection;
+pub use connection::{
+    PrimitiveClose, PrimitiveFile, PrimitiveOpen, PrimitiveStderr, PrimitiveStdin, PrimitiveStdout,
+    PrimitiveTextConnection,
+};
+mod debug;
+pub use debug::{
+    PrimitiveBrowser, PrimitiveDebug, PrimitiveDebugonce, PrimitiveIsdebugged,
+    PrimitiveSetBreakpoint, PrimitiveUndebug,
+};
 mod environment;
 pub use environment::PrimitiveEnvironment;
 mod eval;
 pub use eval::PrimitiveEval;
+mod files;
+pub use files::{
+    PrimitiveBasename, PrimitiveDirCreate, PrimitiveDirname, PrimitiveFileExists,
+    PrimitiveFilePath, PrimitiveFileRemove, PrimitiveListFiles, PrimitiveNormalizePath,
+    PrimitiveTempdir, PrimitiveTempfile,
+};
+mod formula;
+pub use formula::{PrimitiveAllVars, PrimitiveTerms};
+mod future;
+pub use future::{
+    FutureValue, PrimitiveCancel, PrimitiveFuture, PrimitiveFutureAssign, PrimitiveResolved,
+    PrimitiveValue,
+};
+mod generic;
+pub use generic::{
+    PrimitiveCoef, PrimitiveCoefDefault, PrimitivePredict, PrimitiveResiduals,
+    PrimitiveResidualsDefault, PrimitiveSummary,
+};
+mod ifelse;
+pub use ifelse::PrimitiveIfelse;
+mod io;
+pub use io::{
+    PrimitiveCat, PrimitiveReadLines, PrimitiveReadline, PrimitiveScan, PrimitiveWriteLines,
+};
+mod json;
+pub use json::{PrimitiveFromJSON, PrimitiveToJSON};
 mod list;
 pub use list::PrimitiveList;
+mod lm;
+pub use lm::{
+    PrimitiveLm, PrimitivePredictLm, PrimitivePrintLm, PrimitivePrintSummaryLm, PrimitiveSummaryLm,
+};
 mod names;
-pub use names::PrimitiveNames;
+pub use names::{PrimitiveAttr, PrimitiveAttrAssign, PrimitiveNames, PrimitiveNamesAssign};
+mod options;
+pub use options::{PrimitiveGetOption, PrimitiveOptions};
+mod parallel;
+pub use parallel::{PrimitiveMclapply, PrimitiveParallelMap};
 mod parent;
 pub use parent::PrimitiveParent;
+mod partial;
+pub use partial::{ComposedCall, PartialCall, PrimitiveCompose, PrimitivePartial};
 mod paste;
 pub use paste::PrimitivePaste;
 mod print;
-pub use print::PrimitivePrint;
+pub use print::{autoprint, PrimitivePrint, PrimitivePrintDefault};
 mod q;
 pub use q::PrimitiveQ;
 mod quote;
 pub use quote::PrimitiveQuote;
 mod rnorm;
 pub use rnorm::PrimitiveRnorm;
+mod rprof;
+pub use rprof::PrimitiveRprof;
 mod runif;
 pub use runif::PrimitiveRunif;
+mod save;
+pub use save::{PrimitiveLoad, PrimitiveSave, PrimitiveSaveImage};
+mod seq;
+pub use seq::{PrimitiveSeq, PrimitiveSeqAlong, PrimitiveSeqLen};
+mod serialize;
+pub use serialize::{PrimitiveReadRDS, PrimitiveSaveRDS, PrimitiveSerialize, PrimitiveUnserialize};
+mod source;
+pub use source::PrimitiveSource;
+mod stopifnot;
+pub use stopifnot::PrimitiveStopifnot;
 mod sum;
 pub use sum::PrimitiveSum;
+mod switch;
+pub use switch::PrimitiveSwitch;
+mod sys;
+pub use sys::{
+    PrimitiveSysGetenv, PrimitiveSysSetenv, PrimitiveSysSleep, PrimitiveSysTime, PrimitiveSystem2,
+};
+mod time;
+pub use time::{
+    PrimitiveAsDate, PrimitiveAsPOSIXct, PrimitiveDifftime, PrimitiveFormat, PrimitiveStrftime,
+    PrimitiveStrptime, PrimitiveSysDate, PrimitiveUnclass,
+};
+mod trace;
+pub use trace::{PrimitiveTrace, PrimitiveUntrace};
+mod traceback;
+pub use traceback::PrimitiveTraceback;
+mod vector;
+pub use vector::{
+    PrimitiveDuplicated, PrimitiveOrder, PrimitiveRank, PrimitiveRep, PrimitiveRev, PrimitiveSort,
+    PrimitiveTabulate, PrimitiveUnique, PrimitiveWhich, PrimitiveWhichMax, PrimitiveWhichMin,
+};
+mod warning;
+pub use warning::PrimitiveWarning;
@@ -1,9 +1,107 @@
 use r_derive::*;
 
 use crate::callable::core::*;
+use crate::err;
 use crate::lang::*;
 use crate::object::*;
 
+/// The names of an object
+fn names(x: Obj) -> EvalResult {
+    use Obj::*;
+    match x {
+        Null => Ok(Null),
+        Closure(_, _) => Ok(Null),
+        Vector(v) => match v.materialize().attributes().get("names") {
+            Some(names) => Ok(names.to_vec().into()),
+            None => Ok(Null),
+        },
+        Expr(_) => Ok(Null),      // handle arg lists?
+        Function(..) => Ok(Null), // return formals?
+        Formula(..) => Ok(Null),
+        External(_) => Ok(Null),
+        List(x) => {
+            Ok(x.values
+                .borrow()
+                .iter()
+                .map(|(k, _)| match k {
+                    Some(name) => OptionNA::Some(name.clone()),
+                    None => OptionNA::NA, // unlike R, unnamed elements are NAs
+                })
+                .collect::<Vec<OptionNA<String>>>()
+                .into())
+        }
+        Environment(e) => {
+            let mut names = e.values.borrow().keys().cloned().collect::<Vec<String>>();
+
+            names.sort();
+            Ok(names.into())
+        }
+    }
+}
+
+/// An attribute value as character, or `None` when it is `NULL`
+fn attr_values(value: Obj) -> Result<Option<Vec<OptionNA<String>>>, Signal> {
+    match value {
+        Obj::Null => Ok(None),
+        value => match value.as_character()? {
+            Obj::Vector(Vector::Character(v)) => Ok(Some(v.materialize().inner().borrow().clone())),
+            _ => err!("attributes must be atomic vectors"),
+        },
+    }
+}
+
+/// An object with its names replaced, or removed when `value` is `NULL`
+fn with_names(x: Obj, value: Obj) -> EvalResult {
+    let names = attr_values(value)?.unwrap_or_default();
+
+    match x {
+        Obj::Null => Ok(Obj::Null),
+        Obj::Vector(v) => {
+            let v = v.materialize();
+            if names.len() > v.len() {
+                return err!("'names' attribute must be the same length as the vector");
+            }
+
+            // unnamed elements, including those with NA names, are named ""
+            let names = match names.is_empty() {
+                true => vec![],
+                false => (0..v.len())
+                    .map(|i| match names.get(i) {
+                        Some(OptionNA::Some(name)) => name.clone(),
+                        _ => String::new(),
+                    })
+                    .collect(),
+            };
+
+            let attrs = v.attributes().clone().with("names", names);
+            Ok(Obj::Vector(v.with_attributes(attrs)))
+        }
+        Obj::List(x) => {
+            // unnamed when names are too short, as with NA names
+            let values = x
+                .values
+                .borrow()
+                .iter()
+                .enumerate()
+                .map(|(i, (_, v))| match names.get(i) {
+                    Some(OptionNA::Some(name)) => (Some(name.clone()), v.clone()),
+                    _ => (None, v.clone()),
+                })
+                .collect::<Vec<_>>();
+
+            if names.len() > values.len() {
+                return err!("'names' attribute must be the same length as the vector");
+            }
+
+            Ok(Obj::List(List {
+                attributes: x.attributes.clone(),
+                ..List::from(values)
+            }))
+        }
+        _ => err!("names can only be assigned to vectors and lists"),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 #[builtin(sym = "names")]
 pub struct PrimitiveNames;
@@ -14,31 +112,112 @@ impl Callable for PrimitiveNames {
 
     fn call_matched(&self, args: List, mut _ellipsis: List, stack: &mut CallStack) -> EvalResult {
         let x = Obj::List(args).try_get_named("x")?.force(stack)?;
+        names(x)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "names<-")]
+pub struct PrimitiveNamesAssign;
+impl Callable for PrimitiveNamesAssign {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("value")), Expr::Missing),
+        ])
+    }
+
+    fn call_matched(&self, args: List, mut _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = args.try_get_named("x")?.force(stack)?;
+        let value = args.try_get_named("value")?.force(stack)?;
+        with_names(x, value)
+    }
+}
+
+/// Get an attribute of an object
+///
+/// As with `names()`, the names of a list are its element names. Unlike R,
+/// attribute names must match exactly.
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "attr")]
+pub struct PrimitiveAttr;
+impl Callable for PrimitiveAttr {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("which")), Expr::Missing),
+        ])
+    }
+
+    fn call_matched(&self, args: List, mut _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = args.try_get_named("x")?.force(stack)?;
+        let which: String = args.try_get_named("which")?.force(stack)?.try_into()?;
+
+        if which == "names" {
+            return names(x);
+        }
+
+        let attrs = match &x {
+            Obj::Vector(v) => v.attributes(),
+            Obj::List(l) => &l.attributes,
+            _ => return Ok(Obj::Null),
+        };
+
+        match attrs.get(&which) {
+            Some(value) => Ok(value.to_vec().into()),
+            None => Ok(Obj::Null),
+        }
+    }
+}
+
+/// Set an attribute of an object, removing it when `value` is `NULL`
+///
+/// Attribute values are limited to character vectors, and other atomic
+/// vectors are converted to character.
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "attr<-")]
+pub struct PrimitiveAttrAssign;
+impl Callable for PrimitiveAttrAssign {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("which")), Expr::Missing),
+            (Some(String::from("value")), Expr::Missing),
+        ])
+    }
+
+    fn call_matched(&self, args: List, mut _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = args.try_get_named("x")?.force(stack)?;
+        let which: String = args.try_get_named("which")?.force(stack)?.try_into()?;
+        let value = args.try_get_named("value")?.force(stack)?;
+
+        if which == "names" {
+            return with_names(x, value);
+        }
+
+        let value: Vec<String> = attr_values(value)?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|v| match v {
+                OptionNA::Some(v) => v,
+                OptionNA::NA => String::from("NA"),
+            })
+            .collect();
 
-        use Obj::*;
         match x {
-            Null => Ok(Null),
-            Closure(_, _) => Ok(Null),
-            Vector(_) => Ok(Null), // named vectors currently not supported...
-            Expr(_) => Ok(Null),   // handle arg lists?
-            Function(_, _, _) => Ok(Null), // return formals?
-            List(x) => {
-                Ok(x.values
-                    .borrow()
-                    .iter()
-                    .map(|(k, _)| match k {
-                        Some(name) => OptionNA::Some(name.clone()),
-                        None => OptionNA::NA, // unlike R, unnamed elements are NAs
-                    })
-                    .collect::<Vec<OptionNA<String>>>()
-                    .into())
+            Obj::Vector(v) => {
+                let attrs = v.attributes().clone().with(&which, value);
+                Ok(Obj::Vector(v.with_attributes(attrs)))
             }
-            Environment(e) => {
-                let mut names = e.values.borrow().keys().cloned().collect::<Vec<String>>();
-
-                names.sort();
-                Ok(names.into())
+            Obj::List(mut l) => {
+                l.attributes.set(&which, value);
+                Ok(Obj::List(l))
             }
+            Obj::Null => err!("attempt to set an attribute on NULL"),
+            _ => err!("attributes can only be assigned to vectors and lists"),
         }
     }
 }
@@ -46,7 +225,7 @@ impl Callable for PrimitiveNames {
 #[cfg(test)]
 mod test {
     use crate::error::Error;
-    use crate::r;
+    use crate::{r, r_expect};
 
     #[test]
     fn no_args() {
@@ -68,4 +247,145 @@ mod test {
             r! { c("a", "b", NA, "d") }
         )
     }
+
+    #[test]
+    fn assign_names() {
+        r_expect! {{"
+            x <- list(1, 2)
+            names(x) <- c('a', 'b')
+            x$b == 2
+        "}}
+    }
+
+    #[test]
+    fn assign_vector_names() {
+        assert_eq!(
+            r! { x <- c(1, 2, 3); names(x) <- c("a", "b"); names(x) },
+            r! { c("a", "b", "") }
+        );
+        assert_eq!(
+            r! { x <- c(1, 2, 3); names(x) <- c("a", "b", "c"); names(x[c(3, 1)]) },
+            r! { c("c", "a") }
+        );
+        assert_eq!(
+            r! { x <- c(1, 2); names(x) <- c("a", "b"); names(x * 2) },
+            r! { c("a", "b") }
+        );
+        assert_eq!(
+            r! { x <- c(1, 2); names(x) <- c("a", "b"); names(x) <- NULL; names(x) },
+            r! { NULL }
+        );
+        assert!(r! { x <- c(1, 2); names(x) <- c("a", "b", "c") }.is_err());
+    }
+
+    #[test]
+    fn names_are_kept_in_arithmetic() {
+        assert_eq!(
+            r! { x <- c(1L, 2L); names(x) <- c("a", "b"); names(x + 1L) },
+            r! { c("a", "b") }
+        );
+        assert_eq!(
+            r! { x <- c(1L, 2L); names(x) <- c("a", "b"); names(x * 2) },
+            r! { c("a", "b") }
+        );
+        assert_eq!(
+            r! { x <- c(TRUE, FALSE); names(x) <- c("a", "b"); names(x + 1L) },
+            r! { c("a", "b") }
+        );
+        assert_eq!(
+            r! { y <- c(1L, 2L); names(y) <- c("a", "b"); names(2.5 - y) },
+            r! { c("a", "b") }
+        );
+    }
+
+    #[test]
+    fn named_vectors_display_names() {
+        assert_eq!(
+            r! { x <- c(1, 20); names(x) <- c("a", "b"); x }
+                .unwrap()
+                .to_string(),
+            " a  b \n 1 20 "
+        );
+    }
+
+    #[test]
+    fn replacement_keeps_existing_temporaries() {
+        r_expect! {{"
+            `*tmp*` <- 'kept'
+            z <- list(1)
+            z[[1]] <- 2
+            `*tmp*` == 'kept' && z[[1]] == 2
+        "}}
+    }
+
+    #[test]
+    fn get_attributes() {
+        assert_eq!(r! { attr(as.Date("2024-01-01"), "class") }, r! { "Date" });
+        assert_eq!(r! { attr(c(1, 2), "class") }, r! { NULL });
+        assert_eq!(
+            r! { x <- c(1, 2); names(x) <- c("a", "b"); attr(x, "names") },
+            r! { c("a", "b") }
+        );
+        assert_eq!(r! { attr(list(a = 1, b = 2), "names") }, r! { c("a", "b") });
+    }
+
+    #[test]
+    fn set_attributes() {
+        assert_eq!(
+            r! { x <- c(1, 2); attr(x, "units") <- "cm"; attr(x, "units") },
+            r! { "cm" }
+        );
+        assert_eq!(
+            r! { x <- list(1); attr(x, "class") <- c("a", "b"); attr(x, "class") },
+            r! { c("a", "b") }
+        );
+        assert_eq!(
+            r! { x <- c(1, 2); attr(x, "names") <- c("a", "b"); names(x) },
+            r! { c("a", "b") }
+        );
+        assert_eq!(
+            r! { x <- c(1, 2); attr(x, "units") <- "cm"; x * 2 },
+            r! { y <- c(2, 4); attr(y, "units") <- "cm"; y }
+        );
+    }
+
+    #[test]
+    fn remove_attributes() {
+        assert_eq!(
+            r! { x <- c(1, 2); attr(x, "units") <- "cm"; attr(x, "units") <- NULL; attr(x, "units") },
+            r! { NULL }
+        );
+        assert_eq!(
+            r! { x <- list(1); attr(x, "class") <- "a"; attr(x, "class") <- NULL; attr(x, "class") },
+            r! { NULL }
+        );
+    }
+
+    #[test]
+    fn assign_nested_names() {
+        assert_eq!(
+            r! { x <- list(a = 1, b = 2); names(x)[2] <- "c"; names(x) },
+            r! { c("a", "c") }
+        )
+    }
+
+    #[test]
+    fn assign_user_defined_replacement() {
+        r_expect! {{"
+            `second<-` <- function(x, value) { x[[2]] <- value; x }
+            x <- list(1, 2, 3)
+            second(x) <- 10
+            x[[2]] == 10
+        "}}
+    }
+
+    #[test]
+    fn assign_replacement_extra_args() {
+        r_expect! {{"
+            `at<-` <- function(x, i, value) { x[[i]] <- value; x }
+            x <- list(1, 2, 3)
+            at(x, 3) <- 30
+            x[[3]] == 30
+        "}}
+    }
 }
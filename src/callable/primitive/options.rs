@@ -0,0 +1,162 @@
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::context::Context;
+use crate::err;
+use crate::internal_err;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+use crate::options;
+use crate::parser::Localization;
+
+/// Check that an option consulted by the interpreter has a usable value
+fn check(name: &str, value: &Obj) -> Result<(), Signal> {
+    if name != options::LOCALE || *value == Obj::Null {
+        return Ok(());
+    }
+
+    let locale: String = value.clone().try_into()?;
+    match locale.parse::<Localization>() {
+        Ok(_) => Ok(()),
+        Err(_) => err!(format!("invalid locale '{locale}'")),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "options")]
+pub struct PrimitiveOptions;
+impl Callable for PrimitiveOptions {
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let Obj::List(args) = stack.eval_list_eager(args)? else {
+            return internal_err!();
+        };
+
+        // without arguments, list all options
+        if args.values.borrow().is_empty() {
+            let all = options::all().into_iter().map(|(k, v)| (Some(k), v));
+            return Ok(Obj::List(List::from(all.collect::<Vec<_>>())));
+        }
+
+        // set each option, collecting its previous value. Unnamed lists set
+        // each of their elements, as when restoring previous values, while
+        // unnamed strings query options by name
+        let mut old = vec![];
+        for (name, value) in args.values.borrow().clone() {
+            match (name, value) {
+                (Some(name), value) => old.push(set(name, value)?),
+                (None, Obj::List(values)) => {
+                    for (name, value) in values.elements() {
+                        let Some(name) = name else {
+                            return err!("list elements must be named options");
+                        };
+
+                        old.push(set(name, value)?);
+                    }
+                }
+                (None, names @ Obj::Vector(Vector::Character(_))) => {
+                    let names: Vec<String> = names.try_into()?;
+                    for name in names {
+                        let value = options::get(&name).unwrap_or_default();
+                        old.push((Some(name), value));
+                    }
+                }
+                _ => return err!("options must be provided as named arguments"),
+            }
+        }
+
+        Ok(Obj::List(List::from(old)))
+    }
+}
+
+/// Set an option, returning its name and previous value
+fn set(name: String, value: Obj) -> Result<(Option<String>, Obj), Signal> {
+    check(&name, &value)?;
+    let old = options::set(&name, value).unwrap_or_default();
+    Ok((Some(name), old))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "getOption")]
+pub struct PrimitiveGetOption;
+impl Callable for PrimitiveGetOption {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("default")), Expr::Null),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let name: String = args.try_get_named("x")?.force(stack)?.try_into()?;
+
+        match options::get(&name) {
+            Some(value) => Ok(value),
+            None => args.try_get_named("default")?.force(stack),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::repl::eval;
+    use crate::{r, r_expect};
+
+    #[test]
+    fn previous_values_are_returned() {
+        r_expect! {{"
+            options(verbose_calls = TRUE)
+            old <- options(verbose_calls = FALSE)
+            old$verbose_calls
+        "}}
+    }
+
+    #[test]
+    fn input_is_parsed_using_the_locale() {
+        assert_eq!(eval("getOption('locale')"), r! { "en" });
+        eval("options(locale = 'es')").unwrap();
+        assert_eq!(eval("si (CIERTO) 1 sino 2"), r! { 1 });
+        assert!(r! { options(locale = "klingon") }.is_err());
+    }
+
+    #[test]
+    fn previous_values_are_restored() {
+        r_expect! {{"
+            old <- options(digits = 3, unset = 1)
+            options(old)
+            getOption('digits') == 7 && getOption('unset', 0) == 0
+        "}}
+    }
+
+    #[test]
+    fn options_are_queried_by_name() {
+        assert_eq!(
+            r! { options("digits", "unset") },
+            r! { list(digits = 7L, unset = NULL) }
+        );
+    }
+
+    #[test]
+    fn unnamed_options_are_an_error() {
+        assert!(r! { options(TRUE) }.is_err())
+    }
+
+    #[test]
+    fn defaults_are_set() {
+        r_expect! { getOption("digits") == 7 && getOption("width") == 80 }
+    }
+
+    #[test]
+    fn unset_options_use_default() {
+        assert_eq!(r! { getOption("unset") }, r! { NULL });
+        assert_eq!(r! { getOption("unset", 3) }, r! { 3 });
+    }
+
+    #[test]
+    fn digits_are_used_when_printing() {
+        let print = |input: &str| format!("{}", crate::repl::eval(input).unwrap());
+        assert_eq!(print("1 / 3"), "[1] 0.3333333");
+        assert_eq!(print("options(digits = 3); 1 / 3"), "[1] 0.333");
+        assert_eq!(print("options(digits = 3); 123456"), "[1] 123456");
+    }
+}
@@ -0,0 +1,289 @@
+use std::rc::Rc;
+use std::thread;
+
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::context::Context;
+use crate::err;
+use crate::error::Error;
+use crate::future::WORKER_STACK_SIZE;
+use crate::internal_err;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+use crate::options;
+
+/// Names under which a worker binds the mapped function and its arguments
+const WORKER_FN: &str = "*fn*";
+const WORKER_X: &str = "*x*";
+
+type Elements = Vec<(Option<String>, Obj)>;
+
+/// The elements of a list or vector, as the values to map over, named by
+/// the names of the list or vector
+fn elements(x: Obj) -> Result<Elements, Signal> {
+    match x {
+        Obj::Null => Ok(vec![]),
+        Obj::List(l) => Ok(l.elements()),
+        Obj::Vector(v) => {
+            let v = v.materialize();
+            let names = v.attributes().get("names").map(<[String]>::to_vec);
+            Ok((0..v.len())
+                .filter_map(|i| v.get(i))
+                .enumerate()
+                .map(|(i, x)| {
+                    // as with `x[[i]]`, elements are passed without their name
+                    let mut attrs = x.attributes().clone();
+                    attrs.set("names", vec![]);
+
+                    let name = names.as_ref().and_then(|names| names.get(i).cloned());
+                    (name, Obj::Vector(x.with_attributes(attrs)))
+                })
+                .collect())
+        }
+        _ => err!("argument must be a list or vector"),
+    }
+}
+
+/// The number of worker threads to use, defaulting to the `mc.cores` option
+/// or otherwise the available parallelism
+fn cores(cores: Obj, name: &str) -> Result<usize, Signal> {
+    let cores = match cores {
+        Obj::Null => options::get_integer(options::MC_CORES)
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get() as i32)),
+        cores => cores.try_into()?,
+    };
+
+    match cores {
+        n if n >= 1 => Ok(n as usize),
+        _ => Error::ArgumentInvalid(name.to_string()).into(),
+    }
+}
+
+/// Evaluate a task in a new interpreter
+///
+/// A task is a list of the function, additional arguments and the elements
+/// to call it with, returning a list of results.
+fn worker(task: Snapshot) -> Result<Snapshot, Error> {
+    let base = Environment::from_builtins();
+    let global = Rc::new(Environment {
+        parent: Some(base.clone()),
+        ..Default::default()
+    });
+
+    let Obj::List(task) = task.restore(base) else {
+        return Err(Error::Other("malformed parallel task".to_string()));
+    };
+
+    let task = task.elements();
+    let [(_, f), (_, Obj::List(args)), (_, Obj::List(xs))] = task.as_slice() else {
+        return Err(Error::Other("malformed parallel task".to_string()));
+    };
+
+    global.insert(WORKER_FN.to_string(), f.clone());
+
+    let mut call_args = ExprList::from(vec![Expr::Symbol(WORKER_X.to_string())]);
+    for (i, (k, v)) in args.elements().into_iter().enumerate() {
+        let name = format!("*arg{}*", i + 1);
+        global.insert(name.clone(), v);
+        call_args.push((k, Expr::Symbol(name)));
+    }
+
+    let call = Expr::Call(Box::new(Expr::Symbol(WORKER_FN.to_string())), call_args);
+    let mut stack = CallStack::from(global.clone());
+    let mut results = vec![];
+
+    for (k, x) in xs.elements() {
+        global.insert(WORKER_X.to_string(), x);
+        let result = match stack.eval_and_finalize(call.clone()) {
+            Ok(value) => value,
+            Err(Signal::Return(value, _)) => *value,
+            Err(Signal::Error(e)) => return Err(e),
+            Err(_) => return Err(Error::Other("unexpected signal in worker".to_string())),
+        };
+
+        results.push((k, result));
+    }
+
+    Ok(Snapshot::from(&Obj::List(List::from(results))))
+}
+
+/// Call a function with each element of `x` on a pool of worker threads
+///
+/// Elements are split into contiguous chunks, one per worker. Each worker
+/// evaluates its chunk in an interpreter of its own, using a snapshot of the
+/// function (including the environments it captures) and its arguments.
+/// Results are restored in order, such that a list is returned just as a
+/// sequential map would.
+pub fn map(x: Elements, f: Obj, args: Elements, cores: usize, stack: &CallStack) -> EvalResult {
+    if x.is_empty() {
+        return Ok(Obj::List(List::from(vec![])));
+    }
+
+    let size = x.len().div_ceil(cores.min(x.len()));
+    let args = Obj::List(List::from(args));
+    let tasks: Vec<Snapshot> = x
+        .chunks(size)
+        .map(|chunk| {
+            let xs = Obj::List(List::from(chunk.to_vec()));
+            let task = vec![(None, f.clone()), (None, args.clone()), (None, xs)];
+            Snapshot::from(&Obj::List(List::from(task)))
+        })
+        .collect();
+
+    let results: Vec<Result<Snapshot, Error>> = thread::scope(|scope| {
+        let workers: Vec<_> = tasks
+            .into_iter()
+            .map(|task| {
+                thread::Builder::new()
+                    .stack_size(WORKER_STACK_SIZE)
+                    .spawn_scoped(scope, move || worker(task))
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .map(|worker| match worker {
+                Ok(handle) => handle
+                    .join()
+                    .unwrap_or_else(|_| Err(Error::Other("worker panicked".to_string()))),
+                Err(e) => Err(Error::Other(format!("cannot start worker: {e}"))),
+            })
+            .collect()
+    });
+
+    let base = stack.env().base();
+    let mut values = vec![];
+    for result in results {
+        match result?.restore(base.clone()) {
+            Obj::List(l) => values.extend(l.elements()),
+            _ => return internal_err!(),
+        }
+    }
+
+    Ok(Obj::List(List::from(values)))
+}
+
+/// Force the arguments passed through to the mapped function, which are
+/// evaluated once before being sent to workers
+fn force_args(ellipsis: List, stack: &mut CallStack) -> Result<Elements, Signal> {
+    ellipsis
+        .elements()
+        .into_iter()
+        .map(|(k, v)| Ok((k, v.force(stack)?)))
+        .collect()
+}
+
+fn function_arg(f: Obj, stack: &mut CallStack) -> EvalResult {
+    match f.force(stack)? {
+        f @ Obj::Function(..) => Ok(f),
+        _ => err!("argument must be a function"),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "parallel::map")]
+pub struct PrimitiveParallelMap;
+impl Callable for PrimitiveParallelMap {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("f")), Expr::Missing),
+            (None, Expr::Ellipsis(None)),
+            (Some(String::from("cores")), Expr::Null),
+        ])
+    }
+
+    fn call_matched(&self, args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = elements(args.try_get_named("x")?.force(stack)?)?;
+        let f = function_arg(args.try_get_named("f")?, stack)?;
+        // parameters following the ellipsis are only matched when named
+        let cores_arg = args.get_named("cores").unwrap_or_default();
+        let cores = cores(cores_arg.force(stack)?, "cores")?;
+        let args = force_args(ellipsis, stack)?;
+        map(x, f, args, cores, stack)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "mclapply")]
+pub struct PrimitiveMclapply;
+impl Callable for PrimitiveMclapply {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("X")), Expr::Missing),
+            (Some(String::from("FUN")), Expr::Missing),
+            (None, Expr::Ellipsis(None)),
+            (Some(String::from("mc.cores")), Expr::Null),
+        ])
+    }
+
+    fn call_matched(&self, args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = elements(args.try_get_named("X")?.force(stack)?)?;
+        let f = function_arg(args.try_get_named("FUN")?, stack)?;
+        // parameters following the ellipsis are only matched when named
+        let cores_arg = args.get_named("mc.cores").unwrap_or_default();
+        let cores = cores(cores_arg.force(stack)?, "mc.cores")?;
+        let args = force_args(ellipsis, stack)?;
+        map(x, f, args, cores, stack)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::Error;
+    use crate::{r, r_expect};
+
+    #[test]
+    fn maps_over_lists_and_vectors() {
+        assert_eq!(
+            r! {{"parallel::map(list(a = 1, b = 2, c = 3), function(x) x * 10)"}},
+            r! { list(a = 10, b = 20, c = 30) }
+        );
+
+        assert_eq!(
+            r! { mclapply([1, 2, 3, 4, 5], function(x, y) x + y, 100, mc.cores = 2) },
+            r! { list(101, 102, 103, 104, 105) }
+        );
+    }
+
+    #[test]
+    fn vector_names_are_kept() {
+        assert_eq!(
+            r! {{"x <- c(1, 2); names(x) <- c('a', 'b'); parallel::map(x, function(x) x * 10)"}},
+            r! { list(a = 10, b = 20) }
+        );
+        assert_eq!(
+            r! {{"x <- c(1, 2, 3); names(x) <- c('a', 'b', 'c'); mclapply(x[2:3], names)"}},
+            r! { list(b = NULL, c = NULL) }
+        );
+    }
+
+    #[test]
+    fn captured_environments_are_copied_to_workers() {
+        r_expect! {{"
+            n <- 3
+            fib <- function(x) if (x < 2) x else fib(x - 1) + fib(x - 2)
+            f <- function(x) fib(x + n)
+            r <- parallel::map([1, 2, 3], f, cores = 3)
+            r[[1]] == 3 && r[[2]] == 5 && r[[3]] == 8
+        "}}
+    }
+
+    #[test]
+    fn worker_errors_are_raised() {
+        assert!(r! {{"parallel::map([1, 2], function(x) undefined)"}}.is_err());
+        assert!(r! {{"parallel::map([1, 2], function(x) x, cores = 0)"}}.is_err());
+    }
+
+    #[test]
+    fn namespaced_builtins() {
+        assert_eq!(r! {{"base::paste('a', 'b')"}}, r! { "a b" });
+        assert_eq!(
+            r! {{"foo::paste('a', 'b')"}},
+            Err(Error::PackageNotFound(String::from("foo")).into())
+        );
+    }
+}
@@ -0,0 +1,454 @@
+use std::rc::Rc;
+
+use r_derive::*;
+
+use crate::callable::core::*;
+use crate::context::Context;
+use crate::err;
+use crate::lang::*;
+use crate::object::*;
+
+/// Names under which partially applied and composed functions keep their
+/// functions and bound arguments, in the environment of the returned function
+const BOUND_FN: &str = "*fn*";
+const BOUND_FNS: &str = "*fns*";
+const BOUND_ARGS: &str = "*args*";
+const BOUND_HOLES: &str = "*holes*";
+
+/// The formals of a function, including those of primitives
+fn formals(f: &Obj) -> ExprList {
+    match f {
+        Obj::Function(_, Expr::Primitive(p), ..) => p.formals(),
+        f => f.formals(),
+    }
+}
+
+/// Call a function with a list of (possibly lazy) arguments
+///
+/// The function and its arguments are bound in the current frame, such that
+/// arguments are forwarded as promises and only forced as needed.
+pub fn call_with(f: Obj, args: List, stack: &mut CallStack) -> EvalResult {
+    let env = stack.env();
+    env.insert(BOUND_FN.to_string(), f);
+
+    let args = args
+        .values
+        .borrow()
+        .iter()
+        .enumerate()
+        .map(|(i, (k, v))| {
+            let name = format!("*arg{}*", i + 1);
+            env.insert(name.clone(), v.clone());
+            (k.clone(), Expr::Symbol(name))
+        })
+        .collect::<ExprList>();
+
+    let what = Box::new(Expr::Symbol(BOUND_FN.to_string()));
+    stack.eval_and_finalize(Expr::Call(what, args))
+}
+
+/// Evaluate a function argument, which must be a function
+fn function_arg(f: Obj, stack: &mut CallStack) -> EvalResult {
+    match f.force(stack)? {
+        f @ Obj::Function(..) => Ok(f),
+        _ => err!("argument must be a function"),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "partial")]
+pub struct PrimitivePartial;
+impl Callable for PrimitivePartial {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("f")), Expr::Missing),
+            (None, Expr::Ellipsis(None)),
+        ])
+    }
+
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let label = format!("partial({args})");
+
+        // `_` placeholders are left unbound, keeping their place among the
+        // bound arguments for arguments supplied later
+        let f_index = args
+            .keys
+            .iter()
+            .position(|k| k.as_deref() == Some("f"))
+            .or_else(|| args.keys.iter().position(|k| k.is_none()));
+
+        let mut holes = vec![];
+        let mut exprs = ExprList::new();
+        for (i, (k, v)) in args.into_iter().enumerate() {
+            if Some(i) != f_index && is_placeholder(&v) {
+                let index = i - f_index.map_or(0, |f| (f < i) as usize);
+                holes.push((k, Obj::Vector(Vector::from(vec![index as i32]))));
+            } else {
+                exprs.push((k, v));
+            }
+        }
+
+        let (args, ellipsis) = self.match_arg_exprs(exprs, stack)?;
+        let f = function_arg(Obj::List(args).try_get_named("f")?, stack)?;
+
+        // bound arguments are evaluated once, when partially applied
+        let bound = ellipsis
+            .values
+            .borrow()
+            .clone()
+            .into_iter()
+            .map(|(k, v)| Ok((k, v.force(stack)?)))
+            .collect::<Result<Vec<_>, Signal>>()?;
+
+        // remaining formals are those not matched by name, then by position,
+        // where placeholders keep the formal in their place
+        let mut remaining = formals(&f);
+        let mut positional: Vec<bool> = vec![];
+        let mut bound_iter = bound.iter();
+        for i in 0..bound.len() + holes.len() {
+            match holes.iter().find(|(_, index)| hole_index(index) == Some(i)) {
+                Some((None, _)) => positional.push(false),
+                Some(_) => (),
+                None => match bound_iter.next() {
+                    Some((Some(k), _)) => {
+                        remaining.remove_named(k);
+                    }
+                    _ => positional.push(true),
+                },
+            }
+        }
+
+        let mut index = 0;
+        for is_bound in positional {
+            match remaining.values.get(index) {
+                Some(Expr::Ellipsis(_)) | None => break,
+                Some(_) if is_bound => {
+                    remaining.remove(index);
+                }
+                Some(_) => index += 1,
+            }
+        }
+
+        let env = Rc::new(Environment {
+            parent: Some(stack.env()),
+            ..Default::default()
+        });
+
+        env.insert(BOUND_FN.to_string(), f);
+        env.insert(BOUND_ARGS.to_string(), Obj::List(List::from(bound)));
+        env.insert(BOUND_HOLES.to_string(), Obj::List(List::from(holes)));
+
+        let partial = PartialCall {
+            label,
+            formals: remaining.clone(),
+        };
+
+        Ok(Obj::Function(
+            remaining,
+            Expr::as_primitive(partial),
+            env,
+            Box::default(),
+        ))
+    }
+}
+
+/// The position of a placeholder among the arguments of a partial call
+fn hole_index(index: &Obj) -> Option<usize> {
+    match index {
+        Obj::Vector(Vector::Integer(v)) => match v.inner().borrow().first() {
+            Some(OptionNA::Some(i)) => Some(*i as usize),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A partially applied function, as returned by `partial()`
+#[derive(Debug, Clone, PartialEq)]
+#[builtin]
+pub struct PartialCall {
+    label: String,
+    formals: ExprList,
+}
+
+impl Format for PartialCall {
+    fn rfmt_call_with(&self, _state: FormatState, args: &ExprList) -> String {
+        format!("({})({})", self.label, args)
+    }
+
+    fn rfmt_with(&self, _state: FormatState) -> String {
+        self.label.clone()
+    }
+}
+
+impl Callable for PartialCall {
+    fn formals(&self) -> ExprList {
+        self.formals.clone()
+    }
+
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let args: List = stack.parent_frame().eval_list_lazy(args)?.try_into()?;
+        let f = stack.get(BOUND_FN.to_string())?;
+        let Obj::List(bound) = stack.get(BOUND_ARGS.to_string())? else {
+            return err!("partially applied function is missing its arguments");
+        };
+
+        let Obj::List(holes) = stack.get(BOUND_HOLES.to_string())? else {
+            return err!("partially applied function is missing its arguments");
+        };
+
+        // placeholders are filled by the first unnamed arguments
+        let holes = holes.values.borrow().clone();
+        let mut unfilled = holes.iter().peekable();
+        let mut filled = vec![];
+        let mut rest = vec![];
+        for (k, v) in args.values.borrow().iter().cloned() {
+            match (k, unfilled.peek()) {
+                (None, Some((name, index))) => {
+                    filled.push((hole_index(index), (name.clone(), v)));
+                    unfilled.next();
+                }
+                (k, _) => rest.push((k, v)),
+            }
+        }
+
+        if unfilled.peek().is_some() {
+            return err!("argument for placeholder `_` is missing");
+        }
+
+        let mut all = bound.values.borrow().clone();
+        for (index, arg) in filled {
+            let index = index.unwrap_or(all.len()).min(all.len());
+            all.insert(index, arg);
+        }
+
+        all.extend(rest);
+        call_with(f, List::from(all), stack)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "compose")]
+pub struct PrimitiveCompose;
+impl Callable for PrimitiveCompose {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(None, Expr::Ellipsis(None))])
+    }
+
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let label = format!("compose({args})");
+        let (_, ellipsis) = self.match_arg_exprs(args, stack)?;
+        let fns = ellipsis
+            .values
+            .borrow()
+            .clone()
+            .into_iter()
+            .map(|(_, f)| Ok((None, function_arg(f, stack)?)))
+            .collect::<Result<Vec<_>, Signal>>()?;
+
+        // the composed function takes the arguments of the first applied
+        let Some((_, last)) = fns.last() else {
+            return err!("at least one function must be composed");
+        };
+
+        let remaining = formals(last);
+        let env = Rc::new(Environment {
+            parent: Some(stack.env()),
+            ..Default::default()
+        });
+
+        env.insert(BOUND_FNS.to_string(), Obj::List(List::from(fns)));
+
+        let composed = ComposedCall {
+            label,
+            formals: remaining.clone(),
+        };
+
+        Ok(Obj::Function(
+            remaining,
+            Expr::as_primitive(composed),
+            env,
+            Box::default(),
+        ))
+    }
+}
+
+/// A composition of functions, as returned by `compose()`
+#[derive(Debug, Clone, PartialEq)]
+#[builtin]
+pub struct ComposedCall {
+    label: String,
+    formals: ExprList,
+}
+
+impl Format for ComposedCall {
+    fn rfmt_call_with(&self, _state: FormatState, args: &ExprList) -> String {
+        format!("({})({})", self.label, args)
+    }
+
+    fn rfmt_with(&self, _state: FormatState) -> String {
+        self.label.clone()
+    }
+}
+
+impl Callable for ComposedCall {
+    fn formals(&self) -> ExprList {
+        self.formals.clone()
+    }
+
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let args: List = stack.parent_frame().eval_list_lazy(args)?.try_into()?;
+        let Obj::List(fns) = stack.get(BOUND_FNS.to_string())? else {
+            return err!("composed function is missing its functions");
+        };
+
+        // functions are applied from last to first
+        let fns = fns.values.borrow().clone();
+        let mut fns = fns.into_iter().rev().map(|(_, f)| f);
+        let Some(first) = fns.next() else {
+            return Ok(Obj::Null);
+        };
+
+        let mut value = call_with(first, args, stack)?;
+        for f in fns {
+            value = call_with(f, List::from(vec![(None, value)]), stack)?;
+        }
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::object::{Expr, Obj};
+    use crate::{r, r_expect};
+
+    #[test]
+    fn partial_binds_positional_and_named() {
+        r_expect! {{"
+            add <- function(x, y, z = 0) x + y + z
+            inc <- partial(add, 1)
+            add10 <- partial(add, y = 10)
+            inc(2) == 3 && add10(1, z = 5) == 16
+        "}}
+    }
+
+    #[test]
+    fn partial_formals_are_remaining_parameters() {
+        let f = r! { partial(function(x, y, z = 0) x, y = 1) };
+        let Ok(Obj::Function(_, Expr::Primitive(p), ..)) = f else {
+            panic!("expected a partially applied function");
+        };
+
+        let formals = p.formals();
+        assert_eq!(
+            formals.keys,
+            vec![Some("x".to_string()), Some("z".to_string())]
+        );
+    }
+
+    #[test]
+    fn partial_of_primitives() {
+        assert_eq!(
+            r! { p <- partial(paste, "a"); p("b", sep = "-") },
+            r! { "a-b" }
+        );
+    }
+
+    #[test]
+    fn partial_arguments_evaluated_in_calling_frame() {
+        r_expect! {{"
+            add <- function(x, y) x + y
+            inc <- partial(add, 1)
+            f <- function() { y <- 100; inc(y) }
+            f() == 101
+        "}}
+    }
+
+    #[test]
+    fn placeholder_partially_applies() {
+        r_expect! {{"
+            add <- function(x, y) x - y
+            f <- add(y = 1, _)
+            f(10) == 9
+        "}}
+    }
+
+    #[test]
+    fn placeholder_keeps_its_position() {
+        r_expect! {{"
+            sub <- function(x, y) x - y
+            f <- sub(_, 1)
+            g <- sub(10, _)
+            f(10) == 9 && g(1) == 9
+        "}}
+        assert_eq!(r! { p <- paste("a", _, "c"); p("b") }, r! { "a b c" });
+        assert_eq!(
+            r! { p <- partial(paste, _, "b", sep = "-"); p("a") },
+            r! { "a-b" }
+        );
+        assert!(r! { f <- function(x, y) x; g <- f(_, 1); g() }.is_err());
+    }
+
+    #[test]
+    fn placeholders_are_left_to_partial() {
+        r_expect! {{"
+            sub <- function(x, y) x - y
+            p <- partial
+            f <- p(sub, _, 1)
+            g <- base::partial(sub, _, 1)
+            f(10) == 9 && g(10) == 9
+        "}}
+    }
+
+    #[test]
+    fn placeholder_formals_keep_their_position() {
+        let f = r! { f <- function(x, y, z) x; f(_, 2) };
+        let Ok(Obj::Function(_, Expr::Primitive(p), ..)) = f else {
+            panic!("expected a partially applied function");
+        };
+
+        assert_eq!(
+            p.formals().keys,
+            vec![Some("x".to_string()), Some("z".to_string())]
+        );
+    }
+
+    #[test]
+    fn compose_applies_last_to_first() {
+        r_expect! {{"
+            sq <- function(x) x * x
+            add <- function(x, y) x + y
+            f <- compose(sq, add)
+            g <- compose(add(1, _), sq)
+            f(1, 2) == 9 && g(3) == 10
+        "}}
+    }
+
+    #[test]
+    fn partial_and_compose_format() {
+        assert_eq!(
+            r! { add <- function(x, y) x + y; partial(add, y = 1) }
+                .unwrap()
+                .to_string(),
+            "function(x) .Primitive(\"partial(add, y = 1)\")"
+        );
+        assert_eq!(
+            r! { compose(paste, function(x) x) }.unwrap().to_string(),
+            "function(x) .Primitive(\"compose(paste, function(x) x)\")"
+        );
+        assert_eq!(
+            r! { partial(paste, "b") }.unwrap().to_string(),
+            "function(..., sep = \" \", collapse = NULL) .Primitive(\"partial(paste, \\\"b\\\")\")"
+        );
+    }
+
+    #[test]
+    fn anonymous_functions_called_in_own_frame() {
+        r_expect! {{"
+            x <- 1
+            (function(x) x + 1)(2)
+            x == 1
+        "}}
+    }
+}
@@ -46,11 +46,8 @@ impl Callable for PrimitivePaste {
                     collapse = (*v).clone().into();
                 }
                 ("collapse", _) => {
-                    return Err(Signal::Error(Error::WithCallStack(
-                        Box::new(Error::Other(
-                            "collapse parameter must be NULL or a character string.".to_string(),
-                        )),
-                        stack.clone(),
+                    return Err(Signal::Error(Error::Other(
+                        "collapse parameter must be NULL or a character string.".to_string(),
                     )))
                 }
                 _ => continue,
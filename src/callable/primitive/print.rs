@@ -1,7 +1,11 @@
+use std::rc::Rc;
+
 use lazy_static::lazy_static;
 use r_derive::*;
 
+use super::generic::dispatch_on;
 use crate::callable::core::*;
+use crate::context::Context;
 use crate::lang::*;
 use crate::object::*;
 
@@ -12,6 +16,27 @@ lazy_static! {
     ]);
 }
 
+/// Print a value, as the REPL does with the result of each evaluation
+///
+/// Values are printed by the `print` generic, such that classed objects
+/// are printed by the method for their class.
+pub fn autoprint(x: Obj, stack: &mut CallStack) -> Result<(), Signal> {
+    let print = Box::new(Expr::Symbol(String::from("print")));
+    let call = Expr::Call(
+        print,
+        ExprList::from(vec![(None, Expr::Symbol("x".into()))]),
+    );
+    let env = Rc::new(Environment {
+        parent: Some(stack.env()),
+        ..Default::default()
+    });
+
+    env.insert(String::from("x"), x.clone());
+    stack.add_frame(call, env);
+    let result = dispatch_on("print", x, List::default(), stack);
+    stack.pop_frame_and_return(result).map(|_| ())
+}
+
 #[derive(Debug, Clone, PartialEq)]
 #[builtin(sym = "print")]
 pub struct PrimitivePrint;
@@ -20,6 +45,21 @@ impl Callable for PrimitivePrint {
         FORMALS.clone()
     }
 
+    fn call_matched(&self, args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = args.try_get_named("x")?.force(stack)?;
+        dispatch_on("print", x, ellipsis, stack)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "print.default")]
+pub struct PrimitivePrintDefault;
+impl Callable for PrimitivePrintDefault {
+    fn formals(&self) -> ExprList {
+        FORMALS.clone()
+    }
+
     fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
         let mut args = Obj::List(args);
         let x = args.try_get_named("x")?.force(stack)?;
@@ -27,3 +67,18 @@ impl Callable for PrimitivePrint {
         Ok(x)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{r, r_expect};
+
+    #[test]
+    fn methods_are_dispatched_by_class() {
+        r_expect! {{"
+            print.thing <- function(x, ...) 'printed'
+            x <- list(1)
+            attr(x, 'class') <- 'thing'
+            print(x) == 'printed' && print(2) == 2
+        "}}
+    }
+}
@@ -1,14 +1,51 @@
 use r_derive::*;
 
 use crate::callable::core::*;
+use crate::err;
 use crate::lang::*;
-use crate::object::ExprList;
+use crate::object::*;
+use crate::workspace;
 
 #[derive(Debug, Clone, PartialEq)]
 #[builtin(sym = "q")]
 pub struct PrimitiveQ;
 impl Callable for PrimitiveQ {
-    fn call(&self, _args: ExprList, _stack: &mut CallStack) -> EvalResult {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(
+            Some(String::from("save")),
+            Expr::String(String::from("default")),
+        )])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let save: String = args.try_get_named("save")?.force(stack)?.try_into()?;
+
+        // by default, the workspace is saved if the session was started with --save
+        let save = match save.as_str() {
+            "yes" => true,
+            "no" => false,
+            "default" => workspace::save_on_exit(),
+            _ => return err!("argument 'save' must be one of 'yes', 'no' or 'default'"),
+        };
+
+        if save {
+            let global = stack.frames[0].env.clone();
+            workspace::save_image(&global, workspace::DEFAULT_FILE)?;
+        }
+
         Err(Signal::Condition(Cond::Terminate))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::lang::{Cond, Signal};
+    use crate::r;
+
+    #[test]
+    fn quits_without_saving() {
+        assert_eq!(r! { q("no") }, Err(Signal::Condition(Cond::Terminate)));
+        assert!(matches!(r! { q("maybe") }, Err(Signal::Error(_))));
+    }
+}
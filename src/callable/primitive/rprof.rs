@@ -0,0 +1,121 @@
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::error::Error;
+use crate::lang::{CallStack, EvalResult};
+use crate::object::*;
+use crate::profiler::{self, Sampling};
+use std::time::Duration;
+
+/// Start or stop profiling
+///
+/// When sampling by `interval`, ticks of the timer are only recorded as the
+/// next call is evaluated, and are charged to the call stack at that point.
+/// Time spent within a long-running builtin is therefore attributed to the
+/// stack of the call evaluated after it returns, rather than to the builtin.
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "Rprof")]
+pub struct PrimitiveRprof;
+impl Callable for PrimitiveRprof {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (
+                Some(String::from("filename")),
+                Expr::String("Rprof.out".into()),
+            ),
+            (Some(String::from("append")), Expr::Bool(false)),
+            (Some(String::from("interval")), Expr::Number(0.02)),
+            (Some(String::from("calls")), Expr::Null),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+
+        // a NULL filename stops profiling
+        let filename = args.try_get_named("filename")?.force(stack)?;
+        if let Obj::Null = filename {
+            profiler::stop()?;
+            return Ok(Obj::Null);
+        }
+
+        let filename: String = filename.try_into()?;
+        let append: bool = args.try_get_named("append")?.force(stack)?.try_into()?;
+
+        // sample every n closure calls if provided, otherwise by interval
+        let sampling = match args.try_get_named("calls")?.force(stack)? {
+            Obj::Null => {
+                let interval: f64 = args.try_get_named("interval")?.force(stack)?.try_into()?;
+                match Duration::try_from_secs_f64(interval) {
+                    Ok(interval) if !interval.is_zero() => Sampling::Interval(interval),
+                    _ => return Error::ArgumentInvalid(String::from("interval")).into(),
+                }
+            }
+            calls => match TryInto::<i32>::try_into(calls)? {
+                n if n >= 1 => Sampling::Calls(n as usize),
+                _ => return Error::ArgumentInvalid(String::from("calls")).into(),
+            },
+        };
+
+        profiler::start(&filename, sampling, append)?;
+        Ok(Obj::Null)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::r;
+    use crate::repl::eval;
+    use crate::utils::temp_file;
+
+    #[test]
+    fn samples_are_written_as_collapsed_stacks() {
+        let path = temp_file(".out");
+        let result = eval(&format!(
+            "
+            f <- function() g()
+            g <- function() 1
+            Rprof('{path}', calls = 1)
+            f()
+            f()
+            Rprof(NULL)
+            "
+        ));
+
+        assert_eq!(result, r! { NULL });
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content, "f 2\nf;g 2\n");
+    }
+
+    #[test]
+    fn samples_are_taken_on_a_timer() {
+        // no closures are called while the timer ticks, so the sample is
+        // taken by the next call evaluated within `f`
+        let path = temp_file(".out");
+        let result = eval(&format!(
+            "
+            f <- function() {{ Sys.sleep(0.05); 1 + 1 }}
+            Rprof('{path}', interval = 0.001)
+            f()
+            Rprof(NULL)
+            "
+        ));
+
+        assert_eq!(result, r! { NULL });
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.lines().any(|l| l.starts_with("f ")), "{content}");
+    }
+
+    #[test]
+    fn invalid_sampling_is_an_error() {
+        assert!(r! { Rprof("unused.out", calls = 0) }.is_err());
+        assert!(r! { Rprof("unused.out", interval = -1) }.is_err());
+        assert!(r! { Rprof("unused.out", interval = 0) }.is_err());
+    }
+
+    #[test]
+    fn unrepresentable_intervals_are_an_error() {
+        assert!(r! { Rprof("unused.out", interval = Inf) }.is_err());
+        assert!(r! { Rprof("unused.out", interval = 1e300) }.is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use std::rc::Rc;
+
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::context::Context;
+use crate::err;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+use crate::workspace;
+
+fn env_arg(x: Obj) -> Result<Rc<Environment>, Signal> {
+    match x {
+        Obj::Environment(env) => Ok(env),
+        _ => err!("argument 'envir' must be an environment"),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "save")]
+pub struct PrimitiveSave;
+impl Callable for PrimitiveSave {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (None, Expr::Ellipsis(None)),
+            (Some(String::from("list")), Expr::Null),
+            (Some(String::from("file")), Expr::Missing),
+            (Some(String::from("envir")), Expr::Missing),
+        ])
+    }
+
+    // objects are given by name, so arguments are not evaluated
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let mut names = vec![];
+        let mut file = None;
+        let mut envir = stack.env();
+
+        for (k, v) in args {
+            match (k.as_deref(), v) {
+                (Some("list"), v) => {
+                    let list: Vec<String> = stack.eval(v)?.try_into()?;
+                    names.extend(list);
+                }
+                (Some("file"), v) => file = Some(stack.eval(v)?.try_into()?),
+                (Some("envir"), v) => envir = env_arg(stack.eval(v)?)?,
+                (None, Expr::Symbol(name) | Expr::String(name)) => names.push(name),
+                _ => return err!("objects to save must be given by name"),
+            }
+        }
+
+        let Some(file): Option<String> = file else {
+            return err!("argument 'file' is missing");
+        };
+
+        workspace::save(&envir, &names, &file)?;
+        Ok(Obj::Null)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "save.image")]
+pub struct PrimitiveSaveImage;
+impl Callable for PrimitiveSaveImage {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(
+            Some(String::from("file")),
+            Expr::String(workspace::DEFAULT_FILE.to_string()),
+        )])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let file: String = args.try_get_named("file")?.force(stack)?.try_into()?;
+
+        // always saves the global environment
+        let global = stack.frames[0].env.clone();
+        workspace::save_image(&global, &file)?;
+        Ok(Obj::Null)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "load")]
+pub struct PrimitiveLoad;
+impl Callable for PrimitiveLoad {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("file")), Expr::Missing),
+            (Some(String::from("envir")), Expr::Null),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let file: String = args.try_get_named("file")?.force(stack)?.try_into()?;
+
+        // loaded into the calling environment by default
+        let envir = match args.try_get_named("envir")?.force(stack)? {
+            Obj::Null => stack.env(),
+            envir => env_arg(envir)?,
+        };
+
+        let names = workspace::load(&envir, &file)?;
+        Ok(Obj::Vector(Vector::from(names)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::r;
+    use crate::repl::eval;
+    use crate::utils::temp_file;
+
+    #[test]
+    fn bindings_are_saved_and_loaded() {
+        let path = temp_file(".RData");
+        let result = eval(&format!(
+            "x <- 1; y <- 'two'; z <- 3
+             save(x, list = 'y', file = '{path}')
+             x <- 0; y <- 0; z <- 0
+             loaded <- load('{path}')
+             x == 1 && y == 'two' && z == 0 && loaded[[1]] == 'x' && loaded[[2]] == 'y'"
+        ));
+
+        assert_eq!(result, r! { true });
+    }
+
+    #[test]
+    fn closures_capture_the_environment_they_are_loaded_into() {
+        let path = temp_file(".RData");
+        let result = eval(&format!(
+            "n <- 1
+             f <- function() n * 10
+             save.image('{path}')"
+        ));
+
+        assert_eq!(result, r! { NULL });
+
+        let result = eval(&format!("load('{path}'); n <- 5; f()"));
+        assert_eq!(result, r! { 50 });
+    }
+
+    #[test]
+    fn unknown_objects_are_an_error() {
+        let path = temp_file(".RData");
+        assert!(eval(&format!("save(undefined, file = '{path}')")).is_err());
+    }
+}
@@ -0,0 +1,467 @@
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::err;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+use crate::time::{self, tz::Zone, Civil, Class};
+
+/// The longest sequence that can be created
+pub const MAX_LENGTH: f64 = i32::MAX as f64;
+
+/// A step between the elements of a sequence of dates or date-times
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    /// A fixed number of days for dates, or seconds for date-times
+    Fixed(f64),
+    /// A number of calendar days, preserving the local time of date-times
+    Days(i64),
+    /// A number of calendar months, with days beyond the end of a month
+    /// overflowing into the next
+    Months(i64),
+}
+
+impl Step {
+    /// Read a step such as `"day"`, `"2 weeks"` or `"-1 month"`
+    fn parse(by: &str, class: &Class) -> Result<Step, Signal> {
+        let (n, unit) = match by.trim().split_once(char::is_whitespace) {
+            Some((n, unit)) => match n.parse::<i64>() {
+                Ok(n) => (n, unit.trim()),
+                Err(_) => return err!(format!("invalid string for 'by': '{by}'")),
+            },
+            None => (1, by.trim()),
+        };
+
+        let unit = unit.strip_suffix('s').unwrap_or(unit);
+        let date = *class == Class::Date;
+        let fixed = |secs: i64| match date {
+            true => Step::Fixed((n * secs / time::SECS_PER_DAY) as f64),
+            false => Step::Fixed((n * secs) as f64),
+        };
+
+        let step = match unit {
+            "sec" if !date => fixed(1),
+            "min" if !date => fixed(60),
+            "hour" if !date => fixed(3600),
+            "day" => fixed(time::SECS_PER_DAY),
+            "week" => fixed(7 * time::SECS_PER_DAY),
+            "DSTday" => Step::Days(n),
+            "month" => Step::Months(n),
+            "quarter" => Step::Months(3 * n),
+            "year" => Step::Months(12 * n),
+            _ => return err!(format!("invalid string for 'by': '{by}'")),
+        };
+
+        Ok(step)
+    }
+
+    /// Whether a step moves forward in time
+    fn is_increasing(&self) -> bool {
+        match self {
+            Step::Fixed(x) => *x > 0.0,
+            Step::Days(n) | Step::Months(n) => *n > 0,
+        }
+    }
+
+    /// The shortest time a step may span, in days for dates or seconds for
+    /// date-times
+    fn min_span(&self, class: &Class) -> f64 {
+        let day = match class {
+            Class::Date => 1.0,
+            _ => time::SECS_PER_DAY as f64,
+        };
+
+        match self {
+            Step::Fixed(x) => x.abs(),
+            // allowing for days shortened by daylight saving
+            Step::Days(n) => n.abs() as f64 * day / 2.0,
+            Step::Months(n) => n.abs() as f64 * 28.0 * day,
+        }
+    }
+
+    /// The `i`th value of a sequence from `from`
+    fn nth(&self, from: f64, i: i64, class: &Class, zone: &Zone) -> f64 {
+        let civil = match class {
+            Class::Date => Civil::from_date(from.floor() as i64),
+            _ => Civil::from_timestamp(from, zone),
+        };
+
+        let shifted = |mut civil: Civil, days: i64| {
+            let date = civil.days() + days;
+            let (year, month, day) = time::civil_from_days(date);
+            (civil.year, civil.month, civil.day) = (year, month, day);
+            civil
+        };
+
+        let civil = match self {
+            Step::Fixed(x) => return from + x * i as f64,
+            Step::Days(n) => shifted(civil, n * i),
+            Step::Months(n) => {
+                let months = civil.year * 12 + civil.month as i64 - 1 + n * i;
+                let overflow = civil.day as i64 - 1;
+                let mut first = civil;
+                first.year = months.div_euclid(12);
+                first.month = months.rem_euclid(12) as u32 + 1;
+                first.day = 1;
+                shifted(first, overflow)
+            }
+        };
+
+        match class {
+            Class::Date => civil.days() as f64,
+            _ => civil.timestamp(zone),
+        }
+    }
+}
+
+/// The first value of a date or date-time argument
+fn first(x: &Vector, name: &str) -> Result<f64, Signal> {
+    match time::values(x).first() {
+        Some(OptionNA::Some(x)) if x.is_finite() => Ok(*x),
+        _ => err!(format!("'{name}' must be a finite value")),
+    }
+}
+
+/// A sequence of dates or date-times
+fn seq_time(
+    from: &Vector,
+    class: Class,
+    to: Obj,
+    by: Obj,
+    length_out: Option<usize>,
+) -> EvalResult {
+    let zone = match &class {
+        Class::DateTime(tz) => time::zone(tz)?,
+        _ => time::zone("UTC")?,
+    };
+
+    let start = first(from, "from")?;
+    let to = match to {
+        Obj::Null => None,
+        Obj::Vector(to) if Class::of(&to) == Some(class.clone()) => Some(first(&to, "to")?),
+        Obj::Vector(to)
+            if matches!(Class::of(&to), Some(Class::DateTime(_)))
+                && matches!(class, Class::DateTime(_)) =>
+        {
+            Some(first(&to, "to")?)
+        }
+        _ => return err!("'from' and 'to' must be of the same class"),
+    };
+
+    let step = match by {
+        Obj::Null => None,
+        Obj::Vector(v @ Vector::Character(_)) => {
+            let by: String = Obj::Vector(v).try_into()?;
+            Some(Step::parse(&by, &class)?)
+        }
+        Obj::Vector(v) => {
+            let by = match Class::of(&v) {
+                Some(Class::Difftime(units)) => {
+                    let base = if class == Class::Date { "days" } else { "secs" };
+                    let scale = time::unit_seconds(&units).unwrap_or(1.0);
+                    first(&v, "by")? * scale / time::unit_seconds(base).unwrap_or(1.0)
+                }
+                _ => first(&v, "by")?,
+            };
+
+            Some(Step::Fixed(by))
+        }
+        _ => return err!("invalid 'by' argument"),
+    };
+
+    let values: Vec<f64> = match (to, step, length_out) {
+        (Some(to), None, Some(n)) => {
+            let by = if n > 1 {
+                (to - start) / (n - 1) as f64
+            } else {
+                0.0
+            };
+            (0..n).map(|i| start + by * i as f64).collect()
+        }
+        (None, Some(step), Some(n)) => (0..n as i64)
+            .map(|i| step.nth(start, i, &class, &zone))
+            .collect(),
+        (Some(to), Some(step), None) => {
+            if (to > start && !step.is_increasing()) || (to < start && step.is_increasing()) {
+                return err!("wrong sign in 'by' argument");
+            }
+
+            if matches!(step, Step::Fixed(0.0) | Step::Days(0) | Step::Months(0)) {
+                return err!("invalid '(to - from)/by' in seq(.)");
+            }
+
+            if (to - start).abs() / step.min_span(&class) >= MAX_LENGTH {
+                return err!("'by' argument is much too small");
+            }
+
+            let past = |x: f64| match step.is_increasing() {
+                true => x > to,
+                false => x < to,
+            };
+
+            (0..)
+                .map(|i| step.nth(start, i, &class, &zone))
+                .take_while(|x| !past(*x))
+                .collect()
+        }
+        _ => return err!("exactly two of 'to', 'by' and 'length.out' must be specified"),
+    };
+
+    let values = values.into_iter().map(OptionNA::Some).collect();
+    Ok(Obj::Vector(match class {
+        Class::DateTime(tz) => time::datetime(values, &tz),
+        _ => time::date(values),
+    }))
+}
+
+/// A finite number given as an argument, if not `NULL`
+fn number(x: &Obj, name: &str) -> Result<Option<f64>, Signal> {
+    if let Obj::Null = x {
+        return Ok(None);
+    }
+
+    match TryInto::<f64>::try_into(x.clone()) {
+        Ok(x) if x.is_finite() => Ok(Some(x)),
+        _ => err!(format!("'{name}' must be a finite number")),
+    }
+}
+
+/// A non-negative length given as an argument
+fn length(x: Obj, name: &str) -> Result<usize, Signal> {
+    match number(&x, name)? {
+        Some(n) if n > MAX_LENGTH => err!(format!("'{name}' is too large")),
+        Some(n) if n >= 0.0 => Ok(n.ceil() as usize),
+        _ => err!(format!("'{name}' must be a non-negative number")),
+    }
+}
+
+/// The integers from one to `n`
+fn seq_len(n: usize) -> Obj {
+    Obj::Vector(Vector::from((1..=n as i32).collect::<Vec<i32>>()))
+}
+
+/// A sequence of numbers, given any two of `to`, `by` and `length.out`
+/// along with `from`, where `from` and `to` otherwise default to one
+fn seq_numeric(
+    from: Option<f64>,
+    to: Option<f64>,
+    by: Option<f64>,
+    length_out: Option<usize>,
+) -> Result<Vec<f64>, Signal> {
+    let steps = |start: f64, by: f64, n: usize| (0..n).map(move |i| start + by * i as f64);
+
+    let values = match (from, to, by, length_out) {
+        (Some(_), Some(_), Some(_), Some(_)) => return err!("too many arguments"),
+        (from, to, None, None) => {
+            let (from, to) = (from.unwrap_or(1.0), to.unwrap_or(1.0));
+            let by = if from <= to { 1.0 } else { -1.0 };
+            if (to - from).abs() >= MAX_LENGTH {
+                return err!("result would be too long a vector");
+            }
+            steps(from, by, (to - from).abs().floor() as usize + 1).collect()
+        }
+        (from, to, Some(by), None) => {
+            let (from, to) = (from.unwrap_or(1.0), to.unwrap_or(1.0));
+            if from == to {
+                vec![from]
+            } else if by == 0.0 {
+                return err!("invalid '(to - from)/by' in seq(.)");
+            } else if (to - from) / by < 0.0 {
+                return err!("wrong sign in 'by' argument");
+            } else {
+                // allowing for rounding error in the number of steps
+                let n = ((to - from) / by + 1e-10).floor();
+                if n >= MAX_LENGTH {
+                    return err!("'by' argument is much too small");
+                }
+                steps(from, by, n as usize + 1).collect()
+            }
+        }
+        (Some(from), Some(to), None, Some(n)) => {
+            let by = if n > 1 {
+                (to - from) / (n - 1) as f64
+            } else {
+                0.0
+            };
+            steps(from, by, n).collect()
+        }
+        (None, Some(to), by, Some(n)) => {
+            let by = by.unwrap_or(1.0);
+            steps(to - by * n.saturating_sub(1) as f64, by, n).collect()
+        }
+        (from, None, by, Some(n)) => steps(from.unwrap_or(1.0), by.unwrap_or(1.0), n).collect(),
+    };
+
+    Ok(values)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "seq")]
+pub struct PrimitiveSeq;
+impl Callable for PrimitiveSeq {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("from")), Expr::Null),
+            (Some(String::from("to")), Expr::Null),
+            (Some(String::from("by")), Expr::Null),
+            (Some(String::from("length.out")), Expr::Null),
+            (Some(String::from("along.with")), Expr::Null),
+            (None, Expr::Ellipsis(None)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let from = args.try_get_named("from")?.force(stack)?;
+        let to = args.try_get_named("to")?.force(stack)?;
+        let by = args.try_get_named("by")?.force(stack)?;
+        let along_with = args.try_get_named("along.with")?.force(stack)?;
+        let length_out = match (args.try_get_named("length.out")?.force(stack)?, along_with) {
+            (Obj::Null, Obj::Null) => None,
+            (Obj::Null, along_with) => Some(along_with.len().unwrap_or(0)),
+            (n, _) => Some(length(n, "length.out")?),
+        };
+
+        if let Obj::Vector(v) = &from {
+            if let Some(class @ (Class::Date | Class::DateTime(_))) = Class::of(v) {
+                return seq_time(v, class, to, by, length_out);
+            }
+        }
+
+        // as in R, a lone `from` is the length of the sequence, or the
+        // values to sequence along
+        if let (Obj::Vector(v), Obj::Null, Obj::Null, None) = (&from, &to, &by, length_out) {
+            if v.len() != 1 {
+                return Ok(seq_len(v.len()));
+            }
+        }
+
+        let (from_num, to_num) = match (number(&from, "from")?, &to, &by, length_out) {
+            (Some(n), Obj::Null, Obj::Null, None) => (Some(1.0), Some(n)),
+            (from, ..) => (from, number(&to, "to")?),
+        };
+
+        let values = seq_numeric(from_num, to_num, number(&by, "by")?, length_out)?;
+
+        // sequences of integers remain integers
+        let integers = [&from, &to, &by]
+            .iter()
+            .all(|x| matches!(x, Obj::Null | Obj::Vector(Vector::Integer(_))));
+
+        if integers && values.iter().all(|x| x.fract() == 0.0) {
+            let values: Vec<i32> = values.into_iter().map(|x| x as i32).collect();
+            return Ok(Obj::Vector(Vector::from(values)));
+        }
+
+        Ok(Obj::Vector(Vector::from(values)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "seq_len")]
+pub struct PrimitiveSeqLen;
+impl Callable for PrimitiveSeqLen {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("length.out")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let n = args.try_get_named("length.out")?.force(stack)?;
+        Ok(seq_len(length(n, "length.out")?))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "seq_along")]
+pub struct PrimitiveSeqAlong;
+impl Callable for PrimitiveSeqAlong {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("along.with")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = args.try_get_named("along.with")?.force(stack)?;
+        Ok(seq_len(x.len().unwrap_or(0)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{r, r_expect};
+
+    #[test]
+    fn numeric_sequences() {
+        assert_eq!(r! { seq(2, 4) }, r! { c(2, 3, 4) });
+        assert_eq!(r! { seq(5) }, r! { c(1, 2, 3, 4, 5) });
+        assert_eq!(r! { seq(0, 1, by = 0.25) }, r! { c(0, 0.25, 0.5, 0.75, 1) });
+        assert_eq!(r! { seq(10, 1, by = -4) }, r! { c(10, 6, 2) });
+        assert_eq!(r! { seq(0, 1, length.out = 3) }, r! { c(0, 0.5, 1) });
+        assert_eq!(
+            r! { seq(to = 10, by = 2, length.out = 3) },
+            r! { c(6, 8, 10) }
+        );
+        assert_eq!(r! { seq(1L, 7L, by = 3L) }, r! { c(1L, 4L, 7L) });
+        assert_eq!(r! { seq(length.out = 3) }, r! { c(1L, 2L, 3L) });
+        assert!(r! { seq(1, 10, by = -1) }.is_err());
+        assert!(r! { seq(1, NA) }.is_err());
+        assert!(r! { seq(1, 1000000000, by = 0.000001) }.is_err());
+        assert!(r! { seq(1, 1e10) }.is_err());
+    }
+
+    #[test]
+    fn sequences_along_values() {
+        assert_eq!(r! { seq_len(3) }, r! { c(1L, 2L, 3L) });
+        assert_eq!(r! { seq_len(0) }, r! { seq_along(NULL) });
+        assert_eq!(r! { seq_along(c("a", "b")) }, r! { c(1L, 2L) });
+        assert_eq!(r! { seq(along.with = list(1, 2, 3)) }, r! { c(1L, 2L, 3L) });
+        assert_eq!(r! { seq(c(5, 6)) }, r! { c(1L, 2L) });
+        assert!(r! { seq_len(-1) }.is_err());
+        assert!(r! { seq_len(3000000000) }.is_err());
+        assert!(r! { seq(1, by = 2, length.out = 3000000000) }.is_err());
+    }
+
+    #[test]
+    fn dates_by_day_and_month() {
+        assert_eq!(
+            r! { format(seq(as.Date("2024-02-27"), as.Date("2024-03-02"), by = "day")) },
+            r! { c("2024-02-27", "2024-02-28", "2024-02-29", "2024-03-01", "2024-03-02") }
+        );
+        assert_eq!(
+            r! { format(seq(as.Date("2024-01-31"), by = "month", length.out = 3)) },
+            r! { c("2024-01-31", "2024-03-02", "2024-03-31") }
+        );
+        assert_eq!(
+            r! { format(seq(as.Date("2024-01-15"), as.Date("2023-10-01"), by = "-1 month")) },
+            r! { c("2024-01-15", "2023-12-15", "2023-11-15", "2023-10-15") }
+        );
+        assert_eq!(
+            r! { format(seq(as.Date("2024-01-01"), by = 7, length.out = 2)) },
+            r! { c("2024-01-01", "2024-01-08") }
+        );
+        assert!(r! { seq(as.Date("2024-01-01"), as.Date("9999-01-01"), by = 0.000001) }.is_err());
+    }
+
+    #[test]
+    fn datetimes_across_daylight_saving() {
+        assert_eq!(
+            r! {{ r#"
+                x <- as.POSIXct("2024-03-09 12:00", tz = "America/New_York")
+                format(seq(x, by = "DSTday", length.out = 2), usetz = TRUE)
+            "# }},
+            r! { c("2024-03-09 12:00 EST", "2024-03-10 12:00 EDT") }
+        );
+        r_expect! {{ r#"
+            x <- as.POSIXct("2024-03-09 12:00", tz = "America/New_York")
+            sum(seq(x, x + 5 * 86400, by = "DSTday") > 0) == 6
+        "# }}
+        assert_eq!(
+            r! {{ r#"
+                x <- as.POSIXct("2024-03-09 12:00", tz = "America/New_York")
+                format(seq(x, by = "day", length.out = 2), usetz = TRUE)
+            "# }},
+            r! { c("2024-03-09 12:00 EST", "2024-03-10 13:00 EDT") }
+        );
+    }
+}
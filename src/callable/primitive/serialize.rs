@@ -0,0 +1,180 @@
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::err;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+
+/// Serialize an object, as a vector of bytes
+///
+/// The global environment is written as a reference, such that functions and
+/// formulas created at the top level are restored into the global
+/// environment of the reading session rather than copying the workspace.
+pub fn serialize(x: &Obj, stack: &CallStack) -> Result<Vec<u8>, Signal> {
+    let global = stack.frames[0].env.clone();
+    Ok(Snapshot::with_global(x, &global).serialize()?)
+}
+
+/// Unserialize an object, restoring it into the session of the call stack
+pub fn unserialize(bytes: &[u8], stack: &CallStack) -> EvalResult {
+    let global = stack.frames[0].env.clone();
+    Ok(Snapshot::unserialize(bytes)?.restore_global(global))
+}
+
+fn bytes_arg(x: Obj) -> Result<Vec<u8>, Signal> {
+    let x: Vec<f64> = x.try_into()?;
+    x.into_iter()
+        .map(|b| match b {
+            b if b.fract() == 0.0 && (0.0..=255.0).contains(&b) => Ok(b as u8),
+            _ => err!("argument must be a vector of bytes"),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "saveRDS")]
+pub struct PrimitiveSaveRDS;
+impl Callable for PrimitiveSaveRDS {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("object")), Expr::Missing),
+            (Some(String::from("file")), Expr::Missing),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let object = args.try_get_named("object")?.force(stack)?;
+        let file: String = args.try_get_named("file")?.force(stack)?.try_into()?;
+
+        let bytes = serialize(&object, stack)?;
+        match std::fs::write(&file, bytes) {
+            Ok(_) => Ok(Obj::Null),
+            Err(e) => err!(format!("cannot write file '{file}': {e}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "readRDS")]
+pub struct PrimitiveReadRDS;
+impl Callable for PrimitiveReadRDS {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("file")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let file: String = args.try_get_named("file")?.force(stack)?.try_into()?;
+
+        match std::fs::read(&file) {
+            Ok(bytes) => unserialize(&bytes, stack),
+            Err(e) => err!(format!("cannot open file '{file}': {e}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "serialize")]
+pub struct PrimitiveSerialize;
+impl Callable for PrimitiveSerialize {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("object")), Expr::Missing),
+            (Some(String::from("connection")), Expr::Null),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let object = args.try_get_named("object")?.force(stack)?;
+
+        // without connections, objects are only serialized to bytes
+        if args.try_get_named("connection")?.force(stack)? != Obj::Null {
+            return err!("connection must be NULL");
+        }
+
+        let bytes = serialize(&object, stack)?;
+        let bytes: Vec<i32> = bytes.into_iter().map(i32::from).collect();
+        Ok(Obj::Vector(Vector::from(bytes)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "unserialize")]
+pub struct PrimitiveUnserialize;
+impl Callable for PrimitiveUnserialize {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("connection")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let bytes = bytes_arg(args.try_get_named("connection")?.force(stack)?)?;
+        unserialize(&bytes, stack)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{r, r_expect};
+
+    #[test]
+    fn objects_round_trip_through_files() {
+        r_expect! {{"
+            path <- tempfile(fileext = '.rds')
+            x <- list(a = c(1, NA, 3), b = 'text', f = function(n) n + 1)
+            saveRDS(x, path)
+            y <- readRDS(path)
+            file.remove(path)
+            a <- y$a
+            f <- y$f
+            a[[3]] == 3 && y$b == 'text' && f(1) == 2
+        "}}
+    }
+
+    #[test]
+    fn global_environment_is_referenced() {
+        r_expect! {{"
+            p <- partial(paste, 'b')
+            fo <- y ~ x
+            n <- 1
+            f <- function() n
+            g <- unserialize(serialize(f))
+            n <- 2
+            sum(serialize(fo) >= 0) < 100 && g() == 2
+        "}}
+    }
+
+    #[test]
+    fn unserializable_primitives_are_an_error() {
+        assert!(r! { serialize(partial(paste, 'b')) }.is_err());
+    }
+
+    #[test]
+    fn external_objects_are_an_error() {
+        assert!(r! { unserialize(serialize(future(1), NULL)) }.is_err());
+        assert!(r! { serialize(list(con = textConnection("x"))) }.is_err());
+    }
+
+    #[test]
+    fn closures_keep_their_environments() {
+        r_expect! {{"
+            counter <- function() {
+              n <- 0
+              function() n <<- n + 1
+            }
+            inc <- counter()
+            inc()
+            copy <- unserialize(serialize(inc))
+            copy()
+            copy() == 3 && inc() == 2
+        "}}
+    }
+
+    #[test]
+    fn invalid_bytes_are_an_error() {
+        assert!(r! { unserialize(c(1, 2, 3)) }.is_err());
+        assert!(r! { unserialize(c(1000)) }.is_err());
+    }
+}
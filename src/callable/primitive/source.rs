@@ -0,0 +1,88 @@
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::context::Context;
+use crate::err;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+use crate::options;
+use crate::parser::parse_file;
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "source")]
+pub struct PrimitiveSource;
+impl Callable for PrimitiveSource {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("file")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let file: String = args.try_get_named("file")?.force(stack)?.try_into()?;
+
+        let input = match std::fs::read_to_string(&file) {
+            Ok(input) => input,
+            Err(e) => return err!(format!("cannot open file '{file}': {e}")),
+        };
+
+        // parsed using the current `locale` option, with file recorded in
+        // source references for breakpoints
+        let expr = match parse_file(&options::locale(), &file, &input) {
+            Ok(expr) => expr,
+            Err(Signal::Thunk) => return Ok(Obj::Null),
+            Err(e) => return Err(e),
+        };
+
+        // evaluated in the global environment
+        let global = stack.frames[0].env.clone();
+        stack.add_frame(expr.clone(), global);
+        let result = stack.eval_and_finalize(expr);
+        stack.pop_frame_and_return(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::debugger;
+    use crate::r;
+    use crate::repl::eval;
+
+    #[test]
+    fn breakpoints_pause_at_source_lines() {
+        let path: String = eval("tempfile(fileext = '.R')")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let file = path.rsplit('/').next().unwrap_or_default().to_string();
+        std::fs::write(&path, "f <- function() {\n  a <- 1\n  a + 1\n}\n").unwrap();
+
+        let prompts = Rc::new(RefCell::new(vec![]));
+        let recorded = prompts.clone();
+        debugger::set_input(Some(Box::new(move |prompt: &str| {
+            recorded.borrow_mut().push(prompt.to_string());
+            Some("c".to_string())
+        })));
+
+        let result = eval(&format!(
+            "setBreakpoint('{file}', 3); source('{path}'); f()"
+        ));
+
+        assert_eq!(result, r! { 2 });
+        assert_eq!(prompts.borrow().len(), 1);
+    }
+
+    #[test]
+    fn files_are_parsed_using_the_locale() {
+        let path: String = eval("tempfile(fileext = '.R')")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        std::fs::write(&path, "f <- función(a) a + 1\nx <- f(1)\n").unwrap();
+
+        eval("options(locale = 'es')").unwrap();
+        assert_eq!(eval(&format!("source('{path}'); x")), r! { 2 });
+    }
+}
@@ -0,0 +1,85 @@
+use r_derive::*;
+
+use crate::callable::core::*;
+use crate::err;
+use crate::lang::*;
+use crate::object::*;
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "stopifnot")]
+pub struct PrimitiveStopifnot;
+impl Callable for PrimitiveStopifnot {
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let env = stack.parent_frame().env;
+
+        // conditions are evaluated in order, stopping at the first failure
+        for (name, expr) in args {
+            let value = Obj::Closure(expr.clone(), env.clone()).force(stack)?;
+            let values = match value.as_logical()? {
+                Obj::Vector(Vector::Logical(v)) => v.inner().borrow().clone(),
+                _ => vec![],
+            };
+
+            if !values.is_empty() && values.iter().all(|x| x == &OptionNA::Some(true)) {
+                continue;
+            }
+
+            // named conditions are reported using their name as the message
+            return match name {
+                Some(message) => err!(message),
+                None if values.len() == 1 => err!(format!("{expr} is not TRUE")),
+                None => err!(format!("{expr} are not all TRUE")),
+            };
+        }
+
+        Ok(Obj::Null)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::Error;
+    use crate::lang::Signal;
+    use crate::r;
+
+    #[test]
+    fn passing_conditions() {
+        assert_eq!(r! { stopifnot(TRUE, 1 < 2, c(TRUE, TRUE)) }, r! { NULL });
+    }
+
+    #[test]
+    fn failing_condition_message() {
+        assert_eq!(
+            r! { x <- 3; stopifnot(x > 1, x < 2) },
+            Err(Signal::Error(Error::Other(String::from(
+                "x < 2 is not TRUE"
+            ))))
+        );
+        assert_eq!(
+            r! { stopifnot(c(1, 3) > 2) },
+            Err(Signal::Error(Error::Other(String::from(
+                "c(1, 3) > 2 are not all TRUE"
+            ))))
+        );
+    }
+
+    #[test]
+    fn named_conditions_use_name_as_message() {
+        assert_eq!(
+            r! { stopifnot("x must be positive" = -1 > 0) },
+            Err(Signal::Error(Error::Other(String::from(
+                "x must be positive"
+            ))))
+        );
+    }
+
+    #[test]
+    fn stops_at_first_failure() {
+        assert_eq!(
+            r! { stopifnot(FALSE, stop_here()) },
+            Err(Signal::Error(Error::Other(String::from(
+                "FALSE is not TRUE"
+            ))))
+        );
+    }
+}
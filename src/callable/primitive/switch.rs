@@ -0,0 +1,105 @@
+use r_derive::*;
+
+use crate::callable::core::*;
+use crate::err;
+use crate::lang::*;
+use crate::object::*;
+
+fn is_missing(x: &Obj) -> bool {
+    matches!(x, Obj::Closure(Expr::Missing, _))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "switch")]
+pub struct PrimitiveSwitch;
+impl Callable for PrimitiveSwitch {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("EXPR")), Expr::Missing),
+            (None, Expr::Ellipsis(None)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let expr = Obj::List(args).try_get_named("EXPR")?.force(stack)?;
+        let branches = ellipsis.values.borrow().clone();
+
+        let Obj::Vector(v) = &expr else {
+            return err!("EXPR must be a length 1 vector");
+        };
+
+        if v.len() != 1 {
+            return err!("EXPR must be a length 1 vector");
+        }
+
+        // only the selected branch is forced
+        if let Vector::Character(_) = v {
+            let selector: String = expr.try_into()?;
+            let defaults = branches.iter().filter(|(k, _)| k.is_none()).count();
+            if defaults > 1 {
+                return err!("duplicate 'switch' defaults");
+            }
+
+            let selected = branches
+                .iter()
+                .position(|(k, _)| k.as_ref() == Some(&selector))
+                .or_else(|| branches.iter().position(|(k, _)| k.is_none()));
+
+            // empty branches fall through to the next non-empty branch
+            let Some(i) = selected else {
+                return Ok(Obj::Null);
+            };
+
+            return match branches[i..].iter().find(|(_, v)| !is_missing(v)) {
+                Some((_, branch)) => branch.clone().force(stack),
+                None => Ok(Obj::Null),
+            };
+        }
+
+        let i: i32 = expr.try_into()?;
+        match branches.get((i as usize).wrapping_sub(1)) {
+            Some((_, branch)) if is_missing(branch) => err!("empty alternative in numeric switch"),
+            Some((_, branch)) if i >= 1 => branch.clone().force(stack),
+            _ => Ok(Obj::Null),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::r;
+
+    #[test]
+    fn character_selector() {
+        assert_eq!(r! { switch("b", a = 1, b = 2, c = 3) }, r! { 2 });
+        assert_eq!(r! { switch("z", a = 1, b = 2) }, r! { NULL });
+    }
+
+    #[test]
+    fn character_fall_through() {
+        assert_eq!(r! { switch("a", a = , b = "ab", c = "c") }, r! { "ab" });
+    }
+
+    #[test]
+    fn character_default() {
+        assert_eq!(r! { switch("z", a = 1, "default") }, r! { "default" });
+        assert!(r! { switch("z", a = 1, 2, 3) }.is_err());
+    }
+
+    #[test]
+    fn numeric_selector() {
+        assert_eq!(r! { switch(2, "a", "b", "c") }, r! { "b" });
+        assert_eq!(r! { switch(4, "a", "b", "c") }, r! { NULL });
+        assert_eq!(r! { switch(0, "a", "b", "c") }, r! { NULL });
+    }
+
+    #[test]
+    fn only_selected_branch_is_evaluated() {
+        assert_eq!(r! { switch("a", a = 1, b = stop_here()) }, r! { 1 });
+    }
+
+    #[test]
+    fn selector_must_be_scalar() {
+        assert!(r! { switch(c("a", "b"), a = 1) }.is_err());
+    }
+}
@@ -0,0 +1,241 @@
+use std::fs::File;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::err;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+use crate::time;
+use crate::warning;
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "Sys.getenv")]
+pub struct PrimitiveSysGetenv;
+impl Callable for PrimitiveSysGetenv {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Null),
+            (Some(String::from("unset")), Expr::String(String::new())),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = args.try_get_named("x")?.force(stack)?;
+        let Obj::Vector(Vector::Character(unset)) =
+            args.try_get_named("unset")?.force(stack)?.as_character()?
+        else {
+            return err!("argument 'unset' must be a character value");
+        };
+
+        // unset variables may be given as NA
+        let unset = unset.inner().borrow().first().cloned();
+        let unset = unset.unwrap_or(OptionNA::NA);
+
+        // without names, all variables are listed
+        if let Obj::Null = x {
+            let mut vars: Vec<(String, String)> = std::env::vars().collect();
+            vars.sort();
+            let vars = vars
+                .into_iter()
+                .map(|(k, v)| (Some(k), Obj::Vector(Vector::from(vec![v]))));
+
+            return Ok(Obj::List(List::from(vars.collect::<Vec<_>>())));
+        }
+
+        let names: Vec<String> = x.try_into()?;
+        let values: Vec<OptionNA<String>> = names
+            .iter()
+            .map(|name| std::env::var(name).map_or_else(|_| unset.clone(), OptionNA::Some))
+            .collect();
+
+        Ok(Obj::Vector(Vector::from(values)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "Sys.setenv")]
+pub struct PrimitiveSysSetenv;
+impl Callable for PrimitiveSysSetenv {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(None, Expr::Ellipsis(None))])
+    }
+
+    fn call_matched(&self, _args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut set = vec![];
+        for (name, value) in ellipsis.elements() {
+            let Some(name) = name else {
+                return err!("all arguments must be named");
+            };
+
+            let value: String = value.force(stack)?.try_into()?;
+            if name.is_empty() || name.contains('=') || value.contains('\0') {
+                return err!(format!("invalid environment variable '{name}'"));
+            }
+
+            std::env::set_var(name, value);
+            set.push(true);
+        }
+
+        Ok(Obj::Vector(Vector::from(set)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "Sys.time")]
+pub struct PrimitiveSysTime;
+impl Callable for PrimitiveSysTime {
+    fn call(&self, _args: ExprList, _stack: &mut CallStack) -> EvalResult {
+        let now = vec![OptionNA::Some(time::now())];
+        Ok(Obj::Vector(time::datetime(now, "")))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "Sys.sleep")]
+pub struct PrimitiveSysSleep;
+impl Callable for PrimitiveSysSleep {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("time")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let time: f64 = args.try_get_named("time")?.force(stack)?.try_into()?;
+        match Duration::try_from_secs_f64(time) {
+            Ok(duration) => std::thread::sleep(duration),
+            Err(_) => return err!("invalid 'time' value"),
+        }
+
+        Ok(Obj::Null)
+    }
+}
+
+/// Where the output of a command is sent
+enum Output {
+    Inherit,
+    Discard,
+    Capture,
+    File(String),
+}
+
+impl Output {
+    fn from_arg(x: Obj) -> Result<Output, Signal> {
+        if let Obj::Vector(Vector::Logical(_)) = x {
+            let capture: bool = x.try_into()?;
+            return Ok(if capture {
+                Output::Capture
+            } else {
+                Output::Discard
+            });
+        }
+
+        let path: String = x.try_into()?;
+        match path.as_str() {
+            "" => Ok(Output::Inherit),
+            _ => Ok(Output::File(path)),
+        }
+    }
+
+    fn stdio(&self) -> Result<Stdio, Signal> {
+        match self {
+            Output::Inherit => Ok(Stdio::inherit()),
+            Output::Discard => Ok(Stdio::null()),
+            Output::Capture => Ok(Stdio::piped()),
+            Output::File(path) => match File::create(path) {
+                Ok(file) => Ok(Stdio::from(file)),
+                Err(e) => err!(format!("cannot open file '{path}': {e}")),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "system2")]
+pub struct PrimitiveSystem2;
+impl Callable for PrimitiveSystem2 {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("command")), Expr::Missing),
+            (Some(String::from("args")), Expr::Null),
+            (Some(String::from("stdout")), Expr::String(String::new())),
+            (Some(String::from("stderr")), Expr::String(String::new())),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let command: String = args.try_get_named("command")?.force(stack)?.try_into()?;
+        let cmd_args: Vec<String> = args.try_get_named("args")?.force(stack)?.try_into()?;
+        let stdout = Output::from_arg(args.try_get_named("stdout")?.force(stack)?)?;
+        let stderr = Output::from_arg(args.try_get_named("stderr")?.force(stack)?)?;
+
+        // arguments are passed to the command directly, without a shell
+        let mut process = Command::new(&command);
+        process
+            .args(&cmd_args)
+            .stdout(stdout.stdio()?)
+            .stderr(stderr.stdio()?);
+
+        let capture = matches!(stdout, Output::Capture) || matches!(stderr, Output::Capture);
+        let output = match process.output() {
+            Ok(output) => output,
+            Err(e) => {
+                // as with a shell, commands which cannot be run have status 127
+                warning::warn(format!("error in running command '{command}': {e}"))?;
+                return Ok(match capture {
+                    true => Obj::Vector(Vector::from(Vec::<String>::new())),
+                    false => Obj::Vector(Vector::from(vec![127])),
+                });
+            }
+        };
+
+        let status = output.status.code().unwrap_or(-1);
+        if !capture {
+            return Ok(Obj::Vector(Vector::from(vec![status])));
+        }
+
+        if status != 0 {
+            warning::warn(format!("running command '{command}' had status {status}"))?;
+        }
+
+        // captured output is returned as lines, with any errors following
+        let text = [output.stdout, output.stderr].concat();
+        let lines: Vec<String> = String::from_utf8_lossy(&text)
+            .lines()
+            .map(String::from)
+            .collect();
+
+        Ok(Obj::Vector(Vector::from(lines)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::r;
+
+    #[test]
+    fn environment_variables() {
+        assert_eq!(
+            r! {{ "Sys.setenv(R_SYS_TEST_VAR = 'set'); Sys.getenv(c('R_SYS_TEST_VAR', 'R_SYS_TEST_UNSET'))" }},
+            r! { c("set", "") }
+        );
+        assert_eq!(
+            r! {{ "Sys.setenv(R_SYS_TEST_VAR = 'set'); Sys.getenv(c('R_SYS_TEST_UNSET', 'R_SYS_TEST_VAR'), unset = NA)" }},
+            r! { c(NA, "set") }
+        );
+    }
+
+    #[test]
+    fn commands_are_run() {
+        assert_eq!(
+            r! { system2("echo", c("a", "b"), stdout = TRUE) },
+            r! { "a b" }
+        );
+        assert_eq!(r! { system2("true", stdout = FALSE) }, r! { 0L });
+        assert_eq!(r! { system2("false", stdout = FALSE) }, r! { 1L });
+    }
+}
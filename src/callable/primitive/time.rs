@@ -0,0 +1,463 @@
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::err;
+use crate::internal_err;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+use crate::time::{self, Civil, Class};
+
+/// The elements of an object as strings
+fn strings(x: Obj) -> Result<Vec<OptionNA<String>>, Signal> {
+    match x.as_character()? {
+        Obj::Vector(Vector::Character(v)) => Ok(v.inner().borrow().clone()),
+        _ => internal_err!(),
+    }
+}
+
+/// A string argument, which may be `NULL`
+fn optional_string(x: Obj) -> Result<Option<String>, Signal> {
+    match x {
+        Obj::Null => Ok(None),
+        x => Ok(Some(x.try_into()?)),
+    }
+}
+
+fn numbers(x: Obj) -> Result<Vec<OptionNA<f64>>, Signal> {
+    match x.as_numeric()? {
+        Obj::Vector(v) => Ok(time::values(&v)),
+        _ => internal_err!(),
+    }
+}
+
+/// Shift values by the value of an origin, as given by `origin = `
+fn shift(values: Vec<OptionNA<f64>>, origin: Option<f64>) -> Vec<OptionNA<f64>> {
+    match origin {
+        Some(origin) => values.into_iter().map(|x| x.map(|x| x + origin)).collect(),
+        None => values,
+    }
+}
+
+/// Seconds since the epoch of dates and date-times
+fn seconds(x: &Vector) -> Result<Vec<OptionNA<f64>>, Signal> {
+    match Class::of(x) {
+        Some(Class::Date) => Ok(time::values(x)
+            .into_iter()
+            .map(|x| x.map(|x| x * time::SECS_PER_DAY as f64))
+            .collect()),
+        Some(Class::DateTime(_)) => Ok(time::values(x)),
+        _ => err!("expected a date or date-time"),
+    }
+}
+
+fn as_date(x: Obj, format: Option<&str>, origin: Obj, tz: Option<&str>) -> EvalResult {
+    let origin = match origin {
+        Obj::Null => None,
+        origin => match as_date(origin, None, Obj::Null, None)? {
+            Obj::Vector(v) => time::values(&v).first().and_then(|x| match x {
+                OptionNA::Some(x) => Some(*x),
+                OptionNA::NA => None,
+            }),
+            _ => None,
+        },
+    };
+
+    let Obj::Vector(v) = x else {
+        return err!("do not know how to convert 'x' to class \"Date\"");
+    };
+
+    let days = match (Class::of(&v), &v) {
+        (Some(Class::Date), _) => return Ok(Obj::Vector(v)),
+        (Some(Class::DateTime(own)), _) => {
+            let zone = time::zone(tz.unwrap_or(&own))?;
+            time::values(&v)
+                .into_iter()
+                .map(|x| match x {
+                    OptionNA::Some(x) if x.is_finite() => {
+                        OptionNA::Some(Civil::from_timestamp(x, &zone).days() as f64)
+                    }
+                    _ => OptionNA::NA,
+                })
+                .collect()
+        }
+        (_, Vector::Character(_)) => time::parse_dates(&strings(Obj::Vector(v))?, format)?,
+        _ => shift(numbers(Obj::Vector(v))?, origin),
+    };
+
+    Ok(Obj::Vector(time::date(days)))
+}
+
+fn as_datetime(x: Obj, format: Option<&str>, origin: Obj, tz: &str) -> EvalResult {
+    let origin = match origin {
+        Obj::Null => None,
+        origin => match as_datetime(origin, None, Obj::Null, "UTC")? {
+            Obj::Vector(v) => time::values(&v).first().and_then(|x| match x {
+                OptionNA::Some(x) => Some(*x),
+                OptionNA::NA => None,
+            }),
+            _ => None,
+        },
+    };
+
+    let Obj::Vector(v) = x else {
+        return err!("do not know how to convert 'x' to class \"POSIXct\"");
+    };
+
+    let secs = match (Class::of(&v), &v) {
+        // date-times are only given a new time zone when one is specified
+        (Some(Class::DateTime(own)), _) => {
+            let tz = if tz.is_empty() { own } else { tz.to_string() };
+            return Ok(Obj::Vector(time::datetime(time::values(&v), &tz)));
+        }
+        // dates are midnight in UTC
+        (Some(Class::Date), _) => {
+            let tz = if tz.is_empty() { "UTC" } else { tz };
+            return Ok(Obj::Vector(time::datetime(seconds(&v)?, tz)));
+        }
+        (_, Vector::Character(_)) => time::parse_datetimes(&strings(Obj::Vector(v))?, format, tz)?,
+        _ => shift(numbers(Obj::Vector(v))?, origin),
+    };
+
+    Ok(Obj::Vector(time::datetime(secs, tz)))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "as.Date")]
+pub struct PrimitiveAsDate;
+impl Callable for PrimitiveAsDate {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("format")), Expr::Null),
+            (Some(String::from("origin")), Expr::Null),
+            (Some(String::from("tz")), Expr::Null),
+            (None, Expr::Ellipsis(None)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = args.try_get_named("x")?.force(stack)?;
+        let format = optional_string(args.try_get_named("format")?.force(stack)?)?;
+        let origin = args.try_get_named("origin")?.force(stack)?;
+        let tz = optional_string(args.try_get_named("tz")?.force(stack)?)?;
+        as_date(x, format.as_deref(), origin, tz.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "as.POSIXct")]
+pub struct PrimitiveAsPOSIXct;
+impl Callable for PrimitiveAsPOSIXct {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("tz")), Expr::String(String::new())),
+            (Some(String::from("format")), Expr::Null),
+            (Some(String::from("origin")), Expr::Null),
+            (None, Expr::Ellipsis(None)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = args.try_get_named("x")?.force(stack)?;
+        let tz: String = args.try_get_named("tz")?.force(stack)?.try_into()?;
+        let format = optional_string(args.try_get_named("format")?.force(stack)?)?;
+        let origin = args.try_get_named("origin")?.force(stack)?;
+        as_datetime(x, format.as_deref(), origin, &tz)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "format")]
+pub struct PrimitiveFormat;
+impl Callable for PrimitiveFormat {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("format")), Expr::Null),
+            (Some(String::from("tz")), Expr::Null),
+            (Some(String::from("usetz")), Expr::Bool(false)),
+            (None, Expr::Ellipsis(None)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = args.try_get_named("x")?.force(stack)?;
+        let format = optional_string(args.try_get_named("format")?.force(stack)?)?;
+        let tz = optional_string(args.try_get_named("tz")?.force(stack)?)?;
+        let usetz: bool = args.try_get_named("usetz")?.force(stack)?.try_into()?;
+
+        let Obj::Vector(v) = x else {
+            return x.as_character();
+        };
+
+        if let Some(strings) = time::format_difftime(&v) {
+            return Ok(Obj::Vector(Vector::from(strings)));
+        }
+
+        match time::format(&v, format.as_deref(), tz.as_deref(), usetz)? {
+            Some(strings) => Ok(Obj::Vector(Vector::from(strings))),
+            None => Ok(Obj::Vector(v.as_character())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "strftime")]
+pub struct PrimitiveStrftime;
+impl Callable for PrimitiveStrftime {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("format")), Expr::String(String::new())),
+            (Some(String::from("tz")), Expr::String(String::new())),
+            (Some(String::from("usetz")), Expr::Bool(false)),
+            (None, Expr::Ellipsis(None)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = args.try_get_named("x")?.force(stack)?;
+        let format: String = args.try_get_named("format")?.force(stack)?.try_into()?;
+        let tz: String = args.try_get_named("tz")?.force(stack)?.try_into()?;
+        let usetz: bool = args.try_get_named("usetz")?.force(stack)?.try_into()?;
+
+        // values other than date-times are first read as date-times
+        let x = match &x {
+            Obj::Vector(v) if Class::of(v).is_some() => x,
+            _ => as_datetime(x, None, Obj::Null, &tz)?,
+        };
+
+        let Obj::Vector(v) = x else {
+            return internal_err!();
+        };
+        let format = Some(format.as_str()).filter(|f| !f.is_empty());
+        let tz = Some(tz.as_str()).filter(|tz| !tz.is_empty());
+        match time::format(&v, format, tz, usetz)? {
+            Some(strings) => Ok(Obj::Vector(Vector::from(strings))),
+            None => err!("'x' must be a date or date-time"),
+        }
+    }
+}
+
+/// Read date-times from strings
+///
+/// Unlike R, which returns a `POSIXlt` broken down into its fields, the
+/// result is a `POSIXct`.
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "strptime")]
+pub struct PrimitiveStrptime;
+impl Callable for PrimitiveStrptime {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("format")), Expr::Missing),
+            (Some(String::from("tz")), Expr::String(String::new())),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = strings(args.try_get_named("x")?.force(stack)?)?;
+        let format: String = args.try_get_named("format")?.force(stack)?.try_into()?;
+        let tz: String = args.try_get_named("tz")?.force(stack)?.try_into()?;
+
+        let secs = time::parse_datetimes(&x, Some(&format), &tz)?;
+        Ok(Obj::Vector(time::datetime(secs, &tz)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "difftime")]
+pub struct PrimitiveDifftime;
+impl Callable for PrimitiveDifftime {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("time1")), Expr::Missing),
+            (Some(String::from("time2")), Expr::Missing),
+            (Some(String::from("tz")), Expr::String(String::new())),
+            (
+                Some(String::from("units")),
+                Expr::String(String::from("auto")),
+            ),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let tz: String = args.try_get_named("tz")?.force(stack)?.try_into()?;
+        let units: String = args.try_get_named("units")?.force(stack)?.try_into()?;
+
+        let mut time = |name: &str| -> Result<Vector, Signal> {
+            let x = args.try_get_named(name)?.force(stack)?;
+            let x = match &x {
+                Obj::Vector(v)
+                    if matches!(Class::of(v), Some(Class::Date | Class::DateTime(_))) =>
+                {
+                    x
+                }
+                _ => as_datetime(x, None, Obj::Null, &tz)?,
+            };
+
+            match x {
+                Obj::Vector(v) => Ok(v),
+                _ => internal_err!(),
+            }
+        };
+
+        let time1 = seconds(&time("time1")?)?;
+        let time2 = seconds(&time("time2")?)?;
+
+        let n = time1.len().max(time2.len());
+        let n = if time1.is_empty() || time2.is_empty() {
+            0
+        } else {
+            n
+        };
+        let secs: Vec<OptionNA<f64>> = (0..n)
+            .map(
+                |i| match (&time1[i % time1.len()], &time2[i % time2.len()]) {
+                    (OptionNA::Some(l), OptionNA::Some(r)) => OptionNA::Some(l - r),
+                    _ => OptionNA::NA,
+                },
+            )
+            .collect();
+
+        let units = match units.as_str() {
+            "auto" => time::auto_units(&secs),
+            units => units,
+        };
+
+        let Some(scale) = time::unit_seconds(units) else {
+            return err!(format!("invalid units specified: '{units}'"));
+        };
+
+        let values = secs.into_iter().map(|x| x.map(|x| x / scale)).collect();
+        Ok(Obj::Vector(time::difftime(values, units)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "Sys.Date")]
+pub struct PrimitiveSysDate;
+impl Callable for PrimitiveSysDate {
+    fn call(&self, _args: ExprList, _stack: &mut CallStack) -> EvalResult {
+        let today = Civil::from_timestamp(time::now(), &time::tz::Zone::local()).days();
+        Ok(Obj::Vector(time::date(vec![OptionNA::Some(today as f64)])))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "unclass")]
+pub struct PrimitiveUnclass;
+impl Callable for PrimitiveUnclass {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("x")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        match args.try_get_named("x")?.force(stack)? {
+            Obj::Vector(v) => {
+                let mut attrs = v.attributes().clone();
+                attrs.set("class", vec![]);
+                Ok(Obj::Vector(v.with_attributes(attrs)))
+            }
+            Obj::List(mut l) => {
+                l.attributes.set("class", vec![]);
+                Ok(Obj::List(l))
+            }
+            x => Ok(x),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{r, r_expect};
+
+    #[test]
+    fn dates_are_read_and_formatted() {
+        assert_eq!(r! { format(as.Date("2024-03-05")) }, r! { "2024-03-05" });
+        assert_eq!(r! { format(as.Date("2024/03/05")) }, r! { "2024-03-05" });
+        assert_eq!(
+            r! { format(as.Date("05.03.24", format = "%d.%m.%y"), "%A %d %B %Y") },
+            r! { "Tuesday 05 March 2024" }
+        );
+        assert_eq!(
+            r! { format(as.Date(c("2024-01-31", "not a date", NA))) },
+            r! { c("2024-01-31", NA, NA) }
+        );
+        assert_eq!(
+            r! { format(as.Date(10, origin = "2024-01-01")) },
+            r! { "2024-01-11" }
+        );
+        r_expect! { unclass(as.Date("1970-01-11")) == 10 }
+    }
+
+    #[test]
+    fn dates_are_shifted_and_compared() {
+        assert_eq!(
+            r! { format(as.Date("2024-02-28") + 2) },
+            r! { "2024-03-01" }
+        );
+        r_expect! { as.Date("2024-03-01") - as.Date("2024-02-01") == 29 }
+        r_expect! { as.Date("2024-03-01") > "2024-02-29" }
+        r_expect! { as.Date("2024-03-01") == as.Date("2024-02-29") + 1 }
+        assert!(r! { as.Date("2024-03-01") * 2 }.is_err());
+        assert!(r! { as.Date("2024-03-01") + as.Date("2024-03-01") }.is_err());
+    }
+
+    #[test]
+    fn datetimes_use_time_zones() {
+        assert_eq!(
+            r! { format(as.POSIXct("2024-07-01 12:30", tz = "America/New_York"), usetz = TRUE) },
+            r! { "2024-07-01 12:30 EDT" }
+        );
+        assert_eq!(
+            r! { format(as.POSIXct("2005-03-20 12:00:00", tz = "America/New_York"), usetz = TRUE) },
+            r! { "2005-03-20 12:00 EST" }
+        );
+        assert_eq!(
+            r! { format(as.POSIXct("2024-01-01 12:30", tz = "UTC"), tz = "Europe/Paris", usetz = TRUE) },
+            r! { "2024-01-01 13:30 CET" }
+        );
+        r_expect! { unclass(as.POSIXct("1970-01-02", tz = "UTC")) == 86400 }
+        assert_eq!(
+            r! { strftime(strptime("5/3/2024 14:07:09", "%d/%m/%Y %H:%M:%S", tz = "UTC") + 60, "%H:%M:%S", tz = "UTC") },
+            r! { "14:08:09" }
+        );
+    }
+
+    #[test]
+    fn weeks_are_formatted() {
+        assert_eq!(
+            r! { format(as.Date("2024-03-08"), "%A %U %V") },
+            r! { "Friday 09 10" }
+        );
+        assert!(r! { format(as.Date("2024-03-08"), "%A %Q") }.is_err());
+    }
+
+    #[test]
+    fn differences_have_units() {
+        assert_eq!(
+            r! { format(as.POSIXct("2024-01-01 12:00", tz = "UTC") - as.POSIXct("2024-01-01 10:30", tz = "UTC")) },
+            r! { "1.5 hours" }
+        );
+        assert_eq!(
+            r! { format(difftime(as.Date("2024-01-15"), as.Date("2024-01-01"), units = "weeks")) },
+            r! { "2 weeks" }
+        );
+        assert_eq!(
+            r! {{ r#"
+                x <- as.POSIXct("2024-01-01", tz = "UTC")
+                format(x + difftime(x + 7200, x))
+            "# }},
+            r! { "2024-01-01 02:00" }
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::err;
+use crate::lang::{CallStack, EvalResult};
+use crate::object::*;
+use crate::trace::{self, Tracer};
+
+/// Evaluate the `what` argument, which must be a closure
+fn closure_arg(args: &mut Obj, stack: &mut CallStack) -> EvalResult {
+    match args.try_get_named("what")?.force(stack)? {
+        Obj::Function(_, Expr::Primitive(_), ..) => err!("argument must be a closure"),
+        f @ Obj::Function(..) => Ok(f),
+        _ => err!("argument must be a function"),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "trace")]
+pub struct PrimitiveTrace;
+impl Callable for PrimitiveTrace {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("what")), Expr::Missing),
+            (Some(String::from("tracer")), Expr::Null),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let what = closure_arg(&mut args, stack)?;
+
+        let tracer = match args.try_get_named("tracer")?.force(stack)? {
+            Obj::Null => Tracer::Print,
+            Obj::Expr(expr) => Tracer::Expr(expr),
+            f @ Obj::Function(..) => Tracer::Function(f),
+            _ => return err!("tracer must be a function or quoted expression"),
+        };
+
+        trace::trace(what, tracer);
+        Ok(Obj::Null)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "untrace")]
+pub struct PrimitiveUntrace;
+impl Callable for PrimitiveUntrace {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("what")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let what = closure_arg(&mut args, stack)?;
+
+        // returns the number of calls made while traced
+        match trace::untrace(&what) {
+            Some(calls) => Ok(Obj::Vector(Vector::from(vec![calls as i32]))),
+            None => Ok(Obj::Null),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{r, r_expect};
+
+    #[test]
+    fn tracer_evaluates_in_traced_frame() {
+        r_expect! {{"
+            f <- function(x) x + 1
+            trace(f, quote(x <- x * 10))
+            f(1) == 11
+        "}}
+    }
+
+    #[test]
+    fn untrace_returns_call_count() {
+        assert_eq!(
+            r! {{"
+                f <- function(x) x
+                trace(f, function() NULL)
+                f(1); f(2); f(3)
+                untrace(f)
+            "}},
+            r! { 3L }
+        );
+    }
+
+    #[test]
+    fn untraced_functions_are_no_longer_counted() {
+        assert_eq!(
+            r! {{"
+                f <- function(x) x
+                trace(f)
+                untrace(f)
+                f(1)
+                untrace(f)
+            "}},
+            r! { NULL }
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::lang::{CallStack, EvalResult, TRACEBACK};
+use crate::object::*;
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "traceback")]
+pub struct PrimitiveTraceback;
+impl Callable for PrimitiveTraceback {
+    fn call(&self, _args: ExprList, stack: &mut CallStack) -> EvalResult {
+        // recorded at the top level after an error, see CallStack::record_traceback
+        let global = stack.frames[0].env.clone();
+        match global.get(TRACEBACK.to_string()) {
+            Ok(calls @ Obj::List(_)) => Ok(calls),
+            _ => Ok(Obj::Null),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use crate::context::Context;
+    use crate::lang::{CallStack, EvalResult};
+    use crate::object::{Environment, Obj};
+    use crate::parser::{Localization, LocalizedParser};
+    use crate::r;
+
+    fn eval_in(env: &Rc<Environment>, input: &str) -> EvalResult {
+        let mut stack = CallStack::from(env.clone());
+        let expr = Localization::En.parse_input(input)?;
+        let result = stack.eval_and_finalize(expr);
+        if result.is_err() {
+            stack.record_traceback();
+        }
+        result
+    }
+
+    #[test]
+    fn no_error_returns_null() {
+        assert_eq!(r! { traceback() }, Ok(Obj::Null))
+    }
+
+    #[test]
+    fn error_frames_are_captured() {
+        let env = Rc::new(Environment {
+            parent: Some(Environment::from_builtins()),
+            ..Default::default()
+        });
+
+        let setup = "f <- function() g(); g <- function() h(); h <- function() undefined";
+        eval_in(&env, setup).expect("setup evaluates without error");
+        assert!(eval_in(&env, "f()").is_err());
+
+        assert_eq!(
+            eval_in(&env, "traceback()"),
+            eval_in(&env, "list(quote(h()), quote(g()), quote(f()))")
+        );
+    }
+
+    #[test]
+    fn forced_arguments_add_no_frames() {
+        let env = Rc::new(Environment {
+            parent: Some(Environment::from_builtins()),
+            ..Default::default()
+        });
+
+        let setup = "f <- function(x) x + 1; g <- function(y) f(y)";
+        eval_in(&env, setup).expect("setup evaluates without error");
+        assert!(eval_in(&env, "g(undefinedvar)").is_err());
+
+        assert_eq!(
+            eval_in(&env, "traceback()"),
+            eval_in(
+                &env,
+                "list(quote(x + 1), quote(f(y)), quote(g(undefinedvar)))"
+            )
+        );
+    }
+
+    #[test]
+    fn frames_are_released_after_error() {
+        let env = Rc::new(Environment {
+            parent: Some(Environment::from_builtins()),
+            ..Default::default()
+        });
+
+        let mut stack = CallStack::from(env.clone());
+        let expr = Localization::En
+            .parse_input("f <- function() undefined; f()")
+            .expect("parses");
+
+        assert!(stack.eval_and_finalize(expr).is_err());
+        assert_eq!(stack.frames.len(), 1);
+        assert_eq!(stack.traceback().frames.len(), 2);
+    }
+}
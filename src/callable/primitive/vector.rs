@@ -0,0 +1,706 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+use r_derive::builtin;
+
+use super::seq::MAX_LENGTH;
+use crate::callable::core::*;
+use crate::err;
+use crate::internal_err;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::*;
+
+/// A comparable value of a vector element
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+enum Key {
+    Number(f64),
+    String(String),
+}
+
+// `NaN`s are never keys, so numbers are totally ordered
+impl Eq for Key {}
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            // as -0 == 0, both hash alike
+            Key::Number(x) => (x + 0.0).to_bits().hash(state),
+            Key::String(x) => x.hash(state),
+        }
+    }
+}
+
+/// The elements of a vector as keys, where `NA`s and `NaN`s are `None`
+fn keys(x: &Vector) -> Vec<Option<Key>> {
+    fn collect<T: Clone>(v: &[OptionNA<T>], f: impl Fn(T) -> Option<Key>) -> Vec<Option<Key>> {
+        v.iter()
+            .map(|xi| match xi {
+                OptionNA::Some(xi) => f(xi.clone()),
+                OptionNA::NA => None,
+            })
+            .collect()
+    }
+
+    match x {
+        Vector::Numeric(v) => collect(&v.inner().borrow(), |x| {
+            (!x.is_nan()).then_some(Key::Number(x))
+        }),
+        Vector::Integer(v) => collect(&v.inner().borrow(), |x| Some(Key::Number(x as f64))),
+        Vector::Logical(v) => collect(&v.inner().borrow(), |x| Some(Key::Number(x as i32 as f64))),
+        Vector::Character(v) => collect(&v.inner().borrow(), |x| Some(Key::String(x))),
+    }
+}
+
+/// The elements of a vector at each of a set of indices, keeping the
+/// attributes of the vector
+fn select(x: &Vector, indices: &[usize]) -> Vector {
+    fn pick<T: Clone>(v: &[T], indices: &[usize]) -> Vec<T> {
+        indices.iter().map(|&i| v[i].clone()).collect()
+    }
+
+    let result = match x {
+        Vector::Numeric(v) => Vector::from(pick(&v.inner().borrow(), indices)),
+        Vector::Integer(v) => Vector::from(pick(&v.inner().borrow(), indices)),
+        Vector::Logical(v) => Vector::from(pick(&v.inner().borrow(), indices)),
+        Vector::Character(v) => Vector::from(pick(&v.inner().borrow(), indices)),
+    };
+
+    let attrs = x.clone().materialize().attributes().clone();
+    result.with_attributes(attrs.subset_names(indices.iter().map(|&i| Some(i))))
+}
+
+fn vector(x: Obj) -> Result<Vector, Signal> {
+    match x {
+        Obj::Vector(v) => Ok(v),
+        Obj::Null => Ok(Vector::from(Vec::<OptionNA<bool>>::new())),
+        _ => err!("argument is not a vector"),
+    }
+}
+
+/// Positions as 1-based integer indices
+fn positions(x: impl IntoIterator<Item = usize>) -> Obj {
+    let x: Vec<i32> = x.into_iter().map(|i| i as i32 + 1).collect();
+    Obj::Vector(Vector::from(x))
+}
+
+/// Where `NA`s are placed when ordering
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NaLast {
+    First,
+    Last,
+    Remove,
+    Keep,
+}
+
+impl NaLast {
+    fn from_arg(x: Obj) -> Result<NaLast, Signal> {
+        if let Obj::Vector(Vector::Character(_)) = x {
+            let x: String = x.try_into()?;
+            return match x.as_str() {
+                "keep" => Ok(NaLast::Keep),
+                _ => err!("invalid 'na.last' argument"),
+            };
+        }
+
+        let Obj::Vector(Vector::Logical(v)) = x.as_logical()? else {
+            return internal_err!();
+        };
+
+        let first = v.inner().borrow().first().cloned();
+        match first {
+            Some(OptionNA::Some(true)) => Ok(NaLast::Last),
+            Some(OptionNA::Some(false)) => Ok(NaLast::First),
+            Some(OptionNA::NA) => Ok(NaLast::Remove),
+            None => err!("invalid 'na.last' argument"),
+        }
+    }
+}
+
+fn compare(a: &Option<Key>, b: &Option<Key>, decreasing: bool, na_last: NaLast) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, _) if na_last == NaLast::First => Ordering::Less,
+        (None, _) => Ordering::Greater,
+        (_, None) if na_last == NaLast::First => Ordering::Greater,
+        (_, None) => Ordering::Less,
+        (Some(a), Some(b)) if decreasing => b.partial_cmp(a).unwrap_or(Ordering::Equal),
+        (Some(a), Some(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+    }
+}
+
+/// A stable ordering of elements by one or more keys, with ties in earlier
+/// keys broken by later keys
+fn order(keys: &[Vec<Option<Key>>], decreasing: bool, na_last: NaLast) -> Vec<usize> {
+    let n = keys.first().map_or(0, |k| k.len());
+    let mut indices: Vec<usize> = (0..n).collect();
+
+    if na_last == NaLast::Remove {
+        indices.retain(|&i| keys.iter().all(|k| k[i].is_some()));
+    }
+
+    indices.sort_by(|&a, &b| {
+        keys.iter()
+            .map(|k| compare(&k[a], &k[b], decreasing, na_last))
+            .find(|o| *o != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+
+    indices
+}
+
+/// Whether each element is a repeat of an earlier element
+fn duplicates(x: &Vector, from_last: bool) -> Vec<bool> {
+    let keys = keys(x);
+    let mut seen = HashSet::new();
+    let mut result = vec![false; keys.len()];
+    let indices: Box<dyn Iterator<Item = usize>> = match from_last {
+        true => Box::new((0..keys.len()).rev()),
+        false => Box::new(0..keys.len()),
+    };
+
+    for i in indices {
+        result[i] = !seen.insert(&keys[i]);
+    }
+
+    result
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "rep")]
+pub struct PrimitiveRep;
+impl Callable for PrimitiveRep {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("times")), Expr::Number(1.0)),
+            (Some(String::from("each")), Expr::Number(1.0)),
+            (Some(String::from("length.out")), Expr::Null),
+            (None, Expr::Ellipsis(None)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = match args.try_get_named("x")?.force(stack)? {
+            Obj::Null => return Ok(Obj::Null),
+            x => vector(x)?,
+        };
+
+        // counts are read as numbers, such that those too large for an
+        // integer are not truncated
+        let Obj::Vector(Vector::Numeric(times)) =
+            args.try_get_named("times")?.force(stack)?.as_numeric()?
+        else {
+            return internal_err!();
+        };
+
+        let each: i32 = args
+            .try_get_named("each")?
+            .force(stack)?
+            .as_integer()?
+            .try_into()?;
+        let length_out = match args.try_get_named("length.out")?.force(stack)? {
+            Obj::Null => None,
+            n => Some(TryInto::<f64>::try_into(n.as_numeric()?)?),
+        };
+
+        let times: Vec<usize> = times
+            .inner()
+            .borrow()
+            .iter()
+            .map(|t| match t {
+                OptionNA::Some(t) if *t > MAX_LENGTH => err!("result would be too long a vector"),
+                OptionNA::Some(t) if *t >= 0.0 => Ok(*t as usize),
+                _ => err!("invalid 'times' argument"),
+            })
+            .collect::<Result<_, _>>()?;
+
+        if each < 0 {
+            return err!("invalid 'each' argument");
+        }
+
+        let too_long = |n: Option<usize>| n.is_none_or(|n| n as f64 > MAX_LENGTH);
+        if too_long(x.len().checked_mul(each as usize)) {
+            return err!("result would be too long a vector");
+        }
+
+        let each: Vec<usize> = (0..x.len())
+            .flat_map(|i| std::iter::repeat_n(i, each as usize))
+            .collect();
+
+        let indices: Vec<usize> = match (length_out, &times[..]) {
+            (Some(n), _) if n > MAX_LENGTH => return err!("result would be too long a vector"),
+            (Some(n), _) if n >= 0.0 => each.iter().cycle().take(n as usize).copied().collect(),
+            (Some(_), _) => return err!("invalid 'length.out' argument"),
+            (None, [times]) if too_long(each.len().checked_mul(*times)) => {
+                return err!("result would be too long a vector")
+            }
+            (None, [times]) => each
+                .iter()
+                .cycle()
+                .take(each.len() * times)
+                .copied()
+                .collect(),
+            (None, times) if times.len() == each.len() => {
+                if too_long(times.iter().try_fold(0_usize, |n, &t| n.checked_add(t))) {
+                    return err!("result would be too long a vector");
+                }
+
+                each.iter()
+                    .zip(times)
+                    .flat_map(|(&i, &t)| std::iter::repeat_n(i, t))
+                    .collect()
+            }
+            _ => return err!("invalid 'times' argument"),
+        };
+
+        Ok(Obj::Vector(select(&x, &indices)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "rev")]
+pub struct PrimitiveRev;
+impl Callable for PrimitiveRev {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("x")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = match args.try_get_named("x")?.force(stack)? {
+            Obj::Null => return Ok(Obj::Null),
+            x => vector(x)?,
+        };
+
+        let indices: Vec<usize> = (0..x.len()).rev().collect();
+        Ok(Obj::Vector(select(&x, &indices)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "sort")]
+pub struct PrimitiveSort;
+impl Callable for PrimitiveSort {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("decreasing")), Expr::Bool(false)),
+            (Some(String::from("na.last")), Expr::NA),
+            (None, Expr::Ellipsis(None)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = match args.try_get_named("x")?.force(stack)? {
+            Obj::Null => return Ok(Obj::Null),
+            x => vector(x)?,
+        };
+
+        let decreasing: bool = args.try_get_named("decreasing")?.force(stack)?.try_into()?;
+        let na_last = NaLast::from_arg(args.try_get_named("na.last")?.force(stack)?)?;
+
+        let indices = order(&[keys(&x)], decreasing, na_last);
+        Ok(Obj::Vector(select(&x, &indices)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "order")]
+pub struct PrimitiveOrder;
+impl Callable for PrimitiveOrder {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (None, Expr::Ellipsis(None)),
+            (Some(String::from("na.last")), Expr::Bool(true)),
+            (Some(String::from("decreasing")), Expr::Bool(false)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        // arguments following the ellipsis are only given when named
+        let mut args = Obj::List(args);
+        let decreasing: bool = match args.get_named("decreasing") {
+            Some(x) => x.force(stack)?.try_into()?,
+            None => false,
+        };
+
+        let na_last = match args.get_named("na.last") {
+            Some(x) => NaLast::from_arg(x.force(stack)?)?,
+            None => NaLast::Last,
+        };
+
+        let mut by = vec![];
+        for (_, x) in ellipsis.elements() {
+            by.push(keys(&vector(x.force(stack)?)?));
+        }
+
+        if by.iter().any(|k| k.len() != by[0].len()) {
+            return err!("argument lengths differ");
+        }
+
+        Ok(positions(order(&by, decreasing, na_last)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "rank")]
+pub struct PrimitiveRank;
+impl Callable for PrimitiveRank {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("na.last")), Expr::Bool(true)),
+            (
+                Some(String::from("ties.method")),
+                Expr::String(String::from("average")),
+            ),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = vector(args.try_get_named("x")?.force(stack)?)?;
+        let na_last = NaLast::from_arg(args.try_get_named("na.last")?.force(stack)?)?;
+        let ties: String = args
+            .try_get_named("ties.method")?
+            .force(stack)?
+            .try_into()?;
+
+        // NAs which are kept are left unranked
+        let keys = keys(&x);
+        let ranked = match na_last {
+            NaLast::Keep => NaLast::Remove,
+            na_last => na_last,
+        };
+
+        let sorted = order(std::slice::from_ref(&keys), false, ranked);
+        let mut ranks: Vec<OptionNA<f64>> = vec![OptionNA::NA; keys.len()];
+
+        // runs of tied values, where NAs are never tied
+        let mut start = 0;
+        while start < sorted.len() {
+            let key = &keys[sorted[start]];
+            let len = match key {
+                Some(_) => sorted[start..]
+                    .iter()
+                    .take_while(|&&i| keys[i] == *key)
+                    .count(),
+                None => 1,
+            };
+
+            let run = &sorted[start..start + len];
+            for (j, &i) in run.iter().enumerate() {
+                let (first, last) = ((start + 1) as f64, (start + len) as f64);
+                ranks[i] = OptionNA::Some(match ties.as_str() {
+                    "average" => (first + last) / 2.0,
+                    "min" => first,
+                    "max" => last,
+                    "first" => first + j as f64,
+                    "last" => last - j as f64,
+                    _ => return err!("invalid 'ties.method' argument"),
+                });
+            }
+
+            start += len;
+        }
+
+        if na_last == NaLast::Remove {
+            ranks.retain(|r| *r != OptionNA::NA);
+        }
+
+        Ok(Obj::Vector(match ties.as_str() {
+            "average" => Vector::from(ranks),
+            _ => Vector::from(ranks).as_integer(),
+        }))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "unique")]
+pub struct PrimitiveUnique;
+impl Callable for PrimitiveUnique {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("fromLast")), Expr::Bool(false)),
+            (None, Expr::Ellipsis(None)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = match args.try_get_named("x")?.force(stack)? {
+            Obj::Null => return Ok(Obj::Null),
+            x => vector(x)?,
+        };
+
+        let from_last: bool = args.try_get_named("fromLast")?.force(stack)?.try_into()?;
+        let indices: Vec<usize> = duplicates(&x, from_last)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, dup)| (!dup).then_some(i))
+            .collect();
+
+        Ok(Obj::Vector(select(&x, &indices)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "duplicated")]
+pub struct PrimitiveDuplicated;
+impl Callable for PrimitiveDuplicated {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("x")), Expr::Missing),
+            (Some(String::from("fromLast")), Expr::Bool(false)),
+            (None, Expr::Ellipsis(None)),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let x = vector(args.try_get_named("x")?.force(stack)?)?;
+        let from_last: bool = args.try_get_named("fromLast")?.force(stack)?.try_into()?;
+        Ok(Obj::Vector(Vector::from(duplicates(&x, from_last))))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "which")]
+pub struct PrimitiveWhich;
+impl Callable for PrimitiveWhich {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("x")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let Obj::Vector(Vector::Logical(x)) = args.try_get_named("x")?.force(stack)? else {
+            return err!("argument to 'which' is not logical");
+        };
+
+        let x = x.inner();
+        let x = x.borrow();
+        let which = x.iter().enumerate().filter_map(|(i, xi)| match xi {
+            OptionNA::Some(true) => Some(i),
+            _ => None,
+        });
+
+        Ok(positions(which))
+    }
+}
+
+/// The position of the first extreme value, ignoring `NA`s
+fn which_extreme(x: Obj, wanted: Ordering) -> EvalResult {
+    let x = match vector(x)? {
+        Vector::Character(_) => return err!("'x' must be numeric or logical"),
+        x => x,
+    };
+
+    let keys = keys(&x);
+    let extreme = keys
+        .iter()
+        .enumerate()
+        .filter_map(|(i, k)| k.as_ref().map(|k| (i, k)))
+        .fold(None, |best: Option<(usize, &Key)>, (i, k)| match best {
+            Some((_, b)) if k.partial_cmp(b) != Some(wanted) => best,
+            _ => Some((i, k)),
+        });
+
+    Ok(positions(extreme.map(|(i, _)| i)))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "which.max")]
+pub struct PrimitiveWhichMax;
+impl Callable for PrimitiveWhichMax {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("x")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        which_extreme(args.try_get_named("x")?.force(stack)?, Ordering::Greater)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "which.min")]
+pub struct PrimitiveWhichMin;
+impl Callable for PrimitiveWhichMin {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![(Some(String::from("x")), Expr::Missing)])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        which_extreme(args.try_get_named("x")?.force(stack)?, Ordering::Less)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "tabulate")]
+pub struct PrimitiveTabulate;
+impl Callable for PrimitiveTabulate {
+    fn formals(&self) -> ExprList {
+        ExprList::from(vec![
+            (Some(String::from("bin")), Expr::Missing),
+            (Some(String::from("nbins")), Expr::Null),
+        ])
+    }
+
+    fn call_matched(&self, args: List, _ellipsis: List, stack: &mut CallStack) -> EvalResult {
+        let mut args = Obj::List(args);
+        let Obj::Vector(Vector::Integer(bin)) =
+            args.try_get_named("bin")?.force(stack)?.as_integer()?
+        else {
+            return internal_err!();
+        };
+
+        let bin = bin.inner();
+        let bin = bin.borrow();
+
+        // by default, there are as many bins as the largest value
+        let nbins = match args.try_get_named("nbins")?.force(stack)? {
+            Obj::Null => bin
+                .iter()
+                .filter_map(|b| match b {
+                    OptionNA::Some(b) => Some(*b),
+                    OptionNA::NA => None,
+                })
+                .fold(1, i32::max),
+            n => n.as_integer()?.try_into()?,
+        };
+
+        if nbins < 0 {
+            return err!("invalid 'nbins' argument");
+        }
+
+        // values outside of the bins, and NAs, are ignored
+        let mut counts = vec![0; nbins as usize];
+        for b in bin.iter() {
+            if let OptionNA::Some(b @ 1..) = b {
+                if let Some(count) = counts.get_mut(*b as usize - 1) {
+                    *count += 1;
+                }
+            }
+        }
+
+        Ok(Obj::Vector(Vector::from(counts)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::r;
+
+    #[test]
+    fn vectors_are_repeated_and_reversed() {
+        assert_eq!(r! { rep(1:2, 2) }, r! { c(1, 2, 1, 2) });
+        assert_eq!(
+            r! { rep(c("a", "b"), each = 2) },
+            r! { c("a", "a", "b", "b") }
+        );
+        assert_eq!(
+            r! { rep(c(TRUE, NA), times = c(1, 3)) },
+            r! { c(TRUE, NA, NA, NA) }
+        );
+        assert_eq!(r! { rep(1:3, length.out = 5) }, r! { c(1, 2, 3, 1, 2) });
+        assert_eq!(r! { rev(c("a", NA, "c")) }, r! { c("c", NA, "a") });
+        assert!(r! { rep(1:3, times = 1:2) }.is_err());
+    }
+
+    #[test]
+    fn repeats_are_limited_in_length() {
+        assert!(r! { rep(1, times = 3e9) }.is_err());
+        assert!(r! { rep(1, length.out = 3e9) }.is_err());
+        assert!(r! { rep(1:2, each = 2e9) }.is_err());
+        assert!(r! { rep(1:2, each = 2, times = 1e9) }.is_err());
+        assert!(r! { rep(1:2, times = c(2e9, 2e9)) }.is_err());
+    }
+
+    #[test]
+    fn vectors_are_sorted() {
+        assert_eq!(r! { sort(c(3, NA, 1, 2)) }, r! { c(1, 2, 3) });
+        assert_eq!(
+            r! { sort(c(3, NA, 1, 2), decreasing = TRUE, na.last = TRUE) },
+            r! { c(3, 2, 1, NA) }
+        );
+        assert_eq!(r! { sort(c("b", "c", "a")) }, r! { c("a", "b", "c") });
+        assert_eq!(
+            r! { sort(c(TRUE, FALSE, NA), na.last = FALSE) },
+            r! { c(NA, FALSE, TRUE) }
+        );
+        assert_eq!(
+            r! { format(sort(as.Date(c("2024-03-01", "2024-01-01")))) },
+            r! { c("2024-01-01", "2024-03-01") }
+        );
+        assert_eq!(
+            r! { x <- c(2, 1); names(x) <- c("b", "a"); names(sort(x)) },
+            r! { c("a", "b") }
+        );
+    }
+
+    #[test]
+    fn orders_use_multiple_keys() {
+        assert_eq!(r! { order(c(2, 1, NA, 1)) }, r! { c(2L, 4L, 1L, 3L) });
+        assert_eq!(
+            r! { order(c("b", "a", "b", "a"), c(1, 2, 0, 1)) },
+            r! { c(4L, 2L, 3L, 1L) }
+        );
+        assert_eq!(
+            r! { order(c(1, 3, 2), decreasing = TRUE) },
+            r! { c(2L, 3L, 1L) }
+        );
+        assert_eq!(r! { order(c(2, NA, 1), na.last = NA) }, r! { c(3L, 1L) });
+    }
+
+    #[test]
+    fn ranks_break_ties() {
+        assert_eq!(r! { rank(c(10, 20, 10, 30)) }, r! { c(1.5, 3, 1.5, 4) });
+        assert_eq!(
+            r! { rank(c(10, 20, 10, 30), ties.method = "min") },
+            r! { c(1L, 3L, 1L, 4L) }
+        );
+        assert_eq!(
+            r! { rank(c(10, 20, 10), ties.method = "first") },
+            r! { c(1L, 3L, 2L) }
+        );
+        assert_eq!(r! { rank(c(2, NA, 1)) }, r! { c(2, 3, 1) });
+        assert_eq!(
+            r! { rank(c(2, NA, 1), na.last = "keep") },
+            r! { c(2, NA, 1) }
+        );
+    }
+
+    #[test]
+    fn unique_and_duplicated() {
+        assert_eq!(r! { unique(c(1, 2, 1, NA, NA)) }, r! { c(1, 2, NA) });
+        assert_eq!(r! { unique(c("a", "b", "a")) }, r! { c("a", "b") });
+        assert_eq!(
+            r! { duplicated(c(1L, 2L, 1L, NA, NA)) },
+            r! { c(FALSE, FALSE, TRUE, FALSE, TRUE) }
+        );
+        assert_eq!(
+            r! { duplicated(c(1, 2, 1), fromLast = TRUE) },
+            r! { c(TRUE, FALSE, FALSE) }
+        );
+    }
+
+    #[test]
+    fn positions_are_found() {
+        assert_eq!(r! { which(c(FALSE, TRUE, NA, TRUE)) }, r! { c(2L, 4L) });
+        assert!(r! { which(1) }.is_err());
+        assert_eq!(r! { which.max(c(1, NA, 3, 3)) }, r! { 3L });
+        assert_eq!(r! { which.min(c(TRUE, FALSE)) }, r! { 2L });
+        assert_eq!(r! { which.max(c(NA, NA)) }, r! { which(FALSE) });
+        assert_eq!(
+            r! { tabulate(c(2, 3, 3, 5, NA, -1)) },
+            r! { c(0L, 1L, 2L, 0L, 1L) }
+        );
+        assert_eq!(r! { tabulate(c(1, 2, 9), nbins = 2) }, r! { c(1L, 1L) });
+    }
+}
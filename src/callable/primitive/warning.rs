@@ -0,0 +1,58 @@
+use r_derive::builtin;
+
+use crate::callable::core::*;
+use crate::context::Context;
+use crate::internal_err;
+use crate::lang::{CallStack, EvalResult};
+use crate::object::*;
+use crate::warning;
+
+#[derive(Debug, Clone, PartialEq)]
+#[builtin(sym = "warning")]
+pub struct PrimitiveWarning;
+impl Callable for PrimitiveWarning {
+    fn call(&self, args: ExprList, stack: &mut CallStack) -> EvalResult {
+        let Obj::List(args) = stack.eval_list_eager(args)? else {
+            return internal_err!();
+        };
+
+        // message is the concatenation of all arguments
+        let mut msg = String::new();
+        for (_, value) in args.values.borrow().iter() {
+            if let Obj::Vector(Vector::Character(v)) = value.clone().as_character()? {
+                for s in v.inner().borrow().iter() {
+                    msg.push_str(&s.to_string());
+                }
+            }
+        }
+
+        warning::warn(msg.clone())?;
+        Ok(Obj::Vector(Vector::from(vec![msg])))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::r;
+    use crate::warning;
+
+    #[test]
+    fn warnings_are_deferred_by_default() {
+        assert_eq!(r! { warning("a", 1); warning("b") }, r! { "b" });
+        assert_eq!(
+            warning::take_deferred(),
+            Some(String::from("Warning messages:\n1: a1\n2: b\n"))
+        );
+    }
+
+    #[test]
+    fn warnings_can_be_ignored() {
+        assert_eq!(r! { options(warn = -1); warning("a") }, r! { "a" });
+        assert_eq!(warning::take_deferred(), None);
+    }
+
+    #[test]
+    fn warnings_can_be_converted_to_errors() {
+        assert!(r! { options(warn = 2); warning("a") }.is_err());
+    }
+}
@@ -0,0 +1,383 @@
+/// Static Type Checking
+///
+/// Files are parsed, but never evaluated, and checked for obvious mismatches
+/// with the type annotations of functions. Only values whose type is evident
+/// without evaluation are checked: literals, annotated parameters, results of
+/// calls to functions with an annotated return type and variables assigned
+/// one of these. Everything else is assumed to satisfy its annotation.
+///
+/// Checking is run with `r check file.R`.
+///
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::callable::core::Builtin;
+use crate::callable::keywords::KeywordBlock;
+use crate::callable::operators::{InfixAssign, InfixEqAssign, InfixSuperAssign};
+use crate::err;
+use crate::lang::Signal;
+use crate::object::{Expr, ExprList, Signature};
+use crate::parser::{parse_file, Localization};
+
+/// A mismatch between a value and its annotated type
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{file}:{line}: {}", self.message),
+            (Some(file), None) => write!(f, "{file}: {}", self.message),
+            (None, Some(line)) => write!(f, "{line}: {}", self.message),
+            (None, None) => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Parse and check a file
+pub fn check_file(file: &str) -> Result<Vec<Issue>, Signal> {
+    let input = match std::fs::read_to_string(file) {
+        Ok(input) => input,
+        Err(e) => return err!(format!("cannot open file '{file}': {e}")),
+    };
+
+    let expr = parse_file(&Localization::En, file, &input)?;
+    Ok(check(&expr))
+}
+
+/// Check a parsed expression
+pub fn check(expr: &Expr) -> Vec<Issue> {
+    let mut checker = Checker::default();
+    checker.collect_functions(expr);
+    checker.check(expr);
+    checker.issues
+}
+
+/// The annotated type of a literal expression
+fn literal_type(expr: &Expr) -> Option<&'static str> {
+    match expr {
+        Expr::Number(_) | Expr::Inf => Some("numeric"),
+        Expr::Integer(_) => Some("integer"),
+        Expr::Bool(_) | Expr::NA => Some("logical"),
+        Expr::String(_) => Some("character"),
+        Expr::Null => Some("NULL"),
+        Expr::List(_) => Some("list"),
+        Expr::Function(..) => Some("function"),
+        _ => None,
+    }
+}
+
+/// Whether a value of a known type satisfies an annotation, mirroring
+/// `Obj::is_type`
+fn satisfies(found: &str, ty: &str) -> bool {
+    ty == "any" || found == ty || (ty == "numeric" && found == "integer")
+}
+
+fn is_builtin<T: Builtin + 'static>(p: &dyn Builtin) -> bool {
+    p.as_any().downcast_ref::<T>().is_some()
+}
+
+fn is_assignment(p: &dyn Builtin) -> bool {
+    is_builtin::<InfixAssign>(p)
+        || is_builtin::<InfixSuperAssign>(p)
+        || is_builtin::<InfixEqAssign>(p)
+}
+
+#[derive(Default)]
+struct Checker {
+    file: Option<String>,
+    line: Option<usize>,
+    functions: HashMap<String, (ExprList, Signature)>,
+    vars: HashMap<String, String>,
+    issues: Vec<Issue>,
+}
+
+impl Checker {
+    fn report(&mut self, message: String) {
+        self.issues.push(Issue {
+            file: self.file.clone(),
+            line: self.line,
+            message,
+        })
+    }
+
+    /// Gather functions with annotations which are assigned to a name
+    fn collect_functions(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Call(what, args) => {
+                if let Expr::Primitive(p) = &**what {
+                    if let (true, [Expr::Symbol(name), Expr::Function(formals, _, sig)]) =
+                        (is_assignment(p.as_ref()), args.values.as_slice())
+                    {
+                        if !sig.is_empty() {
+                            let f = (formals.clone(), *sig.clone());
+                            self.functions.insert(name.clone(), f);
+                        }
+                    }
+                }
+
+                self.collect_functions(what);
+                args.values.iter().for_each(|v| self.collect_functions(v));
+            }
+            Expr::Function(formals, body, _) => {
+                formals
+                    .values
+                    .iter()
+                    .for_each(|v| self.collect_functions(v));
+                self.collect_functions(body);
+            }
+            Expr::List(args) => args.values.iter().for_each(|v| self.collect_functions(v)),
+            _ => (),
+        }
+    }
+
+    /// The type of an expression, where it is known without evaluation
+    fn type_of(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Symbol(name) => self.vars.get(name).cloned(),
+            Expr::Call(what, _) => match &**what {
+                Expr::Symbol(name) | Expr::String(name) => {
+                    let (_, sig) = self.functions.get(name)?;
+                    sig.returns.clone()
+                }
+                _ => None,
+            },
+            expr => literal_type(expr).map(String::from),
+        }
+    }
+
+    fn check(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Call(what, args) => match &**what {
+                Expr::Primitive(p) => {
+                    if let Some(block) = p.as_any().downcast_ref::<KeywordBlock>() {
+                        self.check_block(block, args);
+                    } else {
+                        self.check_primitive_call(p.as_ref(), args)
+                    }
+                }
+                Expr::Symbol(name) | Expr::String(name) => {
+                    args.values.iter().for_each(|v| self.check(v));
+                    self.check_call(name, args);
+                }
+                what => {
+                    self.check(what);
+                    args.values.iter().for_each(|v| self.check(v));
+                }
+            },
+            Expr::Function(formals, body, sig) => self.check_function(None, formals, body, sig),
+            Expr::List(args) => args.values.iter().for_each(|v| self.check(v)),
+            _ => (),
+        }
+    }
+
+    fn check_block(&mut self, block: &KeywordBlock, args: &ExprList) {
+        let outer = (self.file.clone(), self.line);
+        for (i, expr) in args.values.iter().enumerate() {
+            if let Some(file) = &block.srcref.file {
+                self.file = Some(file.clone());
+            }
+
+            self.line = block.srcref.lines.get(i).copied().or(outer.1);
+            self.check(expr);
+        }
+
+        (self.file, self.line) = outer;
+    }
+
+    fn check_primitive_call(&mut self, p: &dyn Builtin, args: &ExprList) {
+        if !is_assignment(p) {
+            return args.values.iter().for_each(|v| self.check(v));
+        }
+
+        let [target, value] = args.values.as_slice() else {
+            return args.values.iter().for_each(|v| self.check(v));
+        };
+
+        match (target, value) {
+            (Expr::Symbol(name), Expr::Function(formals, body, sig)) => {
+                self.check_function(Some(name), formals, body, sig);
+            }
+            _ => self.check(value),
+        }
+
+        // variables are assumed to hold the type of their latest assignment
+        if let Expr::Symbol(name) = target {
+            match self.type_of(value) {
+                Some(ty) => self.vars.insert(name.clone(), ty),
+                None => self.vars.remove(name),
+            };
+        }
+    }
+
+    fn check_function(
+        &mut self,
+        name: Option<&String>,
+        formals: &ExprList,
+        body: &Expr,
+        sig: &Signature,
+    ) {
+        let label = match name {
+            Some(name) => format!(" of '{name}'"),
+            None => String::new(),
+        };
+
+        for (param, default) in formals.keys.iter().zip(formals.values.iter()) {
+            let Some(param) = param else { continue };
+            self.check(default);
+
+            let Some(ty) = sig.param(param) else { continue };
+            if let Some(found) = self.type_of(default) {
+                if !satisfies(&found, ty) {
+                    self.report(format!(
+                        "default of parameter '{param}'{label} must be {ty}, not {found}"
+                    ));
+                }
+            }
+        }
+
+        // within the body, parameters hold their annotated types
+        let outer = self.vars.clone();
+        for param in formals.keys.iter().flatten() {
+            match sig.param(param) {
+                Some(ty) => self.vars.insert(param.clone(), ty.to_string()),
+                None => self.vars.remove(param),
+            };
+        }
+
+        self.check(body);
+
+        if let Some(ty) = &sig.returns {
+            let last = match body {
+                Expr::Call(what, args) => match &**what {
+                    Expr::Primitive(p) if is_builtin::<KeywordBlock>(p.as_ref()) => {
+                        args.values.last().unwrap_or(&Expr::Null)
+                    }
+                    _ => body,
+                },
+                _ => body,
+            };
+
+            if let Some(found) = self.type_of(last) {
+                if !satisfies(&found, ty) {
+                    self.report(format!("return value{label} must be {ty}, not {found}"));
+                }
+            }
+        }
+
+        self.vars = outer;
+    }
+
+    /// Match arguments to an annotated function's parameters, first by name
+    /// and then by position
+    fn check_call(&mut self, name: &str, args: &ExprList) {
+        let Some((formals, sig)) = self.functions.get(name).cloned() else {
+            return;
+        };
+
+        let mut params: Vec<String> = formals.keys.iter().flatten().cloned().collect();
+        let ellipsis = formals.position_ellipsis().unwrap_or(formals.len());
+        params.truncate(ellipsis);
+
+        let mut matched = vec![];
+        let mut positional = vec![];
+        for (key, value) in args.keys.iter().zip(args.values.iter()) {
+            match key {
+                Some(key) if params.contains(key) => {
+                    params.retain(|p| p != key);
+                    matched.push((key.clone(), value));
+                }
+                Some(_) => (),
+                None => positional.push(value),
+            }
+        }
+
+        matched.extend(params.into_iter().zip(positional));
+
+        for (param, value) in matched {
+            let Some(ty) = sig.param(&param) else {
+                continue;
+            };
+            if let Some(found) = self.type_of(value) {
+                if !satisfies(&found, ty) {
+                    self.report(format!(
+                        "argument '{param}' of '{name}' must be {ty}, not {found}"
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::LocalizedParser;
+
+    fn messages(input: &str) -> Vec<String> {
+        let expr = Localization::En.parse_input(input).unwrap();
+        check(&expr).into_iter().map(|i| i.to_string()).collect()
+    }
+
+    #[test]
+    fn literal_arguments_are_checked() {
+        let issues = messages(
+            "f <- function(x: numeric, n: integer = 1L) -> character paste(x, n)\n\
+             f(1, 2L)\n\
+             f(1L)\n\
+             f('a', n = 2)\n",
+        );
+
+        assert_eq!(
+            issues,
+            vec![
+                "4: argument 'n' of 'f' must be integer, not numeric",
+                "4: argument 'x' of 'f' must be numeric, not character",
+            ]
+        );
+    }
+
+    #[test]
+    fn defaults_and_returns_are_checked() {
+        let issues = messages(
+            "f <- function(x: logical = 'yes') -> numeric {\n\
+               TRUE\n\
+             }\n",
+        );
+
+        assert_eq!(
+            issues,
+            vec![
+                "default of parameter 'x' of 'f' must be logical, not character",
+                "return value of 'f' must be numeric, not logical",
+            ]
+        );
+    }
+
+    #[test]
+    fn annotated_values_are_checked() {
+        let issues = messages(
+            "f <- function(x: character) -> character x\n\
+             g <- function(y: numeric) y\n\
+             h <- function(z: character) g(z)\n\
+             s <- f('a')\n\
+             g(s)\n\
+             g(f('b'))\n\
+             s <- unknown()\n\
+             g(s)\n",
+        );
+
+        assert_eq!(
+            issues,
+            vec![
+                "3: argument 'y' of 'g' must be numeric, not character",
+                "5: argument 'y' of 'g' must be numeric, not character",
+                "6: argument 'y' of 'g' must be numeric, not character",
+            ]
+        );
+    }
+}
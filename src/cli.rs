@@ -12,4 +12,32 @@ pub struct Cli {
     /// Show the extended warranty information at startup
     #[cfg_attr(not(feature = "wasm"), arg(long))]
     pub warranty: bool,
+
+    /// Profile evaluation, writing collapsed call stacks to the given file
+    #[cfg_attr(not(feature = "wasm"), arg(long, value_name = "FILE"))]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub profile: Option<String>,
+
+    /// Save the workspace to .RData when the session ends
+    #[cfg_attr(not(feature = "wasm"), arg(long))]
+    pub save: bool,
+
+    /// Do not restore the workspace from .RData when the session starts
+    #[cfg_attr(not(feature = "wasm"), arg(long))]
+    pub no_restore: bool,
+
+    /// Run a command instead of the REPL
+    #[cfg_attr(not(feature = "wasm"), command(subcommand))]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub command: Option<Command>,
+}
+
+#[cfg_attr(not(feature = "wasm"), derive(clap::Subcommand))]
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Check a file for values which do not match their annotated types
+    Check {
+        /// File to check
+        file: String,
+    },
 }
@@ -0,0 +1,345 @@
+/// Connections
+///
+/// A connection is a source or destination of text, such as a file, a
+/// character vector or the standard streams. An open connection holds a Rust
+/// reader or writer from which builtins stream data a line at a time.
+/// Connections are referred to by external objects (see [`crate::handle`]),
+/// and are closed as the last such object is dropped, except for the
+/// standard streams which are always available.
+///
+/// A connection which is not open is opened for the duration of each
+/// operation, such that reading always starts from the beginning. Once
+/// opened using `open()`, successive reads continue where the previous read
+/// ended until the connection is closed. The standard streams are always
+/// open.
+///
+use std::cell::RefCell;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Cursor, Write};
+
+use crate::err;
+use crate::error::Error;
+use crate::handle::{Registry, Resource};
+use crate::lang::Signal;
+
+pub const STDIN: usize = 0;
+pub const STDOUT: usize = 1;
+pub const STDERR: usize = 2;
+
+/// The mode a connection is opened with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Read,
+    Write,
+    Append,
+}
+
+impl TryFrom<&str> for Mode {
+    type Error = Signal;
+    fn try_from(mode: &str) -> Result<Self, Self::Error> {
+        // text and binary modes are read alike
+        match mode.trim_end_matches(['t', 'b']) {
+            "r" => Ok(Mode::Read),
+            "w" => Ok(Mode::Write),
+            "a" => Ok(Mode::Append),
+            _ => err!(format!("invalid connection mode '{mode}'")),
+        }
+    }
+}
+
+enum Source {
+    File(String),
+    Text(Vec<String>),
+    Stdin,
+    Stdout,
+    Stderr,
+}
+
+enum Stream {
+    Reader(Box<dyn BufRead>),
+    Writer(Box<dyn Write>),
+}
+
+struct State {
+    source: Source,
+    stream: Option<Stream>,
+}
+
+impl State {
+    fn new(source: Source) -> Self {
+        State {
+            source,
+            stream: None,
+        }
+    }
+
+    fn is_standard(&self) -> bool {
+        matches!(self.source, Source::Stdin | Source::Stdout | Source::Stderr)
+    }
+
+    fn open(&self, mode: Mode) -> Result<Stream, Signal> {
+        let stream = match (&self.source, mode) {
+            (Source::File(path), Mode::Read) => match File::open(path) {
+                Ok(file) => Stream::Reader(Box::new(BufReader::new(file))),
+                Err(e) => return err!(format!("cannot open file '{path}': {e}")),
+            },
+            (Source::File(path), mode) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .append(mode == Mode::Append)
+                    .truncate(mode == Mode::Write)
+                    .open(path);
+
+                match file {
+                    Ok(file) => Stream::Writer(Box::new(file)),
+                    Err(e) => return err!(format!("cannot open file '{path}': {e}")),
+                }
+            }
+            (Source::Text(lines), Mode::Read) => {
+                let text = lines.iter().map(|l| format!("{l}\n")).collect::<String>();
+                Stream::Reader(Box::new(Cursor::new(text.into_bytes())))
+            }
+            (Source::Stdin, Mode::Read) => Stream::Reader(Box::new(BufReader::new(io::stdin()))),
+            (Source::Stdout, Mode::Write | Mode::Append) => Stream::Writer(Box::new(io::stdout())),
+            (Source::Stderr, Mode::Write | Mode::Append) => Stream::Writer(Box::new(io::stderr())),
+            _ => return err!("connection cannot be opened in this mode"),
+        };
+
+        Ok(stream)
+    }
+}
+
+thread_local! {
+    static CONNECTIONS: RefCell<Registry<State>> = RefCell::new(
+        Registry::new(STDERR + 1)
+            .with_permanent(STDIN, State::new(Source::Stdin))
+            .with_permanent(STDOUT, State::new(Source::Stdout))
+            .with_permanent(STDERR, State::new(Source::Stderr)),
+    );
+}
+
+fn invalid() -> Signal {
+    Error::Other("invalid connection".to_string()).into()
+}
+
+/// A connection, closed as the last object referring to it is dropped
+/// unless it is a standard stream
+#[derive(Debug)]
+pub struct Connection {
+    id: usize,
+}
+
+impl Connection {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+}
+
+impl Resource for Connection {
+    fn class(&self) -> Vec<String> {
+        let class = summary(self.id).map_or("file", |(class, ..)| class);
+        vec![String::from(class), String::from("connection")]
+    }
+}
+
+impl fmt::Display for Connection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((class, description, open)) = summary(self.id) else {
+            return writeln!(f, "<invalid connection>");
+        };
+
+        let opened = if open { "opened" } else { "closed" };
+        writeln!(
+            f,
+            "A connection with\ndescription \"{description}\"\nclass       \"{class}\"\nopened      \"{opened}\""
+        )
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        if self.id <= STDERR {
+            return;
+        }
+
+        // the registry is gone once the thread has finished
+        let _ = CONNECTIONS.try_with(|c| {
+            let state = c.borrow_mut().remove(self.id);
+            drop(state)
+        });
+    }
+}
+
+fn add(state: State) -> Connection {
+    let id = CONNECTIONS.with(|c| c.borrow_mut().add(state));
+    Connection { id }
+}
+
+/// One of the standard streams, by its id
+pub fn standard(id: usize) -> Connection {
+    debug_assert!(id <= STDERR);
+    Connection { id }
+}
+
+/// Create a connection to a file
+pub fn file(path: &str) -> Connection {
+    add(State::new(Source::File(path.to_string())))
+}
+
+/// Create a connection reading lines of text
+pub fn text(lines: Vec<String>) -> Connection {
+    add(State::new(Source::Text(lines)))
+}
+
+/// Open a connection
+pub fn open(id: usize, mode: Mode) -> Result<(), Signal> {
+    CONNECTIONS.with(|c| {
+        let mut connections = c.borrow_mut();
+        let connection = connections.get_mut(id).ok_or_else(invalid)?;
+        if connection.stream.is_some() {
+            return err!("connection is already open");
+        }
+
+        connection.stream = Some(connection.open(mode)?);
+        Ok(())
+    })
+}
+
+/// Close a connection, after which it can no longer be used
+pub fn close(id: usize) -> Result<(), Signal> {
+    CONNECTIONS.with(|c| {
+        let mut connections = c.borrow_mut();
+        match connections.get(id) {
+            Some(connection) if connection.is_standard() => {
+                err!("cannot close standard connections")
+            }
+            Some(_) => {
+                // writers are flushed as they are dropped
+                connections.remove(id);
+                Ok(())
+            }
+            None => Err(invalid()),
+        }
+    })
+}
+
+/// A description of a connection, as its class, description and whether it
+/// is open
+pub fn summary(id: usize) -> Option<(&'static str, String, bool)> {
+    CONNECTIONS.with(|c| {
+        let connections = c.borrow();
+        let connection = connections.get(id)?;
+        let open = connection.is_standard() || connection.stream.is_some();
+        let (class, description) = match &connection.source {
+            Source::File(path) => ("file", path.clone()),
+            Source::Text(_) => ("textConnection", "text".to_string()),
+            Source::Stdin => ("terminal", "stdin".to_string()),
+            Source::Stdout => ("terminal", "stdout".to_string()),
+            Source::Stderr => ("terminal", "stderr".to_string()),
+        };
+
+        Some((class, description, open))
+    })
+}
+
+/// Take the stream of a connection, opening it if it is not already open
+///
+/// Returns whether the stream was opened for this use only, in which case
+/// it is closed rather than returned afterwards.
+fn take(id: usize, mode: Mode) -> Result<(Stream, bool), Signal> {
+    CONNECTIONS.with(|c| {
+        let mut connections = c.borrow_mut();
+        let connection = connections.get_mut(id).ok_or_else(invalid)?;
+        match connection.stream.take() {
+            Some(stream) => Ok((stream, false)),
+            None => Ok((connection.open(mode)?, !connection.is_standard())),
+        }
+    })
+}
+
+fn restore(id: usize, stream: Stream, temporary: bool) {
+    CONNECTIONS.with(|c| {
+        if let Some(connection) = c.borrow_mut().get_mut(id) {
+            if !temporary {
+                connection.stream = Some(stream)
+            }
+        }
+    })
+}
+
+/// Read from a connection
+pub fn with_reader<T, F>(id: usize, f: F) -> Result<T, Signal>
+where
+    F: FnOnce(&mut dyn BufRead) -> Result<T, Signal>,
+{
+    let (mut stream, temporary) = take(id, Mode::Read)?;
+    let result = match &mut stream {
+        Stream::Reader(reader) => f(reader.as_mut()),
+        Stream::Writer(_) => err!("cannot read from this connection"),
+    };
+
+    restore(id, stream, temporary);
+    result
+}
+
+/// Write to a connection, opened with the given mode if not already open
+pub fn with_writer<T, F>(id: usize, mode: Mode, f: F) -> Result<T, Signal>
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<T>,
+{
+    let (mut stream, temporary) = take(id, mode)?;
+    let result = match &mut stream {
+        Stream::Writer(writer) => f(writer.as_mut())
+            .and_then(|value| writer.flush().map(|_| value))
+            .or_else(|e| err!(format!("cannot write to connection: {e}"))),
+        Stream::Reader(_) => err!("cannot write to this connection"),
+    };
+
+    restore(id, stream, temporary);
+    result
+}
+
+/// Read a line from a reader, without its line ending
+pub fn read_line(reader: &mut dyn BufRead) -> Result<Option<String>, Signal> {
+    let mut line = String::new();
+    match reader.read_line(&mut line) {
+        Ok(0) => Ok(None),
+        Ok(_) => {
+            let trimmed = line.trim_end_matches(['\n', '\r']).len();
+            line.truncate(trimmed);
+            Ok(Some(line))
+        }
+        Err(e) => err!(format!("cannot read from connection: {e}")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CONNECTIONS;
+    use crate::{r, r_expect};
+
+    #[test]
+    fn connections_are_closed_with_their_last_reference() {
+        let con = r! { textConnection("x") }.expect("connection is created");
+        let copy = con.clone();
+        drop(con);
+        assert_eq!(CONNECTIONS.with(|c| c.borrow().len()), 4);
+
+        drop(copy);
+        assert_eq!(CONNECTIONS.with(|c| c.borrow().len()), 3);
+    }
+
+    #[test]
+    fn unreachable_connections_are_freed() {
+        r_expect! {{r#"
+            con <- textConnection("kept")
+            for (i in 1:10) textConnection("freed")
+            readLines(con) == "kept"
+        "#}}
+
+        // at most the standard streams and the kept connection remain
+        assert!(CONNECTIONS.with(|c| c.borrow().len()) <= 4);
+    }
+}
@@ -76,7 +76,7 @@ pub trait Context: std::fmt::Debug + std::fmt::Display {
                         Ok(c @ Obj::Closure(..)) => Ok(vec![(k, c)].into_iter()),
                         _ => Ok(vec![(k, Obj::Closure(Expr::Symbol(s), self.env()))].into_iter()),
                     },
-                    (k, c @ Expr::Call(..)) => {
+                    (k, c @ (Expr::Call(..) | Expr::Missing)) => {
                         let elem = vec![(k, Obj::Closure(c, self.env()))];
                         Ok(elem.into_iter())
                     }
@@ -0,0 +1,300 @@
+/// Interactive Debugging
+///
+/// Evaluation can be paused by calling `browser()`, by flagging a function
+/// using `debug()` or `debugonce()`, or by setting a breakpoint on a line of
+/// a sourced file using `setBreakpoint()`. While paused, a nested prompt
+/// reads commands and evaluates any other input in the paused frame's
+/// environment.
+///
+/// Debugging state is kept per thread, outliving any single evaluation so
+/// that flags and breakpoints persist throughout a session.
+///
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::callable::primitive::autoprint;
+use crate::context::Context;
+use crate::lang::{CallStack, Cond, Signal};
+use crate::object::{Environment, Expr, Obj};
+use crate::options;
+use crate::parser::{LocalizedParser, Srcref};
+
+/// A source of browser input, producing a line of input given a prompt
+///
+/// Returning `None` signals that no more input is available, which resumes
+/// evaluation.
+pub type BrowserInput = Box<dyn FnMut(&str) -> Option<String>>;
+
+#[derive(Debug, Clone, Default)]
+enum Stepping {
+    #[default]
+    Continue,
+    // pause before the next expression evaluated in the environment
+    Next(Rc<Environment>),
+    // as with Next, but also pause upon entering any function call
+    Step(Rc<Environment>),
+}
+
+#[derive(Default)]
+struct Debugger {
+    // functions flagged for debugging, and whether only for their next call
+    flagged: Vec<(Obj, bool)>,
+    breakpoints: Vec<(String, usize)>,
+    stepping: Stepping,
+    input: Option<BrowserInput>,
+    depth: usize,
+}
+
+thread_local! {
+    static DEBUGGER: RefCell<Debugger> = RefCell::new(Debugger::default());
+}
+
+/// Debugging state to restore after a debugged call returns
+pub struct DebugCall {
+    call: Expr,
+    stepping: Stepping,
+}
+
+/// Flag a function to enter the browser when called
+pub fn debug(f: Obj, once: bool) {
+    DEBUGGER.with(|d| {
+        let mut d = d.borrow_mut();
+        d.flagged.retain(|(g, _)| g != &f);
+        d.flagged.push((f, once));
+    })
+}
+
+/// Remove a function's debugging flag, returning whether it was flagged
+pub fn undebug(f: &Obj) -> bool {
+    DEBUGGER.with(|d| {
+        let mut d = d.borrow_mut();
+        let n = d.flagged.len();
+        d.flagged.retain(|(g, _)| g != f);
+        d.flagged.len() != n
+    })
+}
+
+pub fn is_debugged(f: &Obj) -> bool {
+    DEBUGGER.with(|d| d.borrow().flagged.iter().any(|(g, _)| g == f))
+}
+
+/// Add (or clear) a breakpoint at a line of a sourced file
+///
+/// Files are matched by their trailing path components, such that a
+/// breakpoint on `"script.R"` applies to a block sourced from
+/// `"path/to/script.R"`.
+pub fn set_breakpoint(file: &str, line: usize, clear: bool) {
+    DEBUGGER.with(|d| {
+        let mut d = d.borrow_mut();
+        d.breakpoints.retain(|(f, l)| !(f == file && *l == line));
+        if !clear {
+            d.breakpoints.push((file.to_string(), line));
+        }
+    })
+}
+
+/// Replace the source of browser input
+///
+/// By default, input is read from a nested prompt when the `repl` feature
+/// is enabled.
+pub fn set_input(input: Option<BrowserInput>) {
+    DEBUGGER.with(|d| d.borrow_mut().input = input)
+}
+
+/// Stop any stepping left over from a previous evaluation
+pub fn reset() {
+    set_stepping(Stepping::Continue)
+}
+
+fn set_stepping(stepping: Stepping) {
+    DEBUGGER.with(|d| d.borrow_mut().stepping = stepping)
+}
+
+/// Enter the browser if a called function is flagged for debugging
+///
+/// Called once a function's arguments have been matched in its new frame.
+/// When debugging, returns the state to be restored with `exit_call` after
+/// the call has been evaluated.
+pub fn enter_call(f: &Obj, stack: &mut CallStack) -> Result<Option<DebugCall>, Signal> {
+    let stepping = DEBUGGER.with(|d| {
+        let mut d = d.borrow_mut();
+        let flagged = match d.flagged.iter().position(|(g, _)| g == f) {
+            Some(i) if d.flagged[i].1 => {
+                d.flagged.remove(i);
+                true
+            }
+            Some(_) => true,
+            None => false,
+        };
+
+        let stepping_in = matches!(d.stepping, Stepping::Step(_));
+        (flagged || stepping_in).then(|| d.stepping.clone())
+    });
+
+    let Some(stepping) = stepping else {
+        return Ok(None);
+    };
+
+    let call = stack.last_frame().call;
+    println!("debugging in: {call}");
+
+    if let Obj::Function(_, body, ..) = f {
+        println!("debug: {body}");
+    }
+
+    browse(stack)?;
+    Ok(Some(DebugCall { call, stepping }))
+}
+
+/// Restore the debugging state from before a debugged call
+pub fn exit_call(call: DebugCall) {
+    println!("exiting from: {}", call.call);
+    set_stepping(call.stepping)
+}
+
+/// Enter the browser if stepping or at a breakpoint
+///
+/// Called before evaluating each expression of a block, with `i` indexing
+/// the expression within the block's source reference.
+pub fn at_expr(
+    expr: &Expr,
+    srcref: &Srcref,
+    i: usize,
+    stack: &mut CallStack,
+) -> Result<(), Signal> {
+    let line = srcref.lines.get(i).copied();
+    let pause = DEBUGGER.with(|d| {
+        let d = d.borrow();
+
+        let stepping = match &d.stepping {
+            Stepping::Next(env) | Stepping::Step(env) => Rc::ptr_eq(env, &stack.env()),
+            Stepping::Continue => false,
+        };
+
+        let at_breakpoint = match (&srcref.file, line) {
+            (Some(file), Some(line)) => d
+                .breakpoints
+                .iter()
+                .any(|(f, l)| *l == line && Path::new(file).ends_with(f)),
+            _ => false,
+        };
+
+        stepping || at_breakpoint
+    });
+
+    if !pause {
+        return Ok(());
+    }
+
+    match (&srcref.file, line) {
+        (Some(file), Some(line)) => println!("debug at {file}#{line}: {expr}"),
+        (None, Some(line)) => println!("debug at #{line}: {expr}"),
+        _ => println!("debug: {expr}"),
+    }
+
+    browse(stack)
+}
+
+/// Pause evaluation, reading browser commands until evaluation resumes
+///
+/// Accepts commands
+///
+/// * `n` (or an empty line) to evaluate the next expression
+/// * `s` to evaluate the next expression, stepping into function calls
+/// * `c` to continue evaluation
+/// * `Q` to quit the browser, aborting the current evaluation
+/// * `where` to print the call stack
+///
+/// Any other input is evaluated in the environment of the current frame.
+pub fn browse(stack: &mut CallStack) -> Result<(), Signal> {
+    let depth = DEBUGGER.with(|d| {
+        let mut d = d.borrow_mut();
+        d.depth += 1;
+        d.depth
+    });
+
+    let result = browse_at(stack, depth);
+    DEBUGGER.with(|d| d.borrow_mut().depth -= 1);
+    result
+}
+
+fn browse_at(stack: &mut CallStack, depth: usize) -> Result<(), Signal> {
+    let prompt = format!("Browse[{depth}]> ");
+    let env = stack.env();
+
+    loop {
+        // resume evaluation once input is exhausted
+        let Some(line) = read_line(&prompt) else {
+            set_stepping(Stepping::Continue);
+            return Ok(());
+        };
+
+        let stepping = match line.trim() {
+            "" | "n" => Stepping::Next(env.clone()),
+            "s" => Stepping::Step(env.clone()),
+            "c" => Stepping::Continue,
+            "Q" => {
+                set_stepping(Stepping::Continue);
+                return Err(Signal::Condition(Cond::Abort));
+            }
+            "where" => {
+                print!("{stack}");
+                continue;
+            }
+            input => {
+                eval_input(input, stack)?;
+                continue;
+            }
+        };
+
+        set_stepping(stepping);
+        return Ok(());
+    }
+}
+
+fn eval_input(input: &str, stack: &mut CallStack) -> Result<(), Signal> {
+    // input is read using the current `locale` option
+    let expr = match options::locale().parse_input(input) {
+        Ok(expr) => expr,
+        Err(Signal::Thunk) => return Ok(()),
+        Err(e) => {
+            eprint!("{e}");
+            return Ok(());
+        }
+    };
+
+    let result = stack.eval_and_finalize(expr);
+    match result.and_then(|val| autoprint(val, stack)) {
+        Ok(()) => (),
+        // quitting a nested browser, or the session, propagates
+        Err(e @ Signal::Condition(Cond::Abort | Cond::Terminate)) => return Err(e),
+        Err(e) => print!("{e}"),
+    }
+
+    Ok(())
+}
+
+/// Read a line of input, as the browser reads commands
+pub fn read_line(prompt: &str) -> Option<String> {
+    // take input while reading so that it is not borrowed while in use
+    let input = DEBUGGER.with(|d| d.borrow_mut().input.take());
+
+    let Some(mut input) = input else {
+        return default_read_line(prompt);
+    };
+
+    let line = input(prompt);
+    DEBUGGER.with(|d| d.borrow_mut().input = Some(input));
+    line
+}
+
+#[cfg(feature = "repl")]
+fn default_read_line(prompt: &str) -> Option<String> {
+    crate::repl::read_browser_line(prompt)
+}
+
+#[cfg(not(feature = "repl"))]
+fn default_read_line(_prompt: &str) -> Option<String> {
+    None
+}
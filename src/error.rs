@@ -1,7 +1,4 @@
-use crate::{
-    lang::{CallStack, Signal},
-    parser::*,
-};
+use crate::{lang::Signal, parser::*};
 
 use core::fmt;
 use pest::error::LineColLocation::Pos;
@@ -26,6 +23,7 @@ macro_rules! err {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Error {
     VariableNotFound(String),
+    PackageNotFound(String),
     IncorrectContext(String),
     NotInterpretableAsLogical,
     ConditionIsNotScalar,
@@ -38,6 +36,8 @@ pub enum Error {
     Missing,
     ArgumentMissing(String),
     ArgumentInvalid(String),
+    ArgumentTypeMismatch(String, String, String),
+    ReturnTypeMismatch(String, String),
     Other(String),
 
     // parsing errors
@@ -45,9 +45,6 @@ pub enum Error {
     ParseFailure(Box<pest::error::Error<en::Rule>>),
     ParseUnexpected(en::Rule),
 
-    // temporary workaround until we propagate call stack to all error locations
-    WithCallStack(Box<Error>, CallStack),
-
     // in-dev errors
     Unimplemented(Option<String>),
     Internal(Option<String>, &'static str, u32),
@@ -61,6 +58,7 @@ impl Error {
         match self {
             Error::IncorrectContext(x) => format!("'{}' used in an incorrect context", x),
             Error::VariableNotFound(v) => format!("object '{}' not found", v.as_str()),
+            Error::PackageNotFound(p) => format!("there is no package called '{p}'"),
             Error::ParseFailureVerbose(e) => format!("{}", e),
             Error::ParseFailure(e) => match e.line_col {
                 Pos((line, col)) => format!("Parse failed at Line {}, Column {}", line, col),
@@ -89,9 +87,14 @@ impl Error {
                 format!("object cannot be coerced to type '{to}'")
             }
             Error::Other(s) => s.to_string(),
-            Error::WithCallStack(e, c) => format!("{}\n{c}", e.as_str()),
             Error::ArgumentMissing(s) => format!("argument '{s}' is missing with no default"),
             Error::ArgumentInvalid(s) => format!("argument '{s}' is invalid"),
+            Error::ArgumentTypeMismatch(s, ty, found) => {
+                format!("argument '{s}' must be {ty}, not {found}")
+            }
+            Error::ReturnTypeMismatch(ty, found) => {
+                format!("return value must be {ty}, not {found}")
+            }
             Error::Unimplemented(Some(s)) => {
                 format!("Uh, oh! Looks like '{s}' is only partially implemented")
             }
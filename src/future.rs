@@ -0,0 +1,236 @@
+/// Futures
+///
+/// A future evaluates an expression on a background thread, in an
+/// interpreter of its own holding a snapshot of the environment the future
+/// was created in. Futures are referred to by external objects (see
+/// [`crate::handle`]), and a future's value is kept once retrieved for as
+/// long as any such object remains. Futures which can no longer be reached
+/// are cancelled as they are freed.
+///
+/// Cancellation is cooperative. The interpreter evaluating a cancelled
+/// future raises an error at its next call, and its value can no longer be
+/// retrieved.
+///
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::context::Context;
+use crate::error::Error;
+use crate::handle::{Registry, Resource};
+use crate::internal_err;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::{Environment, Expr, Obj, Snapshot};
+
+/// Stack size of worker threads, which evaluate recursively like the main
+/// thread and need the same headroom
+pub const WORKER_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// The cancellation flag of a running future, set as it is dropped such
+/// that futures which are freed stop running
+struct Cancel(Arc<AtomicBool>);
+
+impl Drop for Cancel {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+enum State {
+    Running(JoinHandle<Result<Snapshot, Error>>, Cancel),
+    Resolved(Result<Obj, Error>),
+    Cancelled,
+}
+
+thread_local! {
+    static FUTURES: RefCell<Registry<State>> = RefCell::new(Registry::new(1));
+
+    // flag set when the future evaluated by this thread is cancelled
+    static CANCELLED: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// A future, cancelled and freed as the last object referring to it is
+/// dropped
+#[derive(Debug)]
+pub struct Future {
+    id: usize,
+    expr: Expr,
+}
+
+impl Future {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+}
+
+impl Resource for Future {
+    fn class(&self) -> Vec<String> {
+        vec![String::from("future")]
+    }
+}
+
+impl fmt::Display for Future {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<future ({})> {}", status(self.id), self.expr)
+    }
+}
+
+impl Drop for Future {
+    fn drop(&mut self) {
+        // the registry is gone once the thread has finished
+        let _ = FUTURES.try_with(|f| {
+            let state = f.borrow_mut().remove(self.id);
+            drop(state)
+        });
+    }
+}
+
+fn invalid(id: usize) -> Signal {
+    Error::Other(format!("future {id} does not exist")).into()
+}
+
+fn cancelled() -> Error {
+    Error::Other("future was cancelled".to_string())
+}
+
+/// Evaluate an expression in the environment captured by a snapshot
+fn evaluate(expr: Expr, env: Snapshot, cancelled: Arc<AtomicBool>) -> Result<Snapshot, Error> {
+    CANCELLED.with(|c| c.replace(Some(cancelled)));
+
+    let Obj::Environment(env) = env.restore(Environment::from_builtins()) else {
+        return Err(Error::Other("malformed future environment".to_string()));
+    };
+
+    let value = match CallStack::from(env).eval_and_finalize(expr) {
+        Ok(value) => value,
+        Err(Signal::Return(value, _)) => *value,
+        Err(Signal::Error(e)) => return Err(e),
+        Err(_) => return Err(Error::Other("unexpected signal in future".to_string())),
+    };
+
+    Ok(Snapshot::from(&value))
+}
+
+/// Start evaluating an expression on a background thread
+pub fn spawn(expr: Expr, env: Rc<Environment>) -> Result<Future, Signal> {
+    let snapshot = Snapshot::from(&Obj::Environment(env));
+    let worker_expr = expr.clone();
+    let flag = Arc::new(AtomicBool::new(false));
+    let worker_flag = flag.clone();
+
+    let handle = thread::Builder::new()
+        .stack_size(WORKER_STACK_SIZE)
+        .spawn(move || evaluate(worker_expr, snapshot, worker_flag))
+        .map_err(|e| Error::Other(format!("cannot start future: {e}")))?;
+
+    let state = State::Running(handle, Cancel(flag));
+    let id = FUTURES.with(|f| f.borrow_mut().add(state));
+    Ok(Future { id, expr })
+}
+
+/// Test whether a future has finished evaluating, or was cancelled
+pub fn resolved(id: usize) -> Result<bool, Signal> {
+    FUTURES.with(|f| match f.borrow().get(id) {
+        Some(State::Running(handle, _)) => Ok(handle.is_finished()),
+        Some(_) => Ok(true),
+        None => Err(invalid(id)),
+    })
+}
+
+/// The value of a future, waiting for it to finish evaluating
+///
+/// Values are restored into the session of the given environment.
+pub fn value(id: usize, env: &Rc<Environment>) -> EvalResult {
+    FUTURES.with(|f| {
+        let mut futures = f.borrow_mut();
+        let state = futures.get_mut(id).ok_or_else(|| invalid(id))?;
+
+        if let State::Running(..) = state {
+            // the future is only flagged as cancelled once it has finished
+            let State::Running(handle, cancel) = std::mem::replace(state, State::Cancelled) else {
+                return internal_err!();
+            };
+
+            let result = handle
+                .join()
+                .unwrap_or_else(|_| Err(Error::Other("future panicked".to_string())));
+            drop(cancel);
+            *state = State::Resolved(result.map(|value| value.restore(env.base())));
+        }
+
+        match state {
+            State::Resolved(Ok(value)) => Ok(value.clone()),
+            State::Resolved(Err(e)) => Err(e.clone().into()),
+            State::Cancelled => Err(cancelled().into()),
+            State::Running(..) => internal_err!(),
+        }
+    })
+}
+
+/// Cancel a future, returning whether it was still running
+pub fn cancel(id: usize) -> Result<bool, Signal> {
+    FUTURES.with(|f| {
+        let mut futures = f.borrow_mut();
+        match futures.get_mut(id) {
+            // the running future's flag is set as it is dropped
+            Some(state @ State::Running(..)) => {
+                *state = State::Cancelled;
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+            None => Err(invalid(id)),
+        }
+    })
+}
+
+/// A description of the state of a future
+pub fn status(id: usize) -> &'static str {
+    FUTURES.with(|f| match f.borrow().get(id) {
+        Some(State::Running(handle, _)) if !handle.is_finished() => "running",
+        Some(State::Running(..)) | Some(State::Resolved(_)) => "resolved",
+        Some(State::Cancelled) => "cancelled",
+        None => "invalid",
+    })
+}
+
+/// Raise an error if the future evaluated by this thread was cancelled
+pub fn check_cancelled() -> Result<(), Signal> {
+    CANCELLED.with(|c| match &*c.borrow() {
+        Some(flag) if flag.load(Ordering::Relaxed) => Err(cancelled().into()),
+        _ => Ok(()),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::FUTURES;
+    use crate::{r, r_expect};
+
+    #[test]
+    fn futures_are_freed_with_their_last_reference() {
+        let future = r! { future(1) }.expect("future is created");
+        let copy = future.clone();
+        drop(future);
+        assert_eq!(FUTURES.with(|f| f.borrow().len()), 1);
+
+        drop(copy);
+        assert_eq!(FUTURES.with(|f| f.borrow().len()), 0);
+    }
+
+    #[test]
+    fn unreachable_futures_are_freed() {
+        r_expect! {{"
+            f <- future(1)
+            for (i in 1:10) future(i)
+            x %<-% 2
+            future(3)
+            value(f) == 1 && x == 2
+        "}}
+
+        // the kept future, the assigned future and the last one made
+        assert!(FUTURES.with(|f| f.borrow().len()) <= 3);
+    }
+}
@@ -0,0 +1,122 @@
+/// Handles
+///
+/// Some objects stand for resources held outside of the interpreter, such as
+/// the threads evaluating futures and open connections. These resources are
+/// kept in a registry and referred to by id from an external object, which
+/// can't be taken apart or forged from R.
+///
+/// Copies of an external object share the resource it refers to, which is
+/// freed as the last of them is dropped.
+///
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+/// A resource referred to by external objects
+///
+/// Resources are displayed when printing the objects referring to them, and
+/// are responsible for removing themselves from their registry as they are
+/// dropped.
+pub trait Resource: Any + fmt::Debug + fmt::Display {
+    /// The classes of objects referring to the resource
+    fn class(&self) -> Vec<String>;
+}
+
+/// A reference to a resource, shared by copies of an external object
+#[derive(Debug, Clone)]
+pub struct External(Rc<dyn Resource>);
+
+impl External {
+    pub fn new(resource: impl Resource) -> Self {
+        External(Rc::new(resource))
+    }
+
+    /// The resource referred to, if it is of the given type
+    pub fn downcast<T: Resource>(&self) -> Option<&T> {
+        (self.0.as_ref() as &dyn Any).downcast_ref()
+    }
+
+    pub fn class(&self) -> Vec<String> {
+        self.0.class()
+    }
+}
+
+impl PartialEq for External {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Display for External {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+pub struct Registry<T> {
+    entries: HashMap<usize, T>,
+    next_id: usize,
+}
+
+impl<T> Registry<T> {
+    /// A registry of resources whose ids start from `first_id`
+    pub fn new(first_id: usize) -> Self {
+        Registry {
+            entries: HashMap::new(),
+            next_id: first_id,
+        }
+    }
+
+    /// Add a resource with a fixed id, such as a standard stream
+    pub fn with_permanent(mut self, id: usize, value: T) -> Self {
+        self.entries.insert(id, value);
+        self
+    }
+
+    /// Register a resource, returning its id
+    pub fn add(&mut self, value: T) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.insert(id, value);
+        id
+    }
+
+    pub fn get(&self, id: usize) -> Option<&T> {
+        self.entries.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut T> {
+        self.entries.get_mut(&id)
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<T> {
+        self.entries.remove(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Registry;
+
+    #[test]
+    fn ids_are_not_reused() {
+        let mut registry = Registry::new(1).with_permanent(0, "permanent");
+        let first = registry.add("first");
+        registry.remove(first);
+
+        let second = registry.add("second");
+        assert_ne!(first, second);
+        assert_eq!(registry.get(first), None);
+        assert_eq!(registry.get(second), Some(&"second"));
+        assert_eq!(registry.get(0), Some(&"permanent"));
+    }
+}
@@ -1,9 +1,15 @@
-use crate::callable::core::{builtin, Callable};
+use crate::callable::core::{builtin, Builtin, Callable};
+use crate::callable::primitive::PrimitivePartial;
 use crate::context::Context;
+use crate::err;
 use crate::error::*;
+use crate::future;
 use crate::internal_err;
+use crate::object::attributes::Arith;
 use crate::object::types::*;
 use crate::object::*;
+use crate::profiler;
+use crate::time;
 
 use core::fmt;
 use std::fmt::Display;
@@ -15,6 +21,7 @@ pub type EvalResult = Result<Obj, Signal>;
 pub enum Cond {
     Break,
     Continue,
+    Abort, // return to the top level, as when quitting a browser
     Terminate,
 }
 
@@ -36,9 +43,9 @@ impl From<Cond> for EvalResult {
 pub enum Signal {
     Condition(Cond),
     Error(Error),
-    Return(Obj, bool), // (value, visibility)
-    Tail(Expr, bool),  // (value expr, visibility)
-    Thunk,             // used when evaluating null opts like comments
+    Return(Box<Obj>, bool), // (value, visibility)
+    Tail(Expr, bool),       // (value expr, visibility)
+    Thunk,                  // used when evaluating null opts like comments
 }
 
 impl Display for Signal {
@@ -56,13 +63,13 @@ impl Display for Signal {
 
 impl Obj {
     pub fn with_visibility(self, visibility: bool) -> EvalResult {
-        Signal::Return(self, visibility).into()
+        Signal::Return(Box::new(self), visibility).into()
     }
 
     pub fn force(self, stack: &mut CallStack) -> EvalResult {
         match self {
             // special case for symbols, which are treated as argument promises
-            Obj::Closure(Expr::Symbol(s), mut env) => match env.get(s.clone()) {
+            Obj::Closure(Expr::Symbol(s), env) => match stack.get_in(s.clone(), env) {
                 Err(Signal::Error(Error::Missing)) => Err(Error::ArgumentMissing(s).into()),
                 Ok(result) => result.force(stack),
                 other => other,
@@ -123,7 +130,11 @@ impl Obj {
 
     pub fn as_character(self) -> EvalResult {
         match self {
-            Obj::Vector(v) => Ok(Obj::Vector(v.as_character())),
+            // dates and date-times are formatted as strings
+            Obj::Vector(v) => match time::format(&v, None, None, false)? {
+                Some(strings) => Ok(Obj::Vector(Vector::from(strings))),
+                None => Ok(Obj::Vector(v.as_character())),
+            },
             Obj::Null => Ok(Obj::Vector(Vector::from(Vec::<Character>::new()))),
             _ => Error::CannotBeCoercedToCharacter.into(),
         }
@@ -167,8 +178,10 @@ impl Obj {
             Obj::List(_) => None,
             Obj::Expr(_) => None,
             Obj::Closure(_, _) => None,
-            Obj::Function(_, _, _) => None,
+            Obj::Function(..) => None,
             Obj::Environment(_) => None,
+            Obj::Formula(..) => None,
+            Obj::External(_) => None,
         }
     }
 
@@ -180,10 +193,7 @@ impl Obj {
                 .iter()
                 .find(|(k, _)| *k == Some(String::from(name)))
                 .map(|(_, v)| v.clone()),
-            Obj::Environment(e) => match e.get(String::from(name)) {
-                Ok(v) => Some(v),
-                Err(_) => None,
-            },
+            Obj::Environment(e) => e.get(String::from(name)).ok(),
             _ => None,
         }
     }
@@ -216,11 +226,54 @@ impl Obj {
 
     pub fn environment(&self) -> Option<Rc<Environment>> {
         match self {
-            Obj::Closure(_, e) | Obj::Function(_, _, e) | Obj::Environment(e) => Some(e.clone()),
+            Obj::Closure(_, e)
+            | Obj::Function(_, _, e, _)
+            | Obj::Environment(e)
+            | Obj::Formula(_, e) => Some(e.clone()),
             _ => None,
         }
     }
 
+    /// The classes of an object, with lists and vectors optionally carrying
+    /// their own `class` attribute
+    pub fn class(&self) -> Vec<String> {
+        let class = match self {
+            Obj::Null => "NULL",
+            Obj::Vector(v) if v.attributes().class().is_some() => {
+                return v.attributes().class().unwrap_or_default().to_vec();
+            }
+            Obj::Vector(Vector::Numeric(_)) => "numeric",
+            Obj::Vector(Vector::Integer(_)) => "integer",
+            Obj::Vector(Vector::Logical(_)) => "logical",
+            Obj::Vector(Vector::Character(_)) => "character",
+            Obj::List(l) => {
+                return l
+                    .attributes
+                    .class()
+                    .map_or_else(|| vec![String::from("list")], |c| c.to_vec());
+            }
+            Obj::Expr(_) => "call",
+            Obj::Closure(..) => "promise",
+            Obj::Function(..) => "function",
+            Obj::Environment(_) => "environment",
+            Obj::Formula(..) => "formula",
+            Obj::External(x) => return x.class(),
+        };
+
+        vec![String::from(class)]
+    }
+
+    /// Whether an object satisfies a type annotation, where `any` admits all
+    /// objects and `numeric` admits integers
+    pub fn is_type(&self, ty: &str) -> bool {
+        match (ty, self) {
+            ("any", _) => true,
+            ("numeric", Obj::Vector(Vector::Integer(_))) => true,
+            ("list", Obj::List(_)) => true,
+            (ty, _) => self.class().iter().any(|class| class == ty),
+        }
+    }
+
     pub fn try_get_named(&mut self, name: &str) -> EvalResult {
         use Error::{ArgumentMissing, VariableNotFound};
         match self.get_named(name) {
@@ -290,28 +343,46 @@ impl TryInto<bool> for Obj {
 impl Display for Obj {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Obj::Vector(v) => write!(f, "{}", v),
+            Obj::Vector(v) => match time::fmt_time(v, f) {
+                Some(result) => result,
+                None => display_vector(v, f),
+            },
             Obj::Null => write!(f, "NULL"),
             Obj::Environment(x) => write!(f, "<environment {:?}>", x.values.as_ptr()),
-            Obj::Function(formals, Expr::Primitive(primitive), _) => {
+            Obj::Function(formals, Expr::Primitive(primitive), ..) => {
                 write!(
                     f,
-                    "function({}) .Primitive(\"{}\")",
+                    "function({}) .Primitive({:?})",
                     formals,
                     primitive.rfmt()
                 )
             }
-            Obj::Function(formals, body, parent_env) => {
+            Obj::Function(formals, body, parent_env, sig) => {
                 let parent_env = Obj::Environment(Rc::clone(parent_env));
-                write!(f, "function({}) {}\n{}", formals, body, parent_env)
+                let formals = sig.fmt_formals(formals);
+                let returns = sig.fmt_returns();
+                write!(f, "function({formals}){returns} {body}\n{parent_env}")
             }
             Obj::List(vals) => display_list(vals, f, None),
             Obj::Closure(expr, env) => write!(f, "{expr} @ {env}"),
             Obj::Expr(expr) => write!(f, "{}", expr),
+            Obj::Formula(expr, _) => write!(f, "{}", expr),
+            Obj::External(x) => write!(f, "{x}"),
         }
     }
 }
 
+/// Display a vector followed by its attributes
+fn display_vector(x: &Vector, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", x)?;
+    // names are displayed along with the elements they name
+    for (name, value) in x.attributes().iter().filter(|(k, _)| k != "names") {
+        write!(f, "\nattr(,\"{name}\")\n{}", Vector::from(value.clone()))?;
+    }
+
+    Ok(())
+}
+
 fn display_list(x: &List, f: &mut fmt::Formatter<'_>, bc: Option<String>) -> fmt::Result {
     let v = x.values.borrow();
     let s = x.subsets.clone();
@@ -362,7 +433,8 @@ impl std::ops::Add for Obj {
     type Output = EvalResult;
 
     fn add(self, rhs: Self) -> Self::Output {
-        match (self.as_numeric()?, rhs.as_numeric()?) {
+        let (lhs, rhs) = time::operands(Arith::Add, self, rhs)?;
+        match (lhs.as_numeric()?, rhs.as_numeric()?) {
             (Obj::Vector(l), Obj::Vector(r)) => Ok(Obj::Vector(l + r)),
             _ => internal_err!(),
         }
@@ -373,7 +445,13 @@ impl std::ops::Sub for Obj {
     type Output = EvalResult;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        match (self.as_numeric()?, rhs.as_numeric()?) {
+        let (lhs, rhs) = time::operands(Arith::Sub, self, rhs)?;
+        match (lhs.as_numeric()?, rhs.as_numeric()?) {
+            // differences between date-times are given in the largest units
+            // that suit them
+            (Obj::Vector(l), Obj::Vector(r)) if l.inherits("POSIXct") && r.inherits("POSIXct") => {
+                Ok(Obj::Vector(time::simplify_difference(l - r)))
+            }
             (Obj::Vector(l), Obj::Vector(r)) => Ok(Obj::Vector(l - r)),
             _ => internal_err!(),
         }
@@ -384,7 +462,7 @@ impl std::ops::Neg for Obj {
     type Output = EvalResult;
 
     fn neg(self) -> Self::Output {
-        match self.as_numeric()? {
+        match time::negated(self)?.as_numeric()? {
             Obj::Vector(x) => Ok(Obj::Vector(-x)),
             _ => internal_err!(),
         }
@@ -395,7 +473,8 @@ impl std::ops::Mul for Obj {
     type Output = EvalResult;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        match (self.as_numeric()?, rhs.as_numeric()?) {
+        let (lhs, rhs) = time::operands(Arith::Mul, self, rhs)?;
+        match (lhs.as_numeric()?, rhs.as_numeric()?) {
             (Obj::Vector(l), Obj::Vector(r)) => Ok(Obj::Vector(l * r)),
             _ => internal_err!(),
         }
@@ -406,7 +485,8 @@ impl std::ops::Div for Obj {
     type Output = EvalResult;
 
     fn div(self, rhs: Self) -> Self::Output {
-        match (self.as_numeric()?, rhs.as_numeric()?) {
+        let (lhs, rhs) = time::operands(Arith::Div, self, rhs)?;
+        match (lhs.as_numeric()?, rhs.as_numeric()?) {
             (Obj::Vector(l), Obj::Vector(r)) => Ok(Obj::Vector(l / r)),
             _ => internal_err!(),
         }
@@ -417,7 +497,8 @@ impl super::object::Pow<Obj> for Obj {
     type Output = EvalResult;
 
     fn power(self, rhs: Self) -> Self::Output {
-        match (self.as_numeric()?, rhs.as_numeric()?) {
+        let (lhs, rhs) = time::operands(Arith::Pow, self, rhs)?;
+        match (lhs.as_numeric()?, rhs.as_numeric()?) {
             (Obj::Vector(l), Obj::Vector(r)) => Ok(Obj::Vector(l.power(r))),
             _ => internal_err!(),
         }
@@ -428,7 +509,8 @@ impl std::ops::Rem for Obj {
     type Output = EvalResult;
 
     fn rem(self, rhs: Self) -> Self::Output {
-        match (self.as_numeric()?, rhs.as_numeric()?) {
+        let (lhs, rhs) = time::operands(Arith::Rem, self, rhs)?;
+        match (lhs.as_numeric()?, rhs.as_numeric()?) {
             (Obj::Vector(l), Obj::Vector(r)) => Ok(Obj::Vector(l % r)),
             _ => internal_err!(),
         }
@@ -460,39 +542,44 @@ impl std::ops::BitAnd for Obj {
 impl VecPartialCmp<Obj> for Obj {
     type Output = EvalResult;
     fn vec_gt(self, rhs: Self) -> Self::Output {
-        match (self.as_vector()?, rhs.as_vector()?) {
+        let (lhs, rhs) = time::comparable(self, rhs)?;
+        match (lhs.as_vector()?, rhs.as_vector()?) {
             (Obj::Vector(l), Obj::Vector(r)) => Ok(Obj::Vector(l.vec_gt(r))),
             _ => internal_err!(),
         }
     }
 
     fn vec_gte(self, rhs: Self) -> Self::Output {
-        match (self.as_vector()?, rhs.as_vector()?) {
+        let (lhs, rhs) = time::comparable(self, rhs)?;
+        match (lhs.as_vector()?, rhs.as_vector()?) {
             (Obj::Vector(l), Obj::Vector(r)) => Ok(Obj::Vector(l.vec_gte(r))),
             _ => internal_err!(),
         }
     }
 
     fn vec_lt(self, rhs: Self) -> Self::Output {
-        match (self.as_vector()?, rhs.as_vector()?) {
+        let (lhs, rhs) = time::comparable(self, rhs)?;
+        match (lhs.as_vector()?, rhs.as_vector()?) {
             (Obj::Vector(l), Obj::Vector(r)) => Ok(Obj::Vector(l.vec_lt(r))),
             _ => internal_err!(),
         }
     }
 
     fn vec_lte(self, rhs: Self) -> Self::Output {
-        match (self.as_vector()?, rhs.as_vector()?) {
+        let (lhs, rhs) = time::comparable(self, rhs)?;
+        match (lhs.as_vector()?, rhs.as_vector()?) {
             (Obj::Vector(l), Obj::Vector(r)) => Ok(Obj::Vector(l.vec_lte(r))),
             _ => internal_err!(),
         }
     }
 
     fn vec_eq(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
+        match time::comparable(self, rhs)? {
             (lhs @ Obj::Expr(_), rhs @ Obj::Expr(_)) => Ok((lhs == rhs).into()),
             (lhs @ Obj::Closure(..), rhs @ Obj::Closure(..)) => Ok((lhs == rhs).into()),
             (lhs @ Obj::Function(..), rhs @ Obj::Function(..)) => Ok((lhs == rhs).into()),
             (lhs @ Obj::Environment(_), rhs @ Obj::Environment(_)) => Ok((lhs == rhs).into()),
+            (lhs @ Obj::External(_), rhs @ Obj::External(_)) => Ok((lhs == rhs).into()),
             (lhs, rhs) => match (lhs.as_vector()?, rhs.as_vector()?) {
                 (Obj::Vector(l), Obj::Vector(r)) => Ok(Obj::Vector(l.vec_eq(r))),
                 _ => internal_err!(),
@@ -501,11 +588,12 @@ impl VecPartialCmp<Obj> for Obj {
     }
 
     fn vec_neq(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
+        match time::comparable(self, rhs)? {
             (lhs @ Obj::Expr(_), rhs @ Obj::Expr(_)) => Ok((lhs != rhs).into()),
             (lhs @ Obj::Closure(..), rhs @ Obj::Closure(..)) => Ok((lhs != rhs).into()),
             (lhs @ Obj::Function(..), rhs @ Obj::Function(..)) => Ok((lhs != rhs).into()),
             (lhs @ Obj::Environment(_), rhs @ Obj::Environment(_)) => Ok((lhs != rhs).into()),
+            (lhs @ Obj::External(_), rhs @ Obj::External(_)) => Ok((lhs != rhs).into()),
             (lhs, rhs) => match (lhs.as_vector()?, rhs.as_vector()?) {
                 (Obj::Vector(l), Obj::Vector(r)) => Ok(Obj::Vector(l.vec_neq(r))),
                 _ => internal_err!(),
@@ -551,6 +639,8 @@ impl Display for Frame {
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct CallStack {
     pub frames: Vec<Frame>,
+    // Snapshot of frames at the point where the most recent error was raised
+    pub traceback: Vec<Frame>,
 }
 
 impl CallStack {
@@ -594,18 +684,337 @@ impl CallStack {
     pub fn pop_frame_and_return(&mut self, result: EvalResult) -> EvalResult {
         match result {
             Ok(..) => {
+                // a successful return means any previous error was handled
+                if !self.traceback.is_empty() {
+                    self.traceback.clear();
+                }
+
                 self.frames.pop();
                 result
             }
-            error => error,
+            Err(Signal::Error(..)) => {
+                // capture frames only where the error is first raised, the
+                // outer frames it propagates through are already included
+                if self.traceback.is_empty() {
+                    self.traceback = self.frames.clone();
+                }
+
+                self.frames.pop();
+                result
+            }
+            other => other,
+        }
+    }
+
+    /// Frames captured when the most recent error was raised
+    pub fn traceback(&self) -> CallStack {
+        CallStack::from(self.traceback.clone())
+    }
+
+    /// Persist the most recent error's traceback
+    ///
+    /// Calls are stored as `.Traceback` in the base environment, where they
+    /// can be retrieved by `traceback()` from later evaluations in the same
+    /// session.
+    pub fn record_traceback(&self) {
+        let Some(global) = self.frames.first() else {
+            return;
+        };
+
+        let mut base = global.env.clone();
+        while let Some(parent) = base.parent.clone() {
+            base = parent;
         }
+
+        let calls = self
+            .traceback
+            .iter()
+            .skip(1) // skip global frame
+            .rev() // most recent call first
+            .map(|f| (None, Obj::Expr(f.call.clone())))
+            .collect::<Vec<_>>();
+
+        // errors raised at the top level have no traceback
+        let calls = match calls.len() {
+            0 => Obj::Null,
+            _ => Obj::List(List::from(calls)),
+        };
+
+        base.insert(TRACEBACK.to_string(), calls);
     }
 
     pub fn new() -> CallStack {
         CallStack::from(Frame::new(Expr::Null, Rc::new(Environment::default())))
     }
+
+    /// Evaluate a super-assignment, `to <<- from`
+    pub fn assign_lazy_super(&mut self, to: Expr, from: Expr) -> EvalResult {
+        let result = self.eval(from)?;
+        self.assign_replacement(to, result.clone(), true)?;
+        Ok(result)
+    }
+
+    /// Assign to a symbol in the nearest enclosing environment with a
+    /// binding of the same name, or the global environment if none exists
+    fn assign_super(&mut self, to: Expr, value: Obj) -> EvalResult {
+        let (Expr::String(name) | Expr::Symbol(name)) = to else {
+            return Error::IncorrectContext("<<-".to_string()).into();
+        };
+
+        let global = self.frames[0].env.clone();
+        let mut env = self.env().parent.clone();
+        while let Some(e) = env {
+            if Rc::ptr_eq(&e, &global) || e.values.borrow().contains_key(&name) {
+                e.insert(name, value.clone());
+                return Ok(value);
+            }
+            env = e.parent.clone();
+        }
+
+        global.insert(name, value.clone());
+        Ok(value)
+    }
+
+    /// Assign a value to a symbol, or through a replacement function
+    ///
+    /// Assignment to a call, `f(x, ...) <- value`, is evaluated as
+    /// `` x <- `f<-`(x, ..., value = value) ``, where `x` may itself be a
+    /// call to be replaced in turn, as in `names(x)[2] <- "b"`. Builtin
+    /// operators (`[`, `[[` and `$`) instead replace elements in place.
+    /// When assigning with `<<-`, the final symbol is super-assigned.
+    fn assign_replacement(&mut self, to: Expr, value: Obj, sup: bool) -> EvalResult {
+        let Expr::Call(what, mut args) = to else {
+            return match (to, sup) {
+                (Expr::List(targets), _) => self.assign_destructured(targets, value, sup),
+                (to, true) => self.assign_super(to, value),
+                (to, false) => self.assign(to, value),
+            };
+        };
+
+        let Some(target) = args.values.first().cloned() else {
+            return Error::IncorrectContext("<-".to_string()).into();
+        };
+
+        // bind the target's current value and replacement value to
+        // temporaries, as they may already be evaluated, setting aside any
+        // existing bindings of the same names
+        let env = self.env();
+        let current = self.eval(target.clone())?;
+        let shadowed = [REPLACE_TMP, REPLACE_VALUE].map(|k| env.values.borrow_mut().remove(k));
+        env.insert(REPLACE_TMP.to_string(), current);
+        env.insert(REPLACE_VALUE.to_string(), value);
+        args.values[0] = Expr::Symbol(REPLACE_TMP.to_string());
+
+        let value = Expr::Symbol(REPLACE_VALUE.to_string());
+        let result = match *what {
+            Expr::Primitive(p) => p
+                .call_assign(value, args, self)
+                .and_then(|_| self.eval(Expr::Symbol(REPLACE_TMP.to_string()))),
+            Expr::String(s) | Expr::Symbol(s) => {
+                args.push((Some(String::from("value")), value));
+                let f = Expr::Symbol(format!("{s}<-"));
+                self.eval(Expr::Call(Box::new(f), args))
+            }
+            _ => Error::IncorrectContext("<-".to_string()).into(),
+        };
+
+        for (k, v) in [REPLACE_TMP, REPLACE_VALUE].into_iter().zip(shadowed) {
+            match v {
+                Some(v) => env.insert(k.to_string(), v),
+                None => {
+                    env.values.borrow_mut().remove(k);
+                }
+            }
+        }
+
+        self.assign_replacement(target, result?, sup)
+    }
+}
+
+impl CallStack {
+    /// Look up a name from an environment, forcing any promise bound to it
+    /// using this call stack
+    pub fn get_in(&mut self, name: String, mut env: Rc<Environment>) -> EvalResult {
+        loop {
+            // search in this environment for value by name
+            if let Some(value) = env.values.borrow().get(&name) {
+                let result = value.clone();
+                return match result {
+                    c @ Obj::Closure(..) => c.force(self),
+                    _ => Ok(result),
+                };
+            }
+
+            // if not found, search through parent if available
+            if let Some(parent) = &env.parent {
+                env = parent.clone();
+            } else {
+                break;
+            }
+        }
+
+        builtin(name.as_str()).map(|prim| {
+            Obj::Function(
+                ExprList::new(),
+                Expr::Primitive(prim),
+                self.env(),
+                Box::default(),
+            )
+        })
+    }
+
+    /// Whether the target of a call resolves to `partial()`, found as the
+    /// call would find it
+    fn calls_partial(&mut self, what: &Expr) -> bool {
+        let (Expr::String(name) | Expr::Symbol(name)) = what else {
+            return false;
+        };
+
+        let f = match builtin(name) {
+            Ok(f) => f,
+            Err(_) => match self.env().get(name.clone()) {
+                Ok(Obj::Function(_, Expr::Primitive(f), ..)) => f,
+                _ => return false,
+            },
+        };
+
+        f == (Box::new(PrimitivePartial) as Box<dyn Builtin>)
+    }
+
+    /// Destructure a value into a list of targets, `(a, b) <- value`
+    ///
+    /// Targets may be
+    ///
+    /// * a symbol, assigned the next element
+    /// * a nested list of targets, destructuring the next element
+    /// * `name = x`, where `x` is a bare symbol, assigned the element named
+    ///   `x`
+    /// * `name = default`, where `default` is any other expression, assigned
+    ///   the next element or, if there are no more elements, the evaluated
+    ///   default
+    /// * `..rest`, assigned the elements not matched by any other target
+    ///
+    /// Elements before `..rest` are taken from the start, and those after it
+    /// from the end.
+    pub fn assign_destructured(&mut self, targets: ExprList, value: Obj, sup: bool) -> EvalResult {
+        // subsets are materialized, such that elements are taken from the
+        // values they select
+        let value = match value {
+            Obj::Vector(v) => Obj::Vector(v.materialize()),
+            value => value,
+        };
+
+        let mut elements: Vec<(Option<String>, Obj)> = match &value {
+            Obj::List(l) => l.elements(),
+            Obj::Vector(v) => (0..v.len())
+                .filter_map(|i| v.get(i).map(|x| (None, Obj::Vector(x))))
+                .collect(),
+            Obj::Null => vec![],
+            _ => return err!("cannot destructure a value that is not a list or vector"),
+        };
+
+        let assign = |stack: &mut CallStack, to: Expr, value: Obj| match to {
+            Expr::List(targets) => stack.assign_destructured(targets, value, sup),
+            to if sup => stack.assign_super(to, value),
+            to => stack.assign(to, value),
+        };
+
+        // elements extracted by name are not matched by position
+        let mut positional = vec![];
+        for (name, target) in targets.into_iter() {
+            match (name, target) {
+                (Some(name), Expr::Symbol(s)) => {
+                    let Some(i) = elements.iter().position(|(k, _)| k.as_ref() == Some(&s)) else {
+                        return err!(format!("cannot destructure missing element '{s}'"));
+                    };
+
+                    let (_, x) = elements.remove(i);
+                    assign(self, Expr::Symbol(name), x)?;
+                }
+                (Some(name), default) => positional.push((Expr::Symbol(name), Some(default))),
+                (None, target @ (Expr::Symbol(_) | Expr::List(_) | Expr::Ellipsis(_))) => {
+                    positional.push((target, None))
+                }
+                (None, target) => return err!(format!("invalid destructuring target '{target}'")),
+            }
+        }
+
+        let rest = positional
+            .iter()
+            .position(|(t, _)| matches!(t, Expr::Ellipsis(_)));
+
+        let (before, after) = match rest {
+            Some(i) => (i, positional.len() - i - 1),
+            None => (positional.len(), 0),
+        };
+
+        let required = positional[..before]
+            .iter()
+            .take_while(|(_, default)| default.is_none())
+            .count()
+            + after;
+
+        let n = elements.len();
+        if n < required || (rest.is_none() && n > before) {
+            return err!(format!(
+                "cannot destructure {n} values into {} targets",
+                before + after
+            ));
+        }
+
+        let end = n - after;
+        for (i, (target, default)) in positional.into_iter().enumerate() {
+            let value = match (target, rest) {
+                // rest collects any elements between those before and after it
+                (Expr::Ellipsis(name), Some(r)) if i == r => {
+                    let Some(name) = name else { continue };
+                    let elements = elements[before.min(end)..end].to_vec();
+                    let value = match &value {
+                        Obj::Vector(v) => {
+                            let range = Subset::Range(before.min(end)..end);
+                            Obj::Vector(v.subset(range).materialize())
+                        }
+                        _ => Obj::List(List::from(elements)),
+                    };
+
+                    assign(self, Expr::Symbol(name), value)?;
+                    continue;
+                }
+                (target, Some(r)) if i > r => (target, elements[end + i - r - 1].1.clone()),
+                (target, _) if i < end => (target, elements[i].1.clone()),
+                (target, _) => match default {
+                    Some(default) => (target, self.eval_and_finalize(default)?),
+                    None => {
+                        return err!(format!(
+                            "cannot destructure {n} values into {} targets",
+                            before + after
+                        ))
+                    }
+                },
+            };
+
+            assign(self, value.0, value.1)?;
+        }
+
+        Ok(value)
+    }
+}
+
+/// Name of the binding used to persist the last error's traceback
+pub const TRACEBACK: &str = ".Traceback";
+
+/// Placeholder for arguments left unbound when partially applying a call
+const PLACEHOLDER: &str = "_";
+
+/// Whether an argument marks a place left unbound, as in `f(x, _)`
+pub fn is_placeholder(expr: &Expr) -> bool {
+    matches!(expr, Expr::Symbol(s) if s == PLACEHOLDER)
 }
 
+/// Names of the temporary bindings used while evaluating replacement functions
+const REPLACE_TMP: &str = "*tmp*";
+const REPLACE_VALUE: &str = "*value*";
+
 impl Display for CallStack {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for (i, frame) in self.frames.iter().enumerate().skip(1) {
@@ -617,44 +1026,42 @@ impl Display for CallStack {
 
 impl From<Frame> for CallStack {
     fn from(frame: Frame) -> Self {
+        CallStack::from(vec![frame])
+    }
+}
+
+impl From<Vec<Frame>> for CallStack {
+    fn from(frames: Vec<Frame>) -> Self {
         Self {
-            frames: vec![frame],
+            frames,
+            ..Default::default()
         }
     }
 }
 
 impl From<Rc<Environment>> for CallStack {
     fn from(value: Rc<Environment>) -> Self {
-        CallStack {
-            frames: vec![Frame::new(Expr::Null, value.clone())],
-        }
+        CallStack::from(Frame::new(Expr::Null, value.clone()))
     }
 }
 
 impl Context for CallStack {
     fn assign_lazy(&mut self, to: Expr, from: Expr) -> EvalResult {
         const LIST: &str = "list";
-        let err = Err(Signal::Error(Error::IncorrectContext("<-".to_string())));
 
-        if let Expr::Call(what, mut args) = to {
-            match *what {
-                // special case for list() calls
-                Expr::String(s) | Expr::Symbol(s) if s == LIST => {
+        if let Expr::Call(what, args) = &to {
+            // special case for list() calls
+            if let Expr::String(s) | Expr::Symbol(s) = what.as_ref() {
+                if s == LIST {
                     let result = self.eval(from)?;
-                    return self.assign(Expr::List(args), result);
+                    return self.assign(Expr::List(args.clone()), result);
                 }
-                Expr::String(s) | Expr::Symbol(s) => {
-                    args.insert(0, from);
-                    let s = format!("{}<-", s);
-                    return self.eval(Expr::Call(Box::new(Expr::Symbol(s)), args));
-                }
-                Expr::Primitive(p) => return p.call_assign(from, args, self),
-                _ => return err,
             }
         }
 
         let result = self.eval(from)?;
-        self.assign(to, result)
+        self.assign_replacement(to, result.clone(), false)?;
+        Ok(result)
     }
 
     fn assign(&mut self, to: Expr, from: Obj) -> EvalResult {
@@ -665,27 +1072,7 @@ impl Context for CallStack {
                 self.env().insert(s, from.clone());
                 Ok(from)
             }
-            (Expr::List(l), Obj::List(args)) => {
-                let mut i = 1;
-                for item in l.into_iter() {
-                    match item {
-                        (None, Expr::String(s) | Expr::Symbol(s)) => {
-                            let index = Obj::Vector(Vector::from(vec![i]));
-                            let value = args.try_get_inner(index)?;
-                            self.assign(Expr::Symbol(s), value)?;
-                            i += 1;
-                        }
-                        // TODO(feature): allow arbitrary right-side expressions
-                        // evaluated with list as additional data-frame
-                        (Some(n), Expr::String(s) | Expr::Symbol(s)) => {
-                            let value = args.try_get_inner(Obj::Vector(Vector::from(vec![s])))?;
-                            self.assign(Expr::Symbol(n), value)?;
-                        }
-                        _ => return internal_err!(),
-                    }
-                }
-                Ok(Obj::List(args))
-            }
+            (Expr::List(targets), from) => self.assign_destructured(targets, from, false),
             _ => err,
         }
     }
@@ -699,6 +1086,24 @@ impl Context for CallStack {
             return internal_err!();
         };
 
+        // futures stop evaluating at their next call once cancelled
+        future::check_cancelled()?;
+        profiler::poll(self);
+
+        // calls with a bare `_` argument are partially applied, `f(x = 1, _)`,
+        // with later arguments filling the place of each `_`. Calls to
+        // `partial()` itself already leave placeholders unbound.
+        if !matches!(*what, Expr::Primitive(_))
+            && args.values.iter().any(is_placeholder)
+            && !self.calls_partial(&what)
+        {
+            let args = std::iter::once((None, *what))
+                .chain(args)
+                .collect::<ExprList>();
+
+            return self.eval_call(Expr::new_primitive_call(PrimitivePartial, args));
+        }
+
         match *what {
             Expr::Primitive(f) if f.is_transparent() => f.call(args, self),
             Expr::Primitive(f) => {
@@ -762,8 +1167,16 @@ impl Context for CallStack {
                 self.pop_frame_and_return(result)
             }
             _ => {
-                self.add_frame(expr, self.last_frame().env().clone());
-                let result = (self.eval(*what)?).call(args, self);
+                // functions are called in a new frame of their own environment
+                let obj = self.eval(*what)?;
+                match obj.environment() {
+                    Some(env) if matches!(obj, Obj::Function(..)) => {
+                        self.add_child_frame(expr, env)
+                    }
+                    _ => self.add_frame(expr, self.last_frame().env().clone()),
+                };
+
+                let result = obj.call(args, self);
                 self.pop_frame_and_return(result)
             }
         }
@@ -792,34 +1205,8 @@ impl Context for CallStack {
     }
 
     fn get(&mut self, name: String) -> EvalResult {
-        let mut env = self.env();
-        loop {
-            // search in this environment for value by name
-            if let Some(value) = env.values.borrow().get(&name) {
-                let result = value.clone();
-                return match result {
-                    c @ Obj::Closure(..) => c.force(self),
-                    _ => Ok(result),
-                };
-            }
-
-            // if not found, search through parent if available
-            if let Some(parent) = &env.parent {
-                env = parent.clone();
-            } else {
-                break;
-            }
-        }
-
-        if let Ok(prim) = builtin(name.as_str()) {
-            Ok(Obj::Function(
-                ExprList::new(),
-                Expr::Primitive(prim),
-                self.env(),
-            ))
-        } else {
-            Err(Signal::Error(Error::VariableNotFound(name)))
-        }
+        let env = self.env();
+        self.get_in(name, env)
     }
 
     // NOTE:
@@ -855,7 +1242,9 @@ impl Context for Obj {
             Expr::Integer(x) => Ok(Obj::Vector(Vector::from(vec![x]))),
             Expr::Bool(x) => Ok(Obj::Vector(Vector::from(vec![OptionNA::Some(x)]))),
             Expr::String(x) => Ok(Obj::Vector(Vector::from(vec![OptionNA::Some(x)]))),
-            Expr::Function(formals, body) => Ok(Obj::Function(formals, *body, self.env().clone())),
+            Expr::Function(formals, body, sig) => {
+                Ok(Obj::Function(formals, *body, self.env().clone(), sig))
+            }
             Expr::Symbol(name) => self.get(name),
             Expr::Break => Err(Signal::Condition(Cond::Break)),
             Expr::Continue => Err(Signal::Condition(Cond::Continue)),
@@ -863,6 +1252,7 @@ impl Context for Obj {
                 p.formals(),
                 Expr::Primitive(p),
                 self.environment().unwrap(),
+                Box::default(),
             )),
             Expr::More => Ok(Obj::Null),
 
@@ -898,11 +1288,18 @@ impl Context for Rc<Environment> {
             Expr::Integer(x) => Ok(Obj::Vector(Vector::from(vec![x]))),
             Expr::Bool(x) => Ok(Obj::Vector(Vector::from(vec![OptionNA::Some(x)]))),
             Expr::String(x) => Ok(Obj::Vector(Vector::from(vec![OptionNA::Some(x)]))),
-            Expr::Function(formals, body) => Ok(Obj::Function(formals, *body, self.env().clone())),
+            Expr::Function(formals, body, sig) => {
+                Ok(Obj::Function(formals, *body, self.env().clone(), sig))
+            }
             Expr::Symbol(name) => self.get(name),
             Expr::Break => Err(Signal::Condition(Cond::Break)),
             Expr::Continue => Err(Signal::Condition(Cond::Continue)),
-            Expr::Primitive(p) => Ok(Obj::Function(p.formals(), Expr::Primitive(p), self.clone())),
+            Expr::Primitive(p) => Ok(Obj::Function(
+                p.formals(),
+                Expr::Primitive(p),
+                self.clone(),
+                Box::default(),
+            )),
             Expr::More => Ok(Obj::Null),
 
             // bubbles up to where a symbol can be attached for context
@@ -2,11 +2,22 @@
 extern crate pest_derive;
 
 pub mod callable;
+pub mod check;
 pub mod cli;
+pub mod connection;
 pub mod context;
+pub mod debugger;
 pub mod error;
+pub mod future;
+pub mod handle;
 pub mod lang;
 pub mod object;
+pub mod options;
 pub mod parser;
+pub mod profiler;
 pub mod repl;
+pub mod time;
+pub mod trace;
 pub mod utils;
+pub mod warning;
+pub mod workspace;
@@ -19,7 +19,7 @@ pub enum Expr {
     String(String),
     Symbol(String),
     List(ExprList),
-    Function(ExprList, Box<Expr>),
+    Function(ExprList, Box<Expr>, Box<Signature>),
     Call(Box<Expr>, ExprList),
     Primitive(Box<dyn Builtin>),
 }
@@ -42,7 +42,9 @@ impl PartialEq for Expr {
             (Symbol(l), Symbol(r)) => l == r,
             (List(l), List(r)) => l == r,
             (Primitive(l), Primitive(r)) => l == r,
-            (Function(largs, lbody), Function(rargs, rbody)) => largs == rargs && lbody == rbody,
+            (Function(largs, lbody, lsig), Function(rargs, rbody, rsig)) => {
+                largs == rargs && lbody == rbody && lsig == rsig
+            }
             (Call(lwhat, largs), Call(rwhat, rargs)) => lwhat == rwhat && largs == rargs,
             _ => false,
         }
@@ -66,6 +68,11 @@ impl Expr {
     }
 }
 
+/// Test whether a name is a special infix operator, like `%in%`
+pub fn is_special(name: &str) -> bool {
+    name.len() > 2 && name.starts_with('%') && name.ends_with('%')
+}
+
 impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -84,16 +91,75 @@ impl fmt::Display for Expr {
             Expr::Ellipsis(Some(s)) => write!(f, "..{s}"),
             Expr::Call(what, args) => match &**what {
                 Expr::Primitive(p) => write!(f, "{}", p.rfmt_call(args)),
+                Expr::String(s) | Expr::Symbol(s) if is_special(s) && args.values.len() == 2 => {
+                    write!(f, "{} {s} {}", args.values[0], args.values[1])
+                }
                 Expr::String(s) | Expr::Symbol(s) => write!(f, "{}({})", s, args),
                 rexpr => write!(f, "({})({})", rexpr, args),
             },
-            Expr::Function(head, body) => write!(f, "function({}) {}", head, body),
+            Expr::Function(head, body, sig) => write!(
+                f,
+                "function({}){} {}",
+                sig.fmt_formals(head),
+                sig.fmt_returns(),
+                body
+            ),
             Expr::Primitive(p) => write!(f, "Primitive(\"{}\")", p.rfmt()),
             x => write!(f, "{:?}", x),
         }
     }
 }
 
+/// Optional type annotations of a function's parameters and return value,
+/// as in `function(x: numeric, n: integer = 1L) -> character`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Signature {
+    pub params: Vec<(String, String)>,
+    pub returns: Option<String>,
+}
+
+impl Signature {
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty() && self.returns.is_none()
+    }
+
+    /// The annotated type of a parameter
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, ty)| ty.as_str())
+    }
+
+    /// Format function formals, including their annotations
+    pub fn fmt_formals(&self, formals: &ExprList) -> String {
+        let params: Vec<String> = formals
+            .keys
+            .iter()
+            .zip(formals.values.iter())
+            .map(
+                |(k, v)| match (k.as_deref().map(|k| (k, self.param(k))), v) {
+                    (Some((k, Some(ty))), Expr::Missing) => format!("{k}: {ty}"),
+                    (Some((k, Some(ty))), v) => format!("{k}: {ty} = {v}"),
+                    (Some((k, None)), Expr::Missing) => k.to_string(),
+                    (Some((k, None)), v) => format!("{k} = {v}"),
+                    (None, v) => format!("{v}"),
+                },
+            )
+            .collect();
+
+        params.join(", ")
+    }
+
+    /// Format the return annotation, if any
+    pub fn fmt_returns(&self) -> String {
+        match &self.returns {
+            Some(ty) => format!(" -> {ty}"),
+            None => String::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct ExprList {
     pub keys: Vec<Option<String>>, // TODO: use Vec<RExprListKey>
@@ -117,9 +183,6 @@ impl fmt::Display for ExprList {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct RExprListItem(Option<String>, Expr);
-
 impl IntoIterator for ExprList {
     type Item = (Option<String>, Expr);
     type IntoIter = <Zip<IntoIter<Option<String>>, IntoIter<Expr>> as IntoIterator>::IntoIter;
@@ -1,6 +1,7 @@
 use std::rc::Rc;
 
 use crate::error::Error;
+use crate::handle::External;
 use crate::internal_err;
 use crate::lang::Signal;
 
@@ -17,8 +18,14 @@ pub enum Obj {
     // Metaprogramming structures
     Expr(Expr),
     Closure(Expr, Rc<Environment>),
-    Function(ExprList, Expr, Rc<Environment>),
+    Function(ExprList, Expr, Rc<Environment>, Box<Signature>),
     Environment(Rc<Environment>),
+
+    // Modelling structures
+    Formula(Expr, Rc<Environment>),
+
+    // Resources held outside of the interpreter
+    External(External),
 }
 
 impl PartialEq for Obj {
@@ -36,9 +43,12 @@ impl PartialEq for Obj {
             }
             (Obj::Expr(l), Obj::Expr(r)) => l == r,
             (Obj::Closure(lc, lenv), Obj::Closure(rc, renv)) => lc == rc && lenv == renv,
-            (Obj::Function(largs, lbody, lenv), Obj::Function(rargs, rbody, renv)) => {
+            (Obj::Formula(lf, lenv), Obj::Formula(rf, renv)) => lf == rf && lenv == renv,
+            (Obj::External(l), Obj::External(r)) => l == r,
+            (Obj::Function(largs, lbody, lenv, lsig), Obj::Function(rargs, rbody, renv, rsig)) => {
                 largs == rargs
                     && lbody == rbody
+                    && lsig == rsig
                     && Obj::Environment(lenv.clone()) == Obj::Environment(renv.clone())
             }
             (Obj::Environment(l), Obj::Environment(r)) => {
@@ -104,6 +114,43 @@ impl TryInto<f64> for Obj {
     }
 }
 
+impl TryInto<String> for Obj {
+    type Error = Signal;
+    fn try_into(self) -> Result<String, Self::Error> {
+        use Error::CannotBeCoercedToCharacter;
+
+        let Obj::Vector(Vector::Character(v)) = self.as_character()? else {
+            return internal_err!();
+        };
+
+        match &v.inner().clone().borrow()[..] {
+            [OptionNA::Some(s), ..] => Ok(s.clone()),
+            _ => Err(CannotBeCoercedToCharacter.into()),
+        }
+    }
+}
+
+impl TryInto<Vec<String>> for Obj {
+    type Error = Signal;
+    fn try_into(self) -> Result<Vec<String>, Self::Error> {
+        use Error::CannotBeCoercedToCharacter;
+
+        let Obj::Vector(Vector::Character(v)) = self.as_character()? else {
+            return internal_err!();
+        };
+
+        v.inner()
+            .clone()
+            .borrow()
+            .iter()
+            .map(|vi| match vi {
+                OptionNA::Some(s) => Ok(s.clone()),
+                OptionNA::NA => Err(CannotBeCoercedToCharacter.into()),
+            })
+            .collect()
+    }
+}
+
 impl TryInto<Vec<f64>> for Obj {
     type Error = Signal;
     fn try_into(self) -> Result<Vec<f64>, Self::Error> {
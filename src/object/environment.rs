@@ -5,8 +5,8 @@ use std::fmt::Display;
 use std::rc::Rc;
 
 use crate::callable::builtins::BUILTIN;
+use crate::callable::core::builtin;
 use crate::context::Context;
-use crate::error::Error;
 use crate::lang::EvalResult;
 
 use super::{Expr, ExprList, List, Obj};
@@ -25,6 +25,7 @@ impl Environment {
                 ExprList::new(),
                 Expr::Primitive(builtin.clone()),
                 env.clone(),
+                Box::default(),
             );
 
             env.insert(String::from(*name), builtin_fn);
@@ -32,6 +33,16 @@ impl Environment {
         env
     }
 
+    /// The base environment of builtins, which all environments descend from
+    pub fn base(self: &Rc<Self>) -> Rc<Environment> {
+        let mut env = self.clone();
+        while let Some(parent) = env.parent.clone() {
+            env = parent;
+        }
+
+        env
+    }
+
     pub fn insert(&self, name: String, value: Obj) {
         self.values.borrow_mut().insert(name, value);
     }
@@ -60,16 +71,15 @@ impl Environment {
             parent.clone().get(name)
 
         // if we're at the top level, fall back to primitives if available
-        } else if let Ok(prim) = name.as_str().try_into() {
-            Ok(Obj::Function(
-                ExprList::new(),
-                Expr::Primitive(prim),
-                Rc::new(self.clone()), // TODO(bug): will this retain shared ref?
-            ))
-
-        // otherwise, throw error
         } else {
-            Err(Error::VariableNotFound(name).into())
+            builtin(name.as_str()).map(|prim| {
+                Obj::Function(
+                    ExprList::new(),
+                    Expr::Primitive(prim),
+                    Rc::new(self.clone()), // TODO(bug): will this retain shared ref?
+                    Box::default(),
+                )
+            })
         }
     }
 }
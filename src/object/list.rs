@@ -5,6 +5,7 @@ use std::rc::Rc;
 use crate::error::Error;
 use crate::lang::EvalResult;
 
+use super::attributes::Attributes;
 use super::*;
 
 type ListNameMap = HashMap<String, Vec<usize>>;
@@ -15,6 +16,8 @@ pub struct List {
     pub names: Rc<RefCell<ListNameMap>>,
     pub values: Rc<RefCell<ListValues>>,
     pub subsets: Subsets,
+    // attributes of the list as a whole, such as its class
+    pub attributes: Attributes,
 }
 
 impl From<Vec<(Option<String>, Obj)>> for List {
@@ -30,6 +33,19 @@ impl From<Vec<(Option<String>, Obj)>> for List {
 }
 
 impl List {
+    /// A list of the given class
+    pub fn classed(values: ListValues, class: &[&str]) -> Self {
+        let class = class.iter().map(|c| c.to_string()).collect();
+        List {
+            attributes: Attributes::new().with("class", class),
+            ..List::from(values)
+        }
+    }
+
+    pub fn inherits(&self, class: &str) -> bool {
+        self.attributes.inherits(class)
+    }
+
     pub fn reindex(&mut self) {
         let mut names = self.names.borrow_mut();
         names.drain();
@@ -47,10 +63,12 @@ impl List {
     pub fn subset(&self, by: Subset) -> List {
         let Subsets(mut inner) = self.subsets.clone();
         inner.push(by);
+        // as with vectors, subsets of lists keep only their names
         List {
             names: self.names.clone(),
             values: self.values.clone(),
             subsets: Subsets(inner),
+            attributes: Attributes::new(),
         }
     }
 
@@ -82,6 +100,7 @@ impl List {
                     names: self.names.clone(),
                     values: self.values.clone(),
                     subsets: self.subsets.clone(),
+                    attributes: self.attributes.clone(),
                 }))
             }
 
@@ -119,6 +138,7 @@ impl List {
                     names: self.names.clone(),
                     values: self.values.clone(),
                     subsets: self.subsets.clone(),
+                    attributes: self.attributes.clone(),
                 }))
             }
             // vectorized assignment
@@ -156,6 +176,7 @@ impl List {
                     names: self.names.clone(),
                     values: self.values.clone(),
                     subsets: self.subsets.clone(),
+                    attributes: self.attributes.clone(),
                 }))
             }
             other => {
@@ -191,6 +212,7 @@ impl List {
                     names: self.names.clone(),
                     values: self.values.clone(),
                     subsets: self.subsets.clone(),
+                    attributes: self.attributes.clone(),
                 }))
             }
         }
@@ -234,8 +256,8 @@ impl List {
         {
             let names = self.names.borrow();
             let mut dups: Vec<usize> = names
-                .iter()
-                .flat_map(|(_, indices)| {
+                .values()
+                .flat_map(|indices| {
                     indices
                         .split_last()
                         .map_or(vec![], |(_, leading_dups)| leading_dups.to_vec())
@@ -257,6 +279,18 @@ impl List {
         self
     }
 
+    /// The elements of the list, after applying any subsets
+    pub fn elements(&self) -> Vec<(Option<String>, Obj)> {
+        let values = self.values.borrow();
+        self.subsets
+            .clone()
+            .bind_names(self.names.clone())
+            .into_iter()
+            .take(self.len())
+            .filter_map(|(_, i)| values.get(i?).cloned())
+            .collect()
+    }
+
     pub fn len(&self) -> usize {
         let Subsets(inner) = &self.subsets;
         match inner.as_slice() {
@@ -12,3 +12,8 @@ pub use vector::*;
 
 mod list;
 pub use list::*;
+
+mod snapshot;
+pub use snapshot::*;
+
+mod serialize;
@@ -0,0 +1,658 @@
+/// Serialization
+///
+/// Snapshots are written in a versioned binary format, such that objects can
+/// be persisted and restored by a later session. All integers are written
+/// little-endian and strings are written as their length followed by their
+/// UTF-8 bytes.
+///
+/// ```text
+/// "RSER" version:u8
+/// n:u32 (parent:env values:(n:u32 (name:str value)*))*   environments
+/// value                                                  the object
+/// ```
+///
+/// Environments are referenced by their index plus one, with `0` referring to
+/// the base environment and `u32::MAX` to the global environment of the
+/// session reading the object. Vectors are
+/// written as their length, a bitmap of which elements are `NA` and the
+/// values of the remaining elements. Vectors with attributes are preceded by
+/// their attributes, each written as a name and a vector of strings.
+///
+use crate::callable::core::Builtin;
+use crate::callable::keywords::*;
+use crate::callable::operators::*;
+use crate::error::Error;
+use crate::parser::Srcref;
+
+use super::attributes::Attributes;
+use super::snapshot::{EnvRef, EnvValues, Value};
+use super::*;
+
+const MAGIC: &[u8; 4] = b"RSER";
+const VERSION: u8 = 1;
+
+// the environment reference to the global environment
+const GLOBAL_ENV: u32 = u32::MAX;
+
+// value tags
+const NULL: u8 = 0;
+const NUMERIC: u8 = 1;
+const INTEGER: u8 = 2;
+const LOGICAL: u8 = 3;
+const CHARACTER: u8 = 4;
+const LIST: u8 = 5;
+const EXPR: u8 = 6;
+const CLOSURE: u8 = 7;
+const FUNCTION: u8 = 8;
+const ENVIRONMENT: u8 = 9;
+const FORMULA: u8 = 10;
+const ATTRIBUTED: u8 = 11;
+
+// expression tags
+const EXPR_NULL: u8 = 0;
+const EXPR_NA: u8 = 1;
+const EXPR_INF: u8 = 2;
+const EXPR_MORE: u8 = 3;
+const EXPR_CONTINUE: u8 = 4;
+const EXPR_BREAK: u8 = 5;
+const EXPR_ELLIPSIS: u8 = 6;
+const EXPR_MISSING: u8 = 7;
+const EXPR_BOOL: u8 = 8;
+const EXPR_NUMBER: u8 = 9;
+const EXPR_INTEGER: u8 = 10;
+const EXPR_STRING: u8 = 11;
+const EXPR_SYMBOL: u8 = 12;
+const EXPR_LIST: u8 = 13;
+const EXPR_FUNCTION: u8 = 14;
+const EXPR_CALL: u8 = 15;
+const EXPR_BUILTIN: u8 = 16;
+const EXPR_KEYWORD: u8 = 17;
+const EXPR_BLOCK: u8 = 18;
+
+/// Primitives produced by the parser which are not registered as builtins
+/// under their symbol, or share their symbol with another primitive
+fn keywords() -> Vec<(&'static str, Box<dyn Builtin>)> {
+    vec![
+        ("infix -", Box::new(InfixSub)),
+        ("prefix -", Box::new(PrefixSub)),
+        ("infix ~", Box::new(InfixTilde)),
+        ("prefix ~", Box::new(PrefixTilde)),
+        ("prefix ..", Box::new(PrefixPack)),
+        ("postfix ..", Box::new(PostfixPack)),
+        ("vector", Box::new(PrimVec)),
+        ("if", Box::new(KeywordIf)),
+        ("for", Box::new(KeywordFor)),
+        ("while", Box::new(KeywordWhile)),
+        ("repeat", Box::new(KeywordRepeat)),
+        ("return", Box::new(KeywordReturn)),
+        ("match", Box::new(KeywordMatch)),
+    ]
+}
+
+fn malformed() -> Error {
+    Error::Other("malformed serialized object".to_string())
+}
+
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, x: u8) {
+        self.bytes.push(x)
+    }
+
+    fn u32(&mut self, x: usize) {
+        self.bytes.extend((x as u32).to_le_bytes())
+    }
+
+    fn str(&mut self, x: &str) {
+        self.u32(x.len());
+        self.bytes.extend(x.as_bytes())
+    }
+
+    fn opt_str(&mut self, x: &Option<String>) {
+        match x {
+            Some(x) => {
+                self.u8(1);
+                self.str(x)
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn env(&mut self, env: &EnvRef) {
+        match env {
+            EnvRef::Base => self.u32(0),
+            EnvRef::Global => self.u32(GLOBAL_ENV as usize),
+            EnvRef::Captured(i) => self.u32(i + 1),
+        }
+    }
+
+    fn vector<T>(&mut self, x: &[OptionNA<T>], mut write: impl FnMut(&mut Self, &T)) {
+        self.u32(x.len());
+
+        let mut bitmap = vec![0u8; x.len().div_ceil(8)];
+        for (i, xi) in x.iter().enumerate() {
+            if let OptionNA::NA = xi {
+                bitmap[i / 8] |= 1 << (i % 8);
+            }
+        }
+
+        self.bytes.extend(bitmap);
+        for xi in x {
+            if let OptionNA::Some(xi) = xi {
+                write(self, xi)
+            }
+        }
+    }
+
+    fn value(&mut self, value: &Value) -> Result<(), Error> {
+        match value {
+            Value::Null => self.u8(NULL),
+            Value::Numeric(x) => {
+                self.u8(NUMERIC);
+                self.vector(x, |w, x| w.bytes.extend(x.to_le_bytes()))
+            }
+            Value::Integer(x) => {
+                self.u8(INTEGER);
+                self.vector(x, |w, x| w.bytes.extend(x.to_le_bytes()))
+            }
+            Value::Logical(x) => {
+                self.u8(LOGICAL);
+                self.vector(x, |w, x| w.u8(*x as u8))
+            }
+            Value::Character(x) => {
+                self.u8(CHARACTER);
+                self.vector(x, |w, x| w.str(x))
+            }
+            Value::List(x) => {
+                self.u8(LIST);
+                self.u32(x.len());
+                for (k, v) in x {
+                    self.opt_str(k);
+                    self.value(v)?;
+                }
+            }
+            Value::Expr(expr) => {
+                self.u8(EXPR);
+                self.expr(expr)?
+            }
+            Value::Closure(expr, env) => {
+                self.u8(CLOSURE);
+                self.expr(expr)?;
+                self.env(env)
+            }
+            Value::Function(formals, body, env, sig) => {
+                self.u8(FUNCTION);
+                self.exprs(formals)?;
+                self.expr(body)?;
+                self.env(env);
+                self.signature(sig)
+            }
+            Value::Environment(env) => {
+                self.u8(ENVIRONMENT);
+                self.env(env)
+            }
+            Value::Formula(expr, env) => {
+                self.u8(FORMULA);
+                self.expr(expr)?;
+                self.env(env)
+            }
+            Value::Attributed(value, attrs) => {
+                self.u8(ATTRIBUTED);
+                self.u32(attrs.iter().count());
+                for (name, values) in attrs.iter() {
+                    self.str(name);
+                    self.u32(values.len());
+                    for v in values {
+                        self.str(v);
+                    }
+                }
+                self.value(value)?
+            }
+            Value::External(class) => {
+                return Err(Error::Other(format!(
+                    "cannot serialize object of class '{}'",
+                    class.join("/")
+                )))
+            }
+        }
+
+        Ok(())
+    }
+
+    fn exprs(&mut self, x: &ExprList) -> Result<(), Error> {
+        self.u32(x.len());
+        for (k, v) in x.keys.iter().zip(x.values.iter()) {
+            self.opt_str(k);
+            self.expr(v)?;
+        }
+
+        Ok(())
+    }
+
+    fn signature(&mut self, sig: &Signature) {
+        self.u32(sig.params.len());
+        for (param, ty) in &sig.params {
+            self.str(param);
+            self.str(ty);
+        }
+
+        self.opt_str(&sig.returns)
+    }
+
+    fn primitive(&mut self, p: &dyn Builtin) -> Result<(), Error> {
+        if let Some(block) = p.as_any().downcast_ref::<KeywordBlock>() {
+            self.u8(EXPR_BLOCK);
+            self.opt_str(&block.srcref.file);
+            self.u32(block.srcref.lines.len());
+            block.srcref.lines.iter().for_each(|&line| self.u32(line));
+            return Ok(());
+        }
+
+        if let Some((name, _)) = keywords().into_iter().find(|(_, k)| **k == *p) {
+            self.u8(EXPR_KEYWORD);
+            self.str(name);
+            return Ok(());
+        }
+
+        let sym = p.rfmt();
+        match <Box<dyn Builtin>>::try_from(sym.as_str()) {
+            Ok(builtin) if *builtin == *p => {
+                self.u8(EXPR_BUILTIN);
+                self.str(&sym);
+                Ok(())
+            }
+            _ => Err(Error::Other(format!("cannot serialize primitive '{sym}'"))),
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Result<(), Error> {
+        match expr {
+            Expr::Null => self.u8(EXPR_NULL),
+            Expr::NA => self.u8(EXPR_NA),
+            Expr::Inf => self.u8(EXPR_INF),
+            Expr::More => self.u8(EXPR_MORE),
+            Expr::Continue => self.u8(EXPR_CONTINUE),
+            Expr::Break => self.u8(EXPR_BREAK),
+            Expr::Ellipsis(name) => {
+                self.u8(EXPR_ELLIPSIS);
+                self.opt_str(name)
+            }
+            Expr::Missing => self.u8(EXPR_MISSING),
+            Expr::Bool(x) => {
+                self.u8(EXPR_BOOL);
+                self.u8(*x as u8)
+            }
+            Expr::Number(x) => {
+                self.u8(EXPR_NUMBER);
+                self.bytes.extend(x.to_le_bytes())
+            }
+            Expr::Integer(x) => {
+                self.u8(EXPR_INTEGER);
+                self.bytes.extend(x.to_le_bytes())
+            }
+            Expr::String(x) => {
+                self.u8(EXPR_STRING);
+                self.str(x)
+            }
+            Expr::Symbol(x) => {
+                self.u8(EXPR_SYMBOL);
+                self.str(x)
+            }
+            Expr::List(x) => {
+                self.u8(EXPR_LIST);
+                self.exprs(x)?
+            }
+            Expr::Function(formals, body, sig) => {
+                self.u8(EXPR_FUNCTION);
+                self.exprs(formals)?;
+                self.expr(body)?;
+                self.signature(sig)
+            }
+            Expr::Call(what, args) => {
+                self.u8(EXPR_CALL);
+                self.expr(what)?;
+                self.exprs(args)?
+            }
+            Expr::Primitive(p) => self.primitive(p.as_ref())?,
+        }
+
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], Error> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + n)
+            .ok_or_else(malformed)?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        self.take(N)?.try_into().map_err(|_| malformed())
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> Result<usize, Error> {
+        Ok(u32::from_le_bytes(self.array()?) as usize)
+    }
+
+    fn bool(&mut self) -> Result<bool, Error> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(malformed()),
+        }
+    }
+
+    fn str(&mut self) -> Result<String, Error> {
+        let n = self.u32()?;
+        String::from_utf8(self.take(n)?.to_vec()).map_err(|_| malformed())
+    }
+
+    fn opt_str(&mut self) -> Result<Option<String>, Error> {
+        match self.bool()? {
+            true => Ok(Some(self.str()?)),
+            false => Ok(None),
+        }
+    }
+
+    fn env(&mut self, n: usize) -> Result<EnvRef, Error> {
+        match self.u32()? {
+            0 => Ok(EnvRef::Base),
+            i if i == GLOBAL_ENV as usize => Ok(EnvRef::Global),
+            i if i <= n => Ok(EnvRef::Captured(i - 1)),
+            _ => Err(malformed()),
+        }
+    }
+
+    fn vector<T>(
+        &mut self,
+        mut read: impl FnMut(&mut Self) -> Result<T, Error>,
+    ) -> Result<Vec<OptionNA<T>>, Error> {
+        let n = self.u32()?;
+        let bitmap = self.take(n.div_ceil(8))?.to_vec();
+
+        (0..n)
+            .map(|i| match bitmap[i / 8] & (1 << (i % 8)) {
+                0 => Ok(OptionNA::Some(read(self)?)),
+                _ => Ok(OptionNA::NA),
+            })
+            .collect()
+    }
+
+    // `n` is the number of environments, which references are checked against
+    fn value(&mut self, n: usize) -> Result<Value, Error> {
+        let value = match self.u8()? {
+            NULL => Value::Null,
+            NUMERIC => Value::Numeric(self.vector(|r| Ok(f64::from_le_bytes(r.array()?)))?),
+            INTEGER => Value::Integer(self.vector(|r| Ok(i32::from_le_bytes(r.array()?)))?),
+            LOGICAL => Value::Logical(self.vector(|r| r.bool())?),
+            CHARACTER => Value::Character(self.vector(|r| r.str())?),
+            LIST => {
+                let len = self.u32()?;
+                let mut values = vec![];
+                for _ in 0..len {
+                    values.push((self.opt_str()?, self.value(n)?));
+                }
+                Value::List(values)
+            }
+            EXPR => Value::Expr(self.expr()?),
+            CLOSURE => Value::Closure(self.expr()?, self.env(n)?),
+            FUNCTION => Value::Function(
+                self.exprs()?,
+                self.expr()?,
+                self.env(n)?,
+                Box::new(self.signature()?),
+            ),
+            ENVIRONMENT => Value::Environment(self.env(n)?),
+            FORMULA => Value::Formula(self.expr()?, self.env(n)?),
+            ATTRIBUTED => {
+                let len = self.u32()?;
+                let mut attrs = Attributes::new();
+                for _ in 0..len {
+                    let name = self.str()?;
+                    let values = (0..self.u32()?)
+                        .map(|_| self.str())
+                        .collect::<Result<Vec<_>, _>>()?;
+                    attrs.set(&name, values);
+                }
+                Value::Attributed(Box::new(self.value(n)?), attrs)
+            }
+            _ => return Err(malformed()),
+        };
+
+        Ok(value)
+    }
+
+    fn exprs(&mut self) -> Result<ExprList, Error> {
+        let n = self.u32()?;
+        let mut exprs = ExprList::new();
+        for _ in 0..n {
+            exprs.push((self.opt_str()?, self.expr()?));
+        }
+
+        Ok(exprs)
+    }
+
+    fn signature(&mut self) -> Result<Signature, Error> {
+        let n = self.u32()?;
+        let mut params = vec![];
+        for _ in 0..n {
+            params.push((self.str()?, self.str()?));
+        }
+
+        let returns = self.opt_str()?;
+        Ok(Signature { params, returns })
+    }
+
+    fn expr(&mut self) -> Result<Expr, Error> {
+        let expr = match self.u8()? {
+            EXPR_NULL => Expr::Null,
+            EXPR_NA => Expr::NA,
+            EXPR_INF => Expr::Inf,
+            EXPR_MORE => Expr::More,
+            EXPR_CONTINUE => Expr::Continue,
+            EXPR_BREAK => Expr::Break,
+            EXPR_ELLIPSIS => Expr::Ellipsis(self.opt_str()?),
+            EXPR_MISSING => Expr::Missing,
+            EXPR_BOOL => Expr::Bool(self.bool()?),
+            EXPR_NUMBER => Expr::Number(f64::from_le_bytes(self.array()?)),
+            EXPR_INTEGER => Expr::Integer(i32::from_le_bytes(self.array()?)),
+            EXPR_STRING => Expr::String(self.str()?),
+            EXPR_SYMBOL => Expr::Symbol(self.str()?),
+            EXPR_LIST => Expr::List(self.exprs()?),
+            EXPR_FUNCTION => Expr::Function(
+                self.exprs()?,
+                Box::new(self.expr()?),
+                Box::new(self.signature()?),
+            ),
+            EXPR_CALL => Expr::Call(Box::new(self.expr()?), self.exprs()?),
+            EXPR_BUILTIN => {
+                let sym = self.str()?;
+                let builtin = <Box<dyn Builtin>>::try_from(sym.as_str())
+                    .map_err(|_| Error::Other(format!("unknown primitive '{sym}'")))?;
+                Expr::Primitive(builtin)
+            }
+            EXPR_KEYWORD => {
+                let name = self.str()?;
+                let (_, keyword) = keywords()
+                    .into_iter()
+                    .find(|(k, _)| *k == name)
+                    .ok_or_else(|| Error::Other(format!("unknown primitive '{name}'")))?;
+                Expr::Primitive(keyword)
+            }
+            EXPR_BLOCK => {
+                let file = self.opt_str()?;
+                let n = self.u32()?;
+                let lines = (0..n).map(|_| self.u32()).collect::<Result<_, _>>()?;
+                Expr::Primitive(Box::new(KeywordBlock {
+                    srcref: Srcref { file, lines },
+                }))
+            }
+            _ => return Err(malformed()),
+        };
+
+        Ok(expr)
+    }
+}
+
+impl Snapshot {
+    /// Write the snapshot in the binary serialization format
+    ///
+    /// Fails for objects holding primitives which cannot be recreated by
+    /// name, such as partially applied functions, and for external objects
+    /// such as futures and connections.
+    pub fn serialize(&self) -> Result<Vec<u8>, Error> {
+        let mut w = Writer::default();
+        w.bytes.extend(MAGIC);
+        w.u8(VERSION);
+
+        w.u32(self.envs.len());
+        for env in &self.envs {
+            w.env(&env.parent);
+            w.u32(env.values.len());
+            for (k, v) in &env.values {
+                w.str(k);
+                w.value(v)?;
+            }
+        }
+
+        w.value(&self.value)?;
+        Ok(w.bytes)
+    }
+
+    /// Read a snapshot from the binary serialization format
+    pub fn unserialize(bytes: &[u8]) -> Result<Snapshot, Error> {
+        let mut r = Reader { bytes, pos: 0 };
+        if r.take(MAGIC.len()).ok() != Some(MAGIC) {
+            return Err(Error::Other("unknown serialization format".to_string()));
+        }
+
+        match r.u8()? {
+            VERSION => (),
+            v => {
+                return Err(Error::Other(format!(
+                    "cannot read serialization format version {v}"
+                )))
+            }
+        }
+
+        let n = r.u32()?;
+        let mut envs = vec![];
+        for _ in 0..n {
+            let parent = r.env(n)?;
+            let len = r.u32()?;
+            let mut values = vec![];
+            for _ in 0..len {
+                values.push((r.str()?, r.value(n)?));
+            }
+            envs.push(EnvValues { parent, values });
+        }
+
+        let value = r.value(n)?;
+        if r.pos != bytes.len() {
+            return Err(malformed());
+        }
+
+        Ok(Snapshot { value, envs })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use super::Snapshot;
+    use crate::context::Context;
+    use crate::lang::CallStack;
+    use crate::object::*;
+    use crate::parser::{Localization, LocalizedParser};
+
+    fn eval_in(env: Rc<Environment>, input: &str) -> Obj {
+        let expr = Localization::En.parse_input(input).unwrap();
+        CallStack::from(env).eval_and_finalize(expr).unwrap()
+    }
+
+    fn global() -> Rc<Environment> {
+        Rc::new(Environment {
+            parent: Some(Environment::from_builtins()),
+            ..Default::default()
+        })
+    }
+
+    fn round_trip(x: &Obj) -> Obj {
+        let bytes = Snapshot::from(x).serialize().unwrap();
+        Snapshot::unserialize(&bytes)
+            .unwrap()
+            .restore(Environment::from_builtins())
+    }
+
+    #[test]
+    fn vectors_and_lists_round_trip() {
+        let x = eval_in(
+            global(),
+            "list(a = c(1.5, NA, 3), b = c(1L, NA), c = c(TRUE, NA, FALSE), d = c('x', NA), NULL)",
+        );
+
+        assert_eq!(round_trip(&x), x);
+    }
+
+    #[test]
+    fn attributes_round_trip() {
+        let x = eval_in(
+            global(),
+            "list(as.Date('2024-03-05'), as.POSIXct('2024-03-05 12:00', tz = 'UTC'))",
+        );
+
+        assert_eq!(round_trip(&x), x);
+    }
+
+    #[test]
+    fn expressions_round_trip() {
+        let x = eval_in(
+            global(),
+            "quote({ for (i in 1:3) if (i > 1) x[[i]] <- -i else break; -x ~ y })",
+        );
+
+        assert_eq!(round_trip(&x), x);
+    }
+
+    #[test]
+    fn recursive_closures_round_trip() {
+        let f = eval_in(
+            global(),
+            "fib <- function(x: numeric) -> numeric if (x < 2) x else fib(x - 1) + fib(x - 2); fib",
+        );
+
+        let env = global();
+        env.insert("f".to_string(), round_trip(&f));
+        let result = eval_in(env, "f(10)");
+        assert_eq!(result, Obj::Vector(Vector::from(vec![55.0])));
+    }
+
+    #[test]
+    fn malformed_input_is_an_error() {
+        let bytes = Snapshot::from(&Obj::Null).serialize().unwrap();
+        assert!(Snapshot::unserialize(&bytes[..4]).is_err());
+        assert!(Snapshot::unserialize(b"nope").is_err());
+
+        let mut newer = bytes.clone();
+        newer[4] = 2;
+        assert!(Snapshot::unserialize(&newer).is_err());
+    }
+}
@@ -0,0 +1,391 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::attributes::Attributes;
+use super::*;
+
+/// An environment referenced by a snapshot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum EnvRef {
+    /// The base environment of builtins, which every interpreter creates for
+    /// itself. It is never copied and instead refers to the base environment
+    /// of the interpreter restoring the snapshot.
+    Base,
+    /// The global environment, when captured by reference. As in R, it
+    /// refers to the global environment of the session restoring the
+    /// snapshot, rather than copying the workspace along with the object.
+    Global,
+    /// An environment copied into the snapshot, by its index
+    Captured(usize),
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Value {
+    Null,
+    Numeric(Vec<OptionNA<f64>>),
+    Integer(Vec<OptionNA<i32>>),
+    Logical(Vec<OptionNA<bool>>),
+    Character(Vec<OptionNA<String>>),
+    List(Vec<(Option<String>, Value)>),
+    Expr(Expr),
+    Closure(Expr, EnvRef),
+    Function(ExprList, Expr, EnvRef, Box<Signature>),
+    Environment(EnvRef),
+    Formula(Expr, EnvRef),
+    Attributed(Box<Value>, Attributes),
+    /// An external object, by its classes
+    External(Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct EnvValues {
+    pub(super) parent: EnvRef,
+    pub(super) values: Vec<(String, Value)>,
+}
+
+/// A deep copy of an object, including any environments it captures
+///
+/// Objects share their data and environments using `Rc`s, and can only be
+/// used from the thread that created them. A snapshot holds no references,
+/// such that it can be sent to another thread and restored as an object in
+/// the interpreter running there. Environments shared between objects, or
+/// referencing themselves (as recursive functions do), are copied once and
+/// remain shared when restored. External objects refer to resources of the
+/// interpreter holding them, and are restored as `NULL` (and can't be
+/// serialized).
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub(super) value: Value,
+    pub(super) envs: Vec<EnvValues>,
+}
+
+#[derive(Default)]
+struct Capture {
+    seen: HashMap<*const Environment, usize>,
+    envs: Vec<Option<EnvValues>>,
+    // an environment of which only some bindings are captured
+    partial: Option<(*const Environment, Vec<String>)>,
+    // the global environment, if captured by reference
+    global: Option<*const Environment>,
+}
+
+impl Capture {
+    fn env(&mut self, env: &Rc<Environment>) -> EnvRef {
+        let Some(parent) = env.parent.as_ref() else {
+            return EnvRef::Base;
+        };
+
+        if self.global == Some(Rc::as_ptr(env)) {
+            return EnvRef::Global;
+        }
+
+        if let Some(&i) = self.seen.get(&Rc::as_ptr(env)) {
+            return EnvRef::Captured(i);
+        }
+
+        // reserve an index before capturing values, which may refer back to
+        // this environment
+        let i = self.envs.len();
+        self.seen.insert(Rc::as_ptr(env), i);
+        self.envs.push(None);
+
+        let parent = self.env(parent);
+        let names = match &self.partial {
+            Some((partial, names)) if *partial == Rc::as_ptr(env) => Some(names),
+            _ => None,
+        };
+
+        // bindings are ordered by name, such that snapshots are reproducible
+        let mut bindings: Vec<(String, Obj)> = env
+            .values
+            .borrow()
+            .iter()
+            .filter(|(k, _)| names.is_none_or(|names| names.contains(k)))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        bindings.sort_by(|(l, _), (r, _)| l.cmp(r));
+        let values = bindings
+            .into_iter()
+            .map(|(k, v)| (k, self.value(&v)))
+            .collect();
+
+        self.envs[i] = Some(EnvValues { parent, values });
+        EnvRef::Captured(i)
+    }
+
+    fn value(&mut self, obj: &Obj) -> Value {
+        match obj {
+            Obj::Null => Value::Null,
+            Obj::Vector(v) => {
+                let value = match v {
+                    Vector::Numeric(x) => Value::Numeric(x.inner().borrow().clone()),
+                    Vector::Integer(x) => Value::Integer(x.inner().borrow().clone()),
+                    Vector::Logical(x) => Value::Logical(x.inner().borrow().clone()),
+                    Vector::Character(x) => Value::Character(x.inner().borrow().clone()),
+                };
+
+                match v.attributes() {
+                    attrs if attrs.is_empty() => value,
+                    attrs => Value::Attributed(Box::new(value), attrs.clone()),
+                }
+            }
+            Obj::List(l) => {
+                let value = Value::List(
+                    l.elements()
+                        .iter()
+                        .map(|(k, v)| (k.clone(), self.value(v)))
+                        .collect(),
+                );
+
+                match &l.attributes {
+                    attrs if attrs.is_empty() => value,
+                    attrs => Value::Attributed(Box::new(value), attrs.clone()),
+                }
+            }
+            Obj::Expr(expr) => Value::Expr(expr.clone()),
+            Obj::Closure(expr, env) => Value::Closure(expr.clone(), self.env(env)),
+            Obj::Function(formals, body, env, sig) => {
+                Value::Function(formals.clone(), body.clone(), self.env(env), sig.clone())
+            }
+            Obj::Environment(env) => Value::Environment(self.env(env)),
+            Obj::Formula(expr, env) => Value::Formula(expr.clone(), self.env(env)),
+            // resources are held by the interpreter that created them
+            Obj::External(x) => Value::External(x.class()),
+        }
+    }
+}
+
+struct Restore<'a> {
+    base: Rc<Environment>,
+    global: Rc<Environment>,
+    envs: &'a [EnvValues],
+    restored: Vec<Option<Rc<Environment>>>,
+}
+
+impl Restore<'_> {
+    // environments are created before any values are restored, as parents
+    // must be known when an environment is created
+    fn create(&mut self, env: EnvRef) -> Rc<Environment> {
+        let i = match env {
+            EnvRef::Base => return self.base.clone(),
+            EnvRef::Global => return self.global.clone(),
+            EnvRef::Captured(i) => i,
+        };
+
+        if let Some(env) = &self.restored[i] {
+            return env.clone();
+        }
+
+        let parent = self.create(self.envs[i].parent);
+        let env = Rc::new(Environment {
+            parent: Some(parent),
+            ..Default::default()
+        });
+
+        self.restored[i] = Some(env.clone());
+        env
+    }
+
+    fn env(&self, env: EnvRef) -> Rc<Environment> {
+        match env {
+            EnvRef::Captured(i) => self.restored[i]
+                .clone()
+                .unwrap_or_else(|| self.base.clone()),
+            EnvRef::Global => self.global.clone(),
+            EnvRef::Base => self.base.clone(),
+        }
+    }
+
+    fn value(&self, value: &Value) -> Obj {
+        match value {
+            Value::Null => Obj::Null,
+            Value::Numeric(x) => Obj::Vector(Vector::from(x.clone())),
+            Value::Integer(x) => Obj::Vector(Vector::from(x.clone())),
+            Value::Logical(x) => Obj::Vector(Vector::from(x.clone())),
+            Value::Character(x) => Obj::Vector(Vector::from(x.clone())),
+            Value::List(x) => Obj::List(List::from(
+                x.iter()
+                    .map(|(k, v)| (k.clone(), self.value(v)))
+                    .collect::<Vec<_>>(),
+            )),
+            Value::Expr(expr) => Obj::Expr(expr.clone()),
+            Value::Closure(expr, env) => Obj::Closure(expr.clone(), self.env(*env)),
+            Value::Function(formals, body, env, sig) => {
+                Obj::Function(formals.clone(), body.clone(), self.env(*env), sig.clone())
+            }
+            Value::Environment(env) => Obj::Environment(self.env(*env)),
+            Value::Formula(expr, env) => Obj::Formula(expr.clone(), self.env(*env)),
+            Value::External(_) => Obj::Null,
+            Value::Attributed(value, attrs) => match self.value(value) {
+                Obj::Vector(v) => Obj::Vector(v.with_attributes(attrs.clone())),
+                Obj::List(l) => Obj::List(List {
+                    attributes: attrs.clone(),
+                    ..l
+                }),
+                obj => obj,
+            },
+        }
+    }
+}
+
+impl Snapshot {
+    /// Capture some of the bindings of an environment
+    ///
+    /// The environment is captured as the value of the snapshot, holding
+    /// only the named bindings. Its bindings are restored into another
+    /// environment using `restore_into`, which also takes its place for any
+    /// closures that capture it.
+    pub fn from_bindings(env: &Rc<Environment>, names: &[String]) -> Self {
+        let mut capture = Capture {
+            partial: Some((Rc::as_ptr(env), names.to_vec())),
+            ..Default::default()
+        };
+
+        let value = capture.value(&Obj::Environment(env.clone()));
+        let envs = capture.envs.into_iter().flatten().collect();
+        Snapshot { value, envs }
+    }
+
+    /// Restore the bindings captured by `from_bindings` into an environment,
+    /// returning their names
+    pub fn restore_into(&self, env: Rc<Environment>) -> Option<Vec<String>> {
+        let Value::Environment(EnvRef::Captured(i)) = self.value else {
+            return None;
+        };
+
+        let mut restored = vec![None; self.envs.len()];
+        restored[i] = Some(env.clone());
+        self.restore_with(env.base(), env.clone(), restored);
+
+        let names = self.envs[i].values.iter().map(|(k, _)| k.clone());
+        Some(names.collect())
+    }
+
+    /// Capture an object, referring to the global environment rather than
+    /// copying it
+    ///
+    /// Objects such as top-level closures and formulas capture the global
+    /// environment. Restoring them using `restore_global` binds them to the
+    /// global environment of the restoring session instead.
+    pub fn with_global(obj: &Obj, global: &Rc<Environment>) -> Self {
+        let mut capture = Capture {
+            global: Some(Rc::as_ptr(global)),
+            ..Default::default()
+        };
+
+        let value = capture.value(obj);
+        let envs = capture.envs.into_iter().flatten().collect();
+        Snapshot { value, envs }
+    }
+
+    /// Restore the object, with environments re-created as children of the
+    /// given base environment
+    pub fn restore(&self, base: Rc<Environment>) -> Obj {
+        self.restore_with(base.clone(), base, vec![None; self.envs.len()])
+    }
+
+    /// Restore the object, with references to the global environment bound
+    /// to the given global environment
+    pub fn restore_global(&self, global: Rc<Environment>) -> Obj {
+        self.restore_with(global.base(), global, vec![None; self.envs.len()])
+    }
+
+    fn restore_with(
+        &self,
+        base: Rc<Environment>,
+        global: Rc<Environment>,
+        restored: Vec<Option<Rc<Environment>>>,
+    ) -> Obj {
+        let mut restore = Restore {
+            base,
+            global,
+            envs: &self.envs,
+            restored,
+        };
+
+        for i in 0..self.envs.len() {
+            restore.create(EnvRef::Captured(i));
+        }
+
+        for (i, env) in self.envs.iter().enumerate() {
+            let target = restore.env(EnvRef::Captured(i));
+            for (k, v) in env.values.iter() {
+                target.insert(k.clone(), restore.value(v));
+            }
+        }
+
+        restore.value(&self.value)
+    }
+}
+
+impl From<&Obj> for Snapshot {
+    fn from(obj: &Obj) -> Self {
+        let mut capture = Capture::default();
+        let value = capture.value(obj);
+        let envs = capture.envs.into_iter().flatten().collect();
+        Snapshot { value, envs }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use super::Snapshot;
+    use crate::context::Context;
+    use crate::lang::CallStack;
+    use crate::object::*;
+    use crate::parser::{Localization, LocalizedParser};
+
+    fn eval_in(env: Rc<Environment>, input: &str) -> Obj {
+        let expr = Localization::En.parse_input(input).unwrap();
+        CallStack::from(env).eval_and_finalize(expr).unwrap()
+    }
+
+    fn global() -> Rc<Environment> {
+        Rc::new(Environment {
+            parent: Some(Environment::from_builtins()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn snapshots_can_be_sent_between_threads() {
+        let f = eval_in(
+            global(),
+            "fib <- function(x) if (x < 2) x else fib(x - 1) + fib(x - 2); fib",
+        );
+
+        let snapshot = Snapshot::from(&f);
+        let result = std::thread::spawn(move || {
+            let global = global();
+            let f = snapshot.restore(global.parent.clone().unwrap());
+            global.insert("f".to_string(), f);
+            Snapshot::from(&eval_in(global, "f(10)"))
+        })
+        .join()
+        .unwrap();
+
+        let result = result.restore(global());
+        assert_eq!(result, Obj::Vector(Vector::from(vec![55.0])));
+    }
+
+    #[test]
+    fn shared_environments_remain_shared() {
+        let x = eval_in(
+            global(),
+            "n <- 1; list(get = function() n, set = function(x) n <<- x)",
+        );
+
+        let restored = Snapshot::from(&x).restore(Environment::from_builtins());
+        let env = Rc::new(Environment {
+            parent: Some(Environment::from_builtins()),
+            ..Default::default()
+        });
+
+        env.insert("x".to_string(), restored);
+        let result = eval_in(env, "set <- x$set; get <- x$get; set(3); get()");
+        assert_eq!(result, Obj::Vector(Vector::from(vec![3.0])));
+    }
+}
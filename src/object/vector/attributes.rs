@@ -0,0 +1,129 @@
+/// Vector Attributes
+///
+/// Vectors may carry named attributes such as their `class`. Unlike R,
+/// attribute values are limited to character vectors, which is enough to
+/// describe classed vectors such as dates (`class = "Date"`), date-times
+/// (`class = c("POSIXct", "POSIXt")`, `tzone = "UTC"`) and time differences
+/// (`class = "difftime"`, `units = "days"`). Vectors are named by their
+/// `names` attribute, with unnamed elements given empty names.
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Attributes(Vec<(String, Vec<String>)>);
+
+/// Arithmetic operators, as they determine the attributes of their result
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arith {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+impl Attributes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(String, Vec<String>)> {
+        self.0.iter()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&[String]> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_slice())
+    }
+
+    /// Set an attribute, removing it when given no values
+    pub fn set(&mut self, name: &str, value: Vec<String>) {
+        self.0.retain(|(k, _)| k != name);
+        if !value.is_empty() {
+            self.0.push((name.to_string(), value));
+        }
+    }
+
+    pub fn with(mut self, name: &str, value: Vec<String>) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    /// Attributes of a subset of a vector, given the index of each element
+    /// of the subset, naming elements by the names at those indices
+    pub fn subset_names(&self, indices: impl IntoIterator<Item = Option<usize>>) -> Attributes {
+        let Some(names) = self.get("names") else {
+            return self.clone();
+        };
+
+        let names = indices
+            .into_iter()
+            .map(|i| i.and_then(|i| names.get(i)).cloned().unwrap_or_default())
+            .collect();
+
+        self.clone().with("names", names)
+    }
+
+    pub fn class(&self) -> Option<&[String]> {
+        self.get("class")
+    }
+
+    pub fn inherits(&self, class: &str) -> bool {
+        self.class().is_some_and(|c| c.iter().any(|ci| ci == class))
+    }
+
+    /// Attributes of the result of an arithmetic operator
+    ///
+    /// Attributes are taken from the left operand, or the right if the left
+    /// has none. The difference between two dates or date-times is a time
+    /// difference, and the ratio of two time differences is unitless.
+    pub fn arith(op: Arith, lhs: &Attributes, rhs: &Attributes) -> Attributes {
+        let difftime = |units: &str| {
+            Attributes::new()
+                .with("class", vec!["difftime".to_string()])
+                .with("units", vec![units.to_string()])
+        };
+
+        match op {
+            Arith::Sub if lhs.inherits("Date") && rhs.inherits("Date") => difftime("days"),
+            Arith::Sub if lhs.inherits("POSIXct") && rhs.inherits("POSIXct") => difftime("secs"),
+            Arith::Div if lhs.inherits("difftime") && rhs.inherits("difftime") => Attributes::new(),
+            _ if lhs.is_empty() => rhs.clone(),
+            _ => lhs.clone(),
+        }
+    }
+}
+
+impl From<Vec<(String, Vec<String>)>> for Attributes {
+    fn from(value: Vec<(String, Vec<String>)>) -> Self {
+        value
+            .into_iter()
+            .fold(Attributes::new(), |attrs, (k, v)| attrs.with(&k, v))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn class(x: &[&str]) -> Attributes {
+        Attributes::new().with("class", x.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn attributes_are_taken_from_operands() {
+        let date = class(&["Date"]);
+        let none = Attributes::new();
+        assert_eq!(Attributes::arith(Arith::Add, &date, &none), date);
+        assert_eq!(Attributes::arith(Arith::Add, &none, &date), date);
+        assert!(Attributes::arith(Arith::Sub, &date, &date).inherits("difftime"));
+
+        let difftime = Attributes::arith(Arith::Sub, &date, &date);
+        assert_eq!(Attributes::arith(Arith::Div, &difftime, &difftime), none);
+    }
+}
@@ -5,6 +5,7 @@ use crate::error::Error;
 use crate::lang::EvalResult;
 use crate::object::Obj;
 
+use super::attributes::Attributes;
 use super::coercion::CoercibleInto;
 use super::rep::Rep;
 use super::subset::Subset;
@@ -210,6 +211,30 @@ impl Vector {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    pub fn attributes(&self) -> &Attributes {
+        use Vector::*;
+        match self {
+            Numeric(v) => v.attributes(),
+            Integer(v) => v.attributes(),
+            Logical(v) => v.attributes(),
+            Character(v) => v.attributes(),
+        }
+    }
+
+    pub fn with_attributes(self, attrs: Attributes) -> Self {
+        use Vector::*;
+        match self {
+            Numeric(v) => Numeric(v.with_attributes(attrs)),
+            Integer(v) => Integer(v.with_attributes(attrs)),
+            Logical(v) => Logical(v.with_attributes(attrs)),
+            Character(v) => Character(v.with_attributes(attrs)),
+        }
+    }
+
+    pub fn inherits(&self, class: &str) -> bool {
+        self.attributes().inherits(class)
+    }
 }
 
 impl TryInto<bool> for Vector {
@@ -331,19 +356,39 @@ impl From<Vec<OptionNA<String>>> for Vector {
     }
 }
 
-pub trait DefaultDebug {}
+pub trait DefaultDebug: Display {
+    // format using a number of significant digits, where meaningful
+    fn fmt_digits(&self, _digits: usize) -> String {
+        self.to_string()
+    }
+}
+
 impl DefaultDebug for bool {}
 impl DefaultDebug for i32 {}
-impl DefaultDebug for f64 {}
+impl DefaultDebug for f64 {
+    fn fmt_digits(&self, digits: usize) -> String {
+        if !self.is_finite() || *self == 0.0 {
+            return self.to_string();
+        }
 
+        // round to significant digits, then print the shortest representation
+        let magnitude = self.abs().log10().floor() as i32;
+        let decimals = (digits as i32 - 1 - magnitude).clamp(0, 22) as usize;
+        let rounded = format!("{:.*}", decimals, self);
+        rounded.parse::<f64>().unwrap_or(*self).to_string()
+    }
+}
+
+// formatting precision, as in `{:.7?}`, is used as significant digits
 impl<T> Debug for OptionNA<T>
 where
-    T: DefaultDebug + Display,
+    T: DefaultDebug,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            OptionNA::Some(x) => write!(f, "{}", x),
-            OptionNA::NA => write!(f, "NA"),
+        match (self, f.precision()) {
+            (OptionNA::Some(x), Some(digits)) => write!(f, "{}", x.fmt_digits(digits)),
+            (OptionNA::Some(x), None) => write!(f, "{}", x),
+            (OptionNA::NA, _) => write!(f, "NA"),
         }
     }
 }
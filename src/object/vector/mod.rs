@@ -4,6 +4,7 @@
 /// coercion is handled within rust's type system. It is pretty faithful to
 /// R's vector types, but there is room for improvement.
 ///
+pub mod attributes;
 pub mod coercion;
 pub mod iterators;
 pub mod rep;
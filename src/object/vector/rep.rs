@@ -2,18 +2,21 @@ use std::cell::RefCell;
 use std::fmt::{Debug, Display};
 use std::rc::Rc;
 
+use super::attributes::{Arith, Attributes};
 use super::coercion::{AtomicMode, CoercibleInto, CommonCmp, CommonNum, MinimallyNumeric};
 use super::iterators::{map_common_numeric, zip_recycle};
 use super::subset::Subset;
 use super::subsets::Subsets;
 use super::types::*;
 use super::{OptionNA, Pow, VecPartialCmp};
+use crate::options;
 
 /// Vector
 #[derive(Debug, Clone, PartialEq)]
 pub enum Rep<T> {
-    // Vector::Subset encompasses a "raw" vector (no subsetting)
-    Subset(Rc<RefCell<Vec<T>>>, Subsets),
+    // Vector::Subset encompasses a "raw" vector (no subsetting), along with
+    // any attributes of the vector
+    Subset(Rc<RefCell<Vec<T>>>, Subsets, Attributes),
     // Iterator includes things like ranges 1:Inf, and lazily computed values
     // Iter(Box<dyn Iterator<Item = &T>>)
 }
@@ -44,13 +47,17 @@ impl<T: AtomicMode + Clone + Default> Rep<T> {
     /// ```
     ///
     pub fn new() -> Self {
-        Rep::Subset(Rc::new(RefCell::new(Vec::new())), Subsets(Vec::new()))
+        Rep::Subset(
+            Rc::new(RefCell::new(Vec::new())),
+            Subsets(Vec::new()),
+            Attributes::new(),
+        )
     }
 
     /// Access the internal vector
     pub fn inner(&self) -> Rc<RefCell<Vec<T>>> {
         match self.materialize() {
-            Rep::Subset(v, _) => v.clone(),
+            Rep::Subset(v, ..) => v.clone(),
         }
     }
 
@@ -60,17 +67,17 @@ impl<T: AtomicMode + Clone + Default> Rep<T> {
     ///
     pub fn subset(&self, subset: Subset) -> Self {
         match self {
-            Rep::Subset(v, Subsets(subsets)) => {
+            Rep::Subset(v, Subsets(subsets), attrs) => {
                 let mut subsets = subsets.clone();
                 subsets.push(subset);
-                Rep::Subset(v.clone(), Subsets(subsets))
+                Rep::Subset(v.clone(), Subsets(subsets), attrs.clone())
             }
         }
     }
 
     pub fn len(&self) -> usize {
         match self {
-            Rep::Subset(v, Subsets(s)) => match s.as_slice() {
+            Rep::Subset(v, Subsets(s), _) => match s.as_slice() {
                 [] => v.clone().borrow().len(),
                 [.., last] => std::cmp::min(v.clone().borrow().len(), last.len()),
             },
@@ -91,7 +98,7 @@ impl<T: AtomicMode + Clone + Default> Rep<T> {
         T: Clone,
     {
         match self {
-            Rep::Subset(v, subsets) => {
+            Rep::Subset(v, subsets, attrs) => {
                 let vc = v.clone();
                 let vb = vc.borrow();
                 let index = subsets.get_index_at(index)?;
@@ -99,6 +106,7 @@ impl<T: AtomicMode + Clone + Default> Rep<T> {
                 Some(Rep::Subset(
                     Rc::new(RefCell::new(vec![elem.clone()])),
                     Subsets::new(),
+                    attrs.subset_names([Some(index)]),
                 ))
             }
         }
@@ -114,7 +122,7 @@ impl<T: AtomicMode + Clone + Default> Rep<T> {
         T: Clone + Default,
     {
         match (self, value) {
-            (Rep::Subset(lv, ls), Rep::Subset(rv, rs)) => {
+            (Rep::Subset(lv, ls, attrs), Rep::Subset(rv, rs, _)) => {
                 let lvc = lv.clone();
                 let mut lvb = lvc.borrow_mut();
                 let rvc = rv.clone();
@@ -132,7 +140,7 @@ impl<T: AtomicMode + Clone + Default> Rep<T> {
                     }
                 }
 
-                Rep::Subset(lvc.clone(), ls.clone())
+                Rep::Subset(lvc.clone(), ls.clone(), attrs.clone())
             }
         }
     }
@@ -146,21 +154,24 @@ impl<T: AtomicMode + Clone + Default> Rep<T> {
         T: Clone,
     {
         match self {
-            Rep::Subset(v, subsets) => {
+            Rep::Subset(v, subsets, attrs) => {
                 let vc = v.clone();
                 let vb = vc.borrow();
                 let mut res: Vec<T> = vec![];
                 let vb_len = vb.len();
 
+                let mut indices = vec![];
                 let iter = subsets.clone().into_iter().take_while(|(i, _)| i < &vb_len);
                 for (_, i) in iter {
                     match i {
                         Some(i) => res.push(vb[i].clone()),
                         None => res.push(T::default()),
                     }
+                    indices.push(i);
                 }
 
-                Rep::Subset(Rc::new(RefCell::new(res)), Subsets(vec![]))
+                let attrs = attrs.subset_names(indices);
+                Rep::Subset(Rc::new(RefCell::new(res)), Subsets(vec![]), attrs)
             }
         }
     }
@@ -216,15 +227,22 @@ impl<T: AtomicMode + Clone + Default> Rep<T> {
     pub fn as_mode<Mode>(&self) -> Rep<Mode>
     where
         T: CoercibleInto<Mode>,
+        Mode: AtomicMode,
     {
         match self {
-            Rep::Subset(v, subsets) => {
+            Rep::Subset(v, subsets, attrs) => {
                 let vc = v.clone();
                 let vb = vc.borrow();
 
                 let num_vec: Vec<Mode> = vb.iter().map(|i| (*i).clone().coerce_into()).collect();
 
-                Rep::Subset(Rc::new(RefCell::new(num_vec)), subsets.clone())
+                // as with implicit coercion in R, attributes such as names
+                // are kept when the mode changes
+                Rep::Subset(
+                    Rc::new(RefCell::new(num_vec)),
+                    subsets.clone(),
+                    attrs.clone(),
+                )
             }
         }
     }
@@ -296,7 +314,7 @@ impl<T: AtomicMode + Clone + Default> Rep<T> {
 
     fn get_inner(&self, index: usize) -> Option<T> {
         match self {
-            Rep::Subset(v, subsets) => {
+            Rep::Subset(v, subsets, _) => {
                 let vc = v.clone();
                 let vb = vc.borrow();
                 let index = subsets.get_index_at(index)?;
@@ -306,6 +324,32 @@ impl<T: AtomicMode + Clone + Default> Rep<T> {
     }
 }
 
+impl<T> Rep<T> {
+    /// The attributes of a vector
+    pub fn attributes(&self) -> &Attributes {
+        match self {
+            Rep::Subset(.., attrs) => attrs,
+        }
+    }
+
+    /// Replace the attributes of a vector
+    ///
+    /// Names are dropped when they do not name each element, as when taken
+    /// from an operand recycled to a longer length.
+    pub fn with_attributes(self, mut attrs: Attributes) -> Self {
+        match self {
+            Rep::Subset(v, subsets, _) => {
+                let len = v.borrow().len();
+                if attrs.get("names").is_some_and(|names| names.len() != len) {
+                    attrs.set("names", vec![]);
+                }
+
+                Rep::Subset(v, subsets, attrs)
+            }
+        }
+    }
+}
+
 impl<T> TryInto<bool> for Rep<OptionNA<T>>
 where
     OptionNA<T>: AtomicMode + Clone + CoercibleInto<OptionNA<bool>>,
@@ -325,56 +369,88 @@ where
 impl From<Vec<OptionNA<f64>>> for Rep<Numeric> {
     fn from(value: Vec<OptionNA<f64>>) -> Self {
         let value: Vec<_> = value.into_iter().map(|i| i.coerce_into()).collect();
-        Rep::Subset(Rc::new(RefCell::new(value)), Subsets(Vec::new()))
+        Rep::Subset(
+            Rc::new(RefCell::new(value)),
+            Subsets(Vec::new()),
+            Attributes::new(),
+        )
     }
 }
 
 impl From<Vec<f64>> for Rep<Numeric> {
     fn from(value: Vec<f64>) -> Self {
         let value: Vec<_> = value.into_iter().map(|i| i.coerce_into()).collect();
-        Rep::Subset(Rc::new(RefCell::new(value)), Subsets(Vec::new()))
+        Rep::Subset(
+            Rc::new(RefCell::new(value)),
+            Subsets(Vec::new()),
+            Attributes::new(),
+        )
     }
 }
 
 impl From<Vec<OptionNA<i32>>> for Rep<Integer> {
     fn from(value: Vec<OptionNA<i32>>) -> Self {
         let value: Vec<_> = value.into_iter().map(|i| i.coerce_into()).collect();
-        Rep::Subset(Rc::new(RefCell::new(value)), Subsets(Vec::new()))
+        Rep::Subset(
+            Rc::new(RefCell::new(value)),
+            Subsets(Vec::new()),
+            Attributes::new(),
+        )
     }
 }
 
 impl From<Vec<i32>> for Rep<Integer> {
     fn from(value: Vec<i32>) -> Self {
         let value: Vec<_> = value.into_iter().map(|i| i.coerce_into()).collect();
-        Rep::Subset(Rc::new(RefCell::new(value)), Subsets(Vec::new()))
+        Rep::Subset(
+            Rc::new(RefCell::new(value)),
+            Subsets(Vec::new()),
+            Attributes::new(),
+        )
     }
 }
 
 impl From<Vec<OptionNA<bool>>> for Rep<Logical> {
     fn from(value: Vec<OptionNA<bool>>) -> Self {
         let value: Vec<_> = value.into_iter().map(|i| i.coerce_into()).collect();
-        Rep::Subset(Rc::new(RefCell::new(value)), Subsets(Vec::new()))
+        Rep::Subset(
+            Rc::new(RefCell::new(value)),
+            Subsets(Vec::new()),
+            Attributes::new(),
+        )
     }
 }
 
 impl From<Vec<bool>> for Rep<Logical> {
     fn from(value: Vec<bool>) -> Self {
         let value: Vec<_> = value.into_iter().map(|i| i.coerce_into()).collect();
-        Rep::Subset(Rc::new(RefCell::new(value)), Subsets(Vec::new()))
+        Rep::Subset(
+            Rc::new(RefCell::new(value)),
+            Subsets(Vec::new()),
+            Attributes::new(),
+        )
     }
 }
 
 impl From<Vec<OptionNA<String>>> for Rep<Character> {
     fn from(value: Vec<OptionNA<String>>) -> Self {
         let value: Vec<_> = value.into_iter().map(|i| i.coerce_into()).collect();
-        Rep::Subset(Rc::new(RefCell::new(value)), Subsets(Vec::new()))
+        Rep::Subset(
+            Rc::new(RefCell::new(value)),
+            Subsets(Vec::new()),
+            Attributes::new(),
+        )
     }
 }
 
 impl From<Vec<String>> for Rep<Character> {
     fn from(value: Vec<String>) -> Self {
         let value: Vec<_> = value.into_iter().map(|i| i.coerce_into()).collect();
-        Rep::Subset(Rc::new(RefCell::new(value)), Subsets(Vec::new()))
+        Rep::Subset(
+            Rc::new(RefCell::new(value)),
+            Subsets(Vec::new()),
+            Attributes::new(),
+        )
     }
 }
 
@@ -384,7 +460,7 @@ where
 {
     fn from(value: (Vec<F>, Subsets)) -> Self {
         match Self::from(value.0) {
-            Rep::Subset(v, _) => Rep::Subset(v, value.1),
+            Rep::Subset(v, _, attrs) => Rep::Subset(v, value.1, attrs),
         }
     }
 }
@@ -410,6 +486,11 @@ where
             }
         }
 
+        let x = self.materialize();
+        if let Some(names) = x.attributes().get("names") {
+            return fmt_named(&x, names, f);
+        }
+
         let nlen = format!("{}", n).len();
         // TODO: iteratively calculate when we hit max print so our
         // max_len isn't inflated by a value that is omitted
@@ -417,7 +498,10 @@ where
         let xc = self.inner().clone();
         let xb = xc.borrow();
 
-        let x_strs = xb.iter().map(|xi| format!("{:?}", xi));
+        let digits = options::digits();
+        let width = options::width();
+
+        let x_strs = xb.iter().map(|xi| format!("{:.*?}", digits, xi));
         let max_len = x_strs
             .clone()
             .fold(0, |max_len, xi| std::cmp::max(max_len, xi.len()));
@@ -425,8 +509,8 @@ where
         let mut col = 0;
         let gutterlen = 2 + nlen + 1;
 
-        // hard coded max print & console width
-        let maxprint = 20 * ((80 - gutterlen) / max_len);
+        // max print is limited to 20 lines of console width
+        let maxprint = 20 * (width.saturating_sub(gutterlen) / max_len).max(1);
 
         x_strs
             .take(maxprint)
@@ -435,7 +519,7 @@ where
                 if i == 0 {
                     col = gutterlen + max_len;
                     write!(f, "{:>3$}[{}] {:>4$}", "", i + 1, x_str, nlen - 1, max_len)
-                } else if col + 1 + max_len > 80 {
+                } else if col + 1 + max_len > width {
                     col = gutterlen + max_len;
                     let i_str = format!("{}", i + 1);
                     let gutter = nlen - i_str.len();
@@ -454,6 +538,43 @@ where
     }
 }
 
+/// Display a named vector, with each element beneath its name
+fn fmt_named<T>(x: &Rep<T>, names: &[String], f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+where
+    T: AtomicMode + Debug + Default + Clone,
+{
+    let digits = options::digits();
+    let xc = x.inner().clone();
+    let xb = xc.borrow();
+    let x_strs: Vec<String> = xb.iter().map(|xi| format!("{:.*?}", digits, xi)).collect();
+    let max_len = x_strs
+        .iter()
+        .chain(names)
+        .fold(0, |max_len, xi| std::cmp::max(max_len, xi.len()));
+
+    let per_line = (options::width() / (max_len + 1)).max(1);
+    for (i, (names, x_strs)) in names
+        .chunks(per_line)
+        .zip(x_strs.chunks(per_line))
+        .enumerate()
+    {
+        if i > 0 {
+            writeln!(f)?;
+        }
+
+        for name in names {
+            write!(f, "{name:>max_len$} ")?;
+        }
+
+        writeln!(f)?;
+        for x_str in x_strs {
+            write!(f, "{x_str:>max_len$} ")?;
+        }
+    }
+
+    Ok(())
+}
+
 impl<L, LNum, O> std::ops::Neg for Rep<L>
 where
     L: AtomicMode + Default + Clone + MinimallyNumeric<As = LNum> + CoercibleInto<LNum>,
@@ -462,6 +583,7 @@ where
 {
     type Output = Rep<O>;
     fn neg(self) -> Self::Output {
+        let attrs = self.attributes().clone();
         Rep::from(
             self.inner()
                 .clone()
@@ -470,6 +592,7 @@ where
                 .map(|l| CoercibleInto::<LNum>::coerce_into(l.clone()).neg())
                 .collect::<Vec<O>>(),
         )
+        .with_attributes(attrs)
     }
 }
 
@@ -483,6 +606,8 @@ where
 {
     type Output = Rep<C>;
     fn add(self, rhs: Rep<R>) -> Self::Output {
+        let attrs = Attributes::arith(Arith::Add, self.attributes(), rhs.attributes());
+
         let lc = self.inner().clone();
         let lb = lc.borrow();
         let lhs = lb.iter();
@@ -496,6 +621,7 @@ where
                 .map(|(l, r)| l + r)
                 .collect::<Vec<O>>(),
         )
+        .with_attributes(attrs)
     }
 }
 
@@ -509,6 +635,8 @@ where
 {
     type Output = Rep<C>;
     fn sub(self, rhs: Rep<R>) -> Self::Output {
+        let attrs = Attributes::arith(Arith::Sub, self.attributes(), rhs.attributes());
+
         let lc = self.inner().clone();
         let lb = lc.borrow();
         let lhs = lb.iter();
@@ -522,6 +650,7 @@ where
                 .map(|(l, r)| l - r)
                 .collect::<Vec<O>>(),
         )
+        .with_attributes(attrs)
     }
 }
 
@@ -535,6 +664,8 @@ where
 {
     type Output = Rep<C>;
     fn mul(self, rhs: Rep<R>) -> Self::Output {
+        let attrs = Attributes::arith(Arith::Mul, self.attributes(), rhs.attributes());
+
         let lc = self.inner().clone();
         let lb = lc.borrow();
         let lhs = lb.iter();
@@ -548,6 +679,7 @@ where
                 .map(|(l, r)| l * r)
                 .collect::<Vec<O>>(),
         )
+        .with_attributes(attrs)
     }
 }
 
@@ -561,6 +693,8 @@ where
 {
     type Output = Rep<C>;
     fn div(self, rhs: Rep<R>) -> Self::Output {
+        let attrs = Attributes::arith(Arith::Div, self.attributes(), rhs.attributes());
+
         let lc = self.inner().clone();
         let lb = lc.borrow();
         let lhs = lb.iter();
@@ -574,6 +708,7 @@ where
                 .map(|(l, r)| l / r)
                 .collect::<Vec<O>>(),
         )
+        .with_attributes(attrs)
     }
 }
 
@@ -587,6 +722,8 @@ where
 {
     type Output = Rep<C>;
     fn rem(self, rhs: Rep<R>) -> Self::Output {
+        let attrs = Attributes::arith(Arith::Rem, self.attributes(), rhs.attributes());
+
         let lc = self.inner().clone();
         let lb = lc.borrow();
         let lhs = lb.iter();
@@ -600,6 +737,7 @@ where
                 .map(|(l, r)| l.rem(r))
                 .collect::<Vec<O>>(),
         )
+        .with_attributes(attrs)
     }
 }
 
@@ -612,6 +750,8 @@ where
 {
     type Output = Rep<O>;
     fn power(self, rhs: Rep<R>) -> Self::Output {
+        let attrs = Attributes::arith(Arith::Pow, self.attributes(), rhs.attributes());
+
         let lc = self.inner().clone();
         let lb = lc.borrow();
         let lhs = lb.iter();
@@ -625,6 +765,7 @@ where
                 .map(|(l, r)| l.clone().coerce_into().power(r.clone().coerce_into()))
                 .collect::<Vec<O>>(),
         )
+        .with_attributes(attrs)
     }
 }
 
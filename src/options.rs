@@ -0,0 +1,111 @@
+/// Session Options
+///
+/// A registry of named settings shared throughout a session, set from R
+/// code using `options()` and retrieved using `getOption()`. Options are
+/// initialized with defaults for those consulted by the interpreter itself.
+///
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::object::{Obj, Vector};
+use crate::parser::Localization;
+use crate::trace;
+
+pub const DIGITS: &str = "digits";
+pub const WIDTH: &str = "width";
+pub const PROMPT: &str = "prompt";
+pub const CONTINUE: &str = "continue";
+pub const WARN: &str = "warn";
+pub const MC_CORES: &str = "mc.cores";
+pub const LOCALE: &str = "locale";
+
+fn defaults() -> HashMap<String, Obj> {
+    HashMap::from([
+        (DIGITS.to_string(), Obj::Vector(Vector::from(vec![7]))),
+        (WIDTH.to_string(), Obj::Vector(Vector::from(vec![80]))),
+        (
+            PROMPT.to_string(),
+            Obj::Vector(Vector::from(vec![String::from("> ")])),
+        ),
+        (
+            CONTINUE.to_string(),
+            Obj::Vector(Vector::from(vec![String::from(": ")])),
+        ),
+        (WARN.to_string(), Obj::Vector(Vector::from(vec![0]))),
+        (
+            LOCALE.to_string(),
+            Obj::Vector(Vector::from(vec![Localization::default().to_string()])),
+        ),
+    ])
+}
+
+thread_local! {
+    static OPTIONS: RefCell<HashMap<String, Obj>> = RefCell::new(defaults());
+}
+
+/// Get the value of an option, if set
+pub fn get(name: &str) -> Option<Obj> {
+    OPTIONS.with(|o| o.borrow().get(name).cloned())
+}
+
+/// Set the value of an option, returning its previous value
+///
+/// Setting an option to `NULL` removes it.
+pub fn set(name: &str, value: Obj) -> Option<Obj> {
+    let old = OPTIONS.with(|o| match value {
+        Obj::Null => o.borrow_mut().remove(name),
+        value => o.borrow_mut().insert(name.to_string(), value),
+    });
+
+    // options consulted on every call are mirrored where they are used
+    if name == trace::VERBOSE_CALLS {
+        trace::set_verbose_calls(is_true(name));
+    }
+
+    old
+}
+
+/// All options, ordered by name
+pub fn all() -> Vec<(String, Obj)> {
+    let mut options = OPTIONS.with(|o| {
+        o.borrow()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect::<Vec<_>>()
+    });
+
+    options.sort_by(|(l, _), (r, _)| l.cmp(r));
+    options
+}
+
+/// Test whether a logical option is set to `TRUE`
+pub fn is_true(name: &str) -> bool {
+    get(name).is_some_and(|value| value.try_into().unwrap_or(false))
+}
+
+/// Get an integer option, if set to a valid integer
+pub fn get_integer(name: &str) -> Option<i32> {
+    get(name).and_then(|value| value.try_into().ok())
+}
+
+/// Get a character option, if set to a valid string
+pub fn get_string(name: &str) -> Option<String> {
+    get(name).and_then(|value| value.try_into().ok())
+}
+
+/// Number of significant digits used when printing numbers
+pub fn digits() -> usize {
+    get_integer(DIGITS).map_or(7, |d| d.clamp(1, 22) as usize)
+}
+
+/// Console width used when printing vectors
+pub fn width() -> usize {
+    get_integer(WIDTH).map_or(80, |w| w.clamp(10, 10000) as usize)
+}
+
+/// The localization used to parse input, by its name such as `"es"`
+pub fn locale() -> Localization {
+    get_string(LOCALE)
+        .and_then(|l| l.parse().ok())
+        .unwrap_or_default()
+}
@@ -10,15 +10,42 @@ use crate::callable::{core::*, keywords::*, operators::*};
 use crate::error::Error;
 use crate::internal_err;
 use crate::lang::Signal;
-use crate::object::{Expr, ExprList};
+use crate::object::{Expr, ExprList, Signature};
 use crate::parser::*;
 use pest::iterators::{Pair, Pairs};
 use pest::pratt_parser::PrattParser;
 use pest::{Parser, RuleType};
+use std::cell::RefCell;
 
 pub type ParseResult = Result<Expr, Signal>;
 pub type ParseListResult = Result<ExprList, Signal>;
 
+thread_local! {
+    // file currently being parsed, recorded in block source references
+    static SRCFILE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Source references of the expressions within a block
+///
+/// Lines are recorded from the spans of each expression as it is parsed,
+/// alongside the file it was parsed from when parsed using `parse_file`.
+#[derive(Debug, Clone, Default)]
+pub struct Srcref {
+    pub file: Option<String>,
+    pub lines: Vec<usize>,
+}
+
+/// Parse the contents of a file, recording the file in source references
+pub fn parse_file<P>(parser: &P, file: &str, input: &str) -> ParseResult
+where
+    P: LocalizedParser,
+{
+    SRCFILE.with(|f| f.replace(Some(file.to_string())));
+    let result = parser.parse_input(input);
+    SRCFILE.with(|f| f.replace(None));
+    result
+}
+
 pub fn parse_expr<P, R>(parser: &P, pratt: &PrattParser<R>, pairs: Pairs<R>) -> ParseResult
 where
     P: Parser<R> + LocalizedParser,
@@ -27,8 +54,21 @@ where
     pratt
         .map_primary(|pair| parse_primary(parser, pratt, pair))
         .map_infix(|lhs, op, rhs| {
+            // right assignment is parsed as the equivalent left assignment
+            let (lhs, rhs, op) = match op.as_rule().into() {
+                en::Rule::rassign | en::Rule::rsuperassign => (rhs, lhs, op),
+                _ => (lhs, rhs, op),
+            };
+
             // infix operator with two unnamed arguments
             let args = vec![(None, lhs?), (None, rhs?)].into();
+
+            // special `%op%` operators are called by name, like any function
+            if let en::Rule::special = op.as_rule().into() {
+                let what = Expr::Symbol(op.as_str().to_string());
+                return Ok(Expr::Call(Box::new(what), args));
+            }
+
             let op: Box<dyn Builtin> = match op.as_rule().into() {
                 en::Rule::add => Box::new(InfixAdd),
                 en::Rule::subtract => Box::new(InfixSub),
@@ -38,7 +78,9 @@ where
                 en::Rule::power => Box::new(InfixPow),
                 en::Rule::colon => Box::new(InfixColon),
                 en::Rule::modulo => Box::new(InfixMod),
-                en::Rule::assign => Box::new(InfixAssign),
+                en::Rule::assign | en::Rule::rassign => Box::new(InfixAssign),
+                en::Rule::superassign | en::Rule::rsuperassign => Box::new(InfixSuperAssign),
+                en::Rule::eqassign => Box::new(InfixEqAssign),
                 en::Rule::or => Box::new(InfixOr),
                 en::Rule::and => Box::new(InfixAnd),
                 en::Rule::vor => Box::new(InfixVectorOr),
@@ -50,6 +92,7 @@ where
                 en::Rule::eq => Box::new(InfixEqual),
                 en::Rule::neq => Box::new(InfixNotEqual),
                 en::Rule::pipe => Box::new(InfixPipe),
+                en::Rule::tilde => Box::new(InfixTilde),
                 rule => return Err(Error::ParseUnexpected(rule).into()),
             };
 
@@ -74,6 +117,7 @@ where
 
         // keyworded composite expressions
         en::Rule::kw_function => parse_function(parser, pratt, pair),
+        en::Rule::formula => parse_formula(parser, pratt, pair),
         en::Rule::kw_while => parse_while(parser, pratt, pair),
         en::Rule::kw_for => parse_for(parser, pratt, pair),
         en::Rule::kw_if_else => parse_if_else(parser, pratt, pair),
@@ -81,6 +125,7 @@ where
         en::Rule::kw_break => Ok(Expr::Break),
         en::Rule::kw_continue => Ok(Expr::Continue),
         en::Rule::kw_return => parse_return(parser, pratt, pair),
+        en::Rule::kw_match => parse_match(parser, pratt, pair),
 
         // reserved values
         en::Rule::val_true => Ok(Expr::Bool(true)),
@@ -115,6 +160,7 @@ where
         // calls and symbols
         en::Rule::call => parse_call(parser, pratt, pair),
         en::Rule::symbol_ident => parse_symbol(parser, pratt, pair),
+        en::Rule::symbol_namespaced => parse_symbol(parser, pratt, pair),
         en::Rule::symbol_backticked => Ok(Expr::Symbol(String::from(pair.as_str()))),
 
         // otherwise fail
@@ -128,13 +174,20 @@ where
     R: RuleType + Into<en::Rule>,
 {
     // extract each inline expression, and treat as unnamed list
+    let mut lines = vec![];
     let exprs: ExprList = pair
         .into_inner()
-        .map(|i| parse_expr(parser, pratt, i.into_inner()))
+        .map(|i| {
+            lines.push(i.as_span().start_pos().line_col().0);
+            parse_expr(parser, pratt, i.into_inner())
+        })
         .collect::<Result<_, _>>()?;
 
+    let file = SRCFILE.with(|f| f.borrow().clone());
+    let srcref = Srcref { file, lines };
+
     // build call from symbol and list
-    Ok(Expr::new_primitive_call(KeywordBlock, exprs))
+    Ok(Expr::new_primitive_call(KeywordBlock { srcref }, exprs))
 }
 
 fn parse_named<P, R>(
@@ -148,6 +201,12 @@ where
 {
     let mut inner = pair.into_inner();
     let name = String::from(inner.next().unwrap().as_str());
+
+    // named arguments may be empty, as in `switch(x, a = , b = 1)`
+    if inner.peek().is_none() {
+        return Ok((Some(name), Expr::Missing));
+    }
+
     Ok((Some(name), parse_expr(parser, pratt, inner)?))
 }
 
@@ -189,10 +248,64 @@ where
     R: RuleType + Into<en::Rule>,
 {
     let mut inner = pair.into_inner();
-    let params =
-        parse_pairlist(parser, pratt, inner.next().map_or(internal_err!(), Ok)?)?.as_formals();
+    let mut sig = Signature::default();
+    let params = inner.next().map_or(internal_err!(), Ok)?;
+    let params: ExprList = params
+        .into_inner()
+        .map(|i| match i.as_rule().into() {
+            en::Rule::param_annotated => {
+                let (param, default) = parse_param_annotated(parser, pratt, i, &mut sig)?;
+                Ok((Some(param), default))
+            }
+            en::Rule::named => parse_named(parser, pratt, i),
+            en::Rule::ellipsis => Ok((None, Expr::Ellipsis(None))),
+            _ => Ok((None, parse_primary(parser, pratt, i)?)),
+        })
+        .collect::<Result<_, _>>()?;
+
+    if let Some(en::Rule::returns) = inner.peek().map(|i| i.as_rule().into()) {
+        let returns = inner.next().map_or(internal_err!(), Ok)?.into_inner();
+        sig.returns = Some(returns.as_str().to_string());
+    }
+
     let body = parse_expr(parser, pratt, inner)?;
-    Ok(Expr::Function(params, Box::new(body)))
+    Ok(Expr::Function(
+        params.as_formals(),
+        Box::new(body),
+        Box::new(sig),
+    ))
+}
+
+/// Parse a parameter annotated with a type, as in `x: numeric = 1`
+fn parse_param_annotated<P, R>(
+    parser: &P,
+    pratt: &PrattParser<R>,
+    pair: Pair<R>,
+    sig: &mut Signature,
+) -> Result<(String, Expr), Signal>
+where
+    P: Parser<R> + LocalizedParser,
+    R: RuleType + Into<en::Rule>,
+{
+    let mut inner = pair.into_inner();
+    let param = inner
+        .next()
+        .map_or(internal_err!(), Ok)?
+        .as_str()
+        .to_string();
+    let ty = inner
+        .next()
+        .map_or(internal_err!(), Ok)?
+        .as_str()
+        .to_string();
+    sig.params.push((param.clone(), ty));
+
+    let default = match inner.peek() {
+        Some(_) => parse_expr(parser, pratt, inner)?,
+        None => Expr::Missing,
+    };
+
+    Ok((param, default))
 }
 
 fn parse_if_else<P, R>(parser: &P, pratt: &PrattParser<R>, pair: Pair<R>) -> ParseResult
@@ -286,6 +399,116 @@ where
     Ok(Expr::new_primitive_call(KeywordRepeat, args))
 }
 
+fn parse_match<P, R>(parser: &P, pratt: &PrattParser<R>, pair: Pair<R>) -> ParseResult
+where
+    P: Parser<R> + LocalizedParser,
+    R: RuleType + Into<en::Rule>,
+{
+    let mut inner = pair.into_inner();
+    let inner_value = inner.next().map_or(internal_err!(), Ok)?.into_inner();
+    let mut args = vec![parse_expr(parser, pratt, inner_value)?];
+
+    // each arm is flattened into its pattern, guard and body
+    for arm in inner {
+        let mut arm = arm.into_inner();
+        let pattern = parse_pattern(parser, pratt, arm.next().map_or(internal_err!(), Ok)?)?;
+
+        let mut next = arm.next().map_or(internal_err!(), Ok)?;
+        let guard = match next.as_rule().into() {
+            en::Rule::match_guard => {
+                let guard = parse_expr(parser, pratt, next.into_inner())?;
+                next = arm.next().map_or(internal_err!(), Ok)?;
+                guard
+            }
+            _ => Expr::Missing,
+        };
+
+        let body = parse_expr(parser, pratt, next.into_inner())?;
+        args.extend([pattern, guard, body]);
+    }
+
+    Ok(Expr::new_primitive_call(KeywordMatch, ExprList::from(args)))
+}
+
+fn parse_pattern<P, R>(parser: &P, pratt: &PrattParser<R>, pair: Pair<R>) -> ParseResult
+where
+    P: Parser<R> + LocalizedParser,
+    R: RuleType + Into<en::Rule>,
+{
+    match pair.as_rule().into() {
+        en::Rule::pattern_list => Ok(Expr::List(parse_pattern_elems(parser, pratt, pair)?)),
+        en::Rule::pattern_vec => {
+            let args = parse_pattern_elems(parser, pratt, pair)?;
+            Ok(Expr::new_primitive_call(PrimVec, args))
+        }
+        en::Rule::pattern_literal => {
+            let mut inner = pair.into_inner();
+            let first = inner.next().map_or(internal_err!(), Ok)?;
+            let Some(literal) = inner.next() else {
+                return parse_primary(parser, pratt, first);
+            };
+
+            // negative literals are negated while parsing
+            let rule = literal.as_rule().into();
+            match parse_primary(parser, pratt, literal)? {
+                Expr::Number(x) => Ok(Expr::Number(-x)),
+                Expr::Integer(x) => Ok(Expr::Integer(-x)),
+                Expr::Inf => Ok(Expr::Number(f64::NEG_INFINITY)),
+                _ => Err(Error::ParseUnexpected(rule).into()),
+            }
+        }
+        en::Rule::pattern_class => {
+            let mut inner = pair.into_inner();
+            let class = inner
+                .next()
+                .map_or(internal_err!(), Ok)?
+                .as_str()
+                .to_string();
+            let args = match inner.next() {
+                Some(pattern) => ExprList::from(vec![parse_pattern(parser, pratt, pattern)?]),
+                None => ExprList::new(),
+            };
+
+            Ok(Expr::Call(Box::new(Expr::String(class)), args))
+        }
+        en::Rule::pattern_bind => {
+            let inner = pair.into_inner().next().map_or(internal_err!(), Ok)?;
+            Ok(Expr::Symbol(inner.as_str().to_string()))
+        }
+        rule => Err(Error::ParseUnexpected(rule).into()),
+    }
+}
+
+fn parse_pattern_elems<P, R>(parser: &P, pratt: &PrattParser<R>, pair: Pair<R>) -> ParseListResult
+where
+    P: Parser<R> + LocalizedParser,
+    R: RuleType + Into<en::Rule>,
+{
+    pair.into_inner()
+        .map(|i| match i.as_rule().into() {
+            en::Rule::pattern_rest => {
+                let name = i.into_inner().next().map(|s| s.as_str().to_string());
+                Ok((None, Expr::Ellipsis(name)))
+            }
+            en::Rule::pattern_named => {
+                let mut inner = i.into_inner();
+                let name = inner
+                    .next()
+                    .map_or(internal_err!(), Ok)?
+                    .as_str()
+                    .to_string();
+                let pattern = match inner.next() {
+                    Some(pattern) => parse_pattern(parser, pratt, pattern)?,
+                    None => Expr::Missing,
+                };
+
+                Ok((Some(name), pattern))
+            }
+            _ => Ok((None, parse_pattern(parser, pratt, i)?)),
+        })
+        .collect()
+}
+
 fn parse_postfix<P, R>(
     parser: &P,
     pratt: &PrattParser<R>,
@@ -376,6 +599,18 @@ where
     Ok(result)
 }
 
+fn parse_formula<P, R>(parser: &P, pratt: &PrattParser<R>, pair: Pair<R>) -> ParseResult
+where
+    P: Parser<R> + LocalizedParser,
+    R: RuleType + Into<en::Rule>,
+{
+    let rhs = parse_expr(parser, pratt, pair.into_inner())?;
+    Ok(Expr::new_primitive_call(
+        PrefixTilde,
+        ExprList::from(vec![rhs]),
+    ))
+}
+
 fn parse_vec<P, R>(parser: &P, pratt: &PrattParser<R>, pair: Pair<R>) -> ParseResult
 where
     P: Parser<R> + LocalizedParser,
@@ -7,7 +7,9 @@ pub trait LocalizedParser: std::marker::Sync {
 }
 
 #[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
-#[derive(Debug, Copy, Clone, Default, PartialEq, clap::ValueEnum, strum::EnumString)]
+#[derive(
+    Debug, Copy, Clone, Default, PartialEq, clap::ValueEnum, strum::EnumString, strum::Display,
+)]
 #[strum(serialize_all = "snake_case")]
 pub enum Localization {
     #[default]
@@ -0,0 +1,217 @@
+/// Sampling Profiler
+///
+/// When enabled, the call stack is sampled on a timer. A background thread
+/// ticks once per sampling interval, and the interpreter samples its call
+/// stack at the next call it evaluates, weighted by the ticks since the last
+/// sample. Alternatively, the call stack can be sampled every N closure
+/// calls, which is deterministic. Samples are aggregated by the calls of
+/// each frame and written as collapsed stacks (one `outer;inner count` line
+/// per unique stack), the format consumed by flamegraph tools.
+///
+/// Profiling is started with `Rprof(file)` (or the `--profile` flag) and
+/// samples are written when stopped with `Rprof(NULL)`.
+///
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::error::Error;
+use crate::lang::{CallStack, Signal};
+use crate::object::Expr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sampling {
+    // sample on every nth closure call
+    Calls(usize),
+    // sample on the next call after each interval elapses
+    Interval(Duration),
+}
+
+/// A background thread counting the sampling intervals elapsed, which stops
+/// once dropped
+struct Timer {
+    ticks: Arc<AtomicUsize>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl Timer {
+    fn start(interval: Duration) -> Result<Timer, Signal> {
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (thread_ticks, thread_stopped) = (ticks.clone(), stopped.clone());
+
+        thread::Builder::new()
+            .name("profiler".to_string())
+            .spawn(move || loop {
+                thread::sleep(interval);
+                if thread_stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                thread_ticks.fetch_add(1, Ordering::Relaxed);
+            })
+            .map_err(|e| Error::Other(format!("cannot start profiler: {e}")))?;
+
+        Ok(Timer { ticks, stopped })
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Sampling::Interval(Duration::from_millis(20))
+    }
+}
+
+struct Profiler {
+    file: String,
+    sampling: Sampling,
+    calls: usize,
+    timer: Option<Timer>,
+    samples: HashMap<String, usize>,
+}
+
+thread_local! {
+    static PROFILER: RefCell<Option<Profiler>> = const { RefCell::new(None) };
+}
+
+fn io_error(file: &str, e: std::io::Error) -> Signal {
+    Error::Other(format!("cannot open file '{file}': {e}")).into()
+}
+
+/// Start profiling, writing samples to `file` when stopped
+///
+/// Any profiling already in progress is stopped first. Unless appending,
+/// the file is truncated immediately.
+pub fn start(file: &str, sampling: Sampling, append: bool) -> Result<(), Signal> {
+    stop()?;
+
+    OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(file)
+        .map_err(|e| io_error(file, e))?;
+
+    let timer = match sampling {
+        Sampling::Interval(interval) => Some(Timer::start(interval)?),
+        Sampling::Calls(_) => None,
+    };
+
+    PROFILER.with(|p| {
+        p.replace(Some(Profiler {
+            file: file.to_string(),
+            sampling,
+            calls: 0,
+            timer,
+            samples: HashMap::new(),
+        }))
+    });
+
+    Ok(())
+}
+
+/// Stop profiling, appending collapsed stacks to the profiling file
+pub fn stop() -> Result<(), Signal> {
+    let Some(profiler) = PROFILER.with(|p| p.take()) else {
+        return Ok(());
+    };
+
+    let mut stacks = profiler.samples.into_iter().collect::<Vec<_>>();
+    stacks.sort();
+
+    let file = profiler.file;
+    let mut f = OpenOptions::new()
+        .append(true)
+        .open(&file)
+        .map_err(|e| io_error(&file, e))?;
+
+    for (stack, count) in stacks {
+        writeln!(f, "{stack} {count}").map_err(|e| io_error(&file, e))?;
+    }
+
+    Ok(())
+}
+
+/// Record a sample of the call stack every N closure calls, when sampling
+/// by calls
+///
+/// Called on entry to each closure, once its frame has been added.
+pub fn sample(stack: &CallStack) {
+    PROFILER.with(|p| {
+        let mut p = p.borrow_mut();
+        let Some(profiler) = p.as_mut() else {
+            return;
+        };
+
+        let Sampling::Calls(n) = profiler.sampling else {
+            return;
+        };
+
+        profiler.calls += 1;
+        if profiler.calls % n.max(1) == 0 {
+            profiler.record(stack, 1);
+        }
+    })
+}
+
+/// Record a sample of the call stack if the timer has ticked since the last
+/// sample, when sampling by interval
+///
+/// Called as each call is evaluated.
+pub fn poll(stack: &CallStack) {
+    PROFILER.with(|p| {
+        let mut p = p.borrow_mut();
+        let Some(profiler) = p.as_mut() else {
+            return;
+        };
+
+        let ticks = match &profiler.timer {
+            Some(timer) => timer.ticks.swap(0, Ordering::Relaxed),
+            None => return,
+        };
+
+        if ticks > 0 {
+            profiler.record(stack, ticks);
+        }
+    })
+}
+
+impl Profiler {
+    fn record(&mut self, stack: &CallStack, weight: usize) {
+        let collapsed = stack
+            .frames
+            .iter()
+            .skip(1) // skip global frame
+            .map(|f| frame_label(&f.call))
+            .collect::<Vec<_>>()
+            .join(";");
+
+        *self.samples.entry(collapsed).or_default() += weight;
+    }
+}
+
+/// Label a frame by the name of the function it called
+fn frame_label(call: &Expr) -> String {
+    let label = match call {
+        Expr::Call(what, _) => match what.as_ref() {
+            Expr::Symbol(s) | Expr::String(s) => s.clone(),
+            Expr::Primitive(p) => p.rfmt(),
+            _ => String::from("<anonymous>"),
+        },
+        other => other.to_string(),
+    };
+
+    // separators and whitespace are meaningful in collapsed stacks
+    label.replace([';', ' ', '\n'], "_")
+}
@@ -2,23 +2,44 @@ use reedline::{FileBackedHistory, Reedline};
 use std::path::Path;
 use std::rc::Rc;
 
-use super::prompt::RPrompt;
+use super::prompt::{BrowserPrompt, RPrompt};
 use super::release::*;
+use crate::callable::primitive::autoprint;
 use crate::context::Context;
+use crate::debugger;
 use crate::lang::{CallStack, Cond, EvalResult, Signal};
-use crate::object::Environment;
+use crate::object::{Environment, Obj, Vector};
+use crate::options;
 use crate::parser::{Localization, LocalizedParser};
+use crate::warning;
+use crate::workspace;
 
-pub fn repl<P>(locale: Localization, history: Option<&P>, warranty: bool) -> Result<(), Signal>
+pub fn repl<P>(
+    locale: Localization,
+    history: Option<&P>,
+    warranty: bool,
+    restore: bool,
+) -> Result<(), Signal>
 where
     P: AsRef<Path>,
 {
     println!("{}", session_header(warranty, &locale));
+    let name = Obj::Vector(Vector::from(vec![locale.to_string()]));
+    options::set(options::LOCALE, name);
+
     let global_env = Rc::new(Environment {
         parent: Some(Environment::from_builtins()),
         ..Default::default()
     });
 
+    if restore {
+        match workspace::restore(&global_env) {
+            Ok(true) => println!("[Previously saved workspace restored]\n"),
+            Ok(false) => (),
+            Err(e) => eprint!("{e}"),
+        }
+    }
+
     let history = if let Some(_history_path) = history {
         println!("Restoring session history...");
         FileBackedHistory::with_file(1000, "/tmp/history.txt".into())
@@ -31,12 +52,21 @@ where
         .with_validator(Box::new(locale))
         .with_highlighter(Box::new(locale))
         .with_history(Box::new(history));
+    let mut locale = locale;
 
     // initialize our repl prompt
     let prompt = RPrompt;
 
     // REPL
     loop {
+        // input is read using the current `locale` option
+        if options::locale() != locale {
+            locale = options::locale();
+            line_editor = line_editor
+                .with_validator(Box::new(locale))
+                .with_highlighter(Box::new(locale));
+        }
+
         let signal = line_editor.read_line(&prompt);
         match signal {
             Ok(reedline::Signal::Success(line)) => {
@@ -50,23 +80,41 @@ where
                 match parse_res {
                     Ok(expr) => {
                         let mut stack = CallStack::from(global_env.clone());
-                        match stack.eval_and_finalize(expr) {
+                        let result = stack.eval_and_finalize(expr);
+                        debugger::reset();
+
+                        match result {
                             Err(Signal::Condition(Cond::Terminate)) => break,
+                            Err(Signal::Condition(Cond::Abort)) => (),
                             Err(Signal::Return(value, true)) => {
                                 print!("{value}")
                             }
                             Err(Signal::Return(_value, false)) => (),
                             Err(e) => {
                                 print!("{e}");
-                                print!("traceback:\n{stack}");
+                                print!("traceback:\n{}", stack.traceback());
+                                stack.record_traceback();
+                            }
+                            Ok(val) => {
+                                if let Err(e) = autoprint(val, &mut stack) {
+                                    print!("{e}");
+                                }
                             }
-                            Ok(val) => println!("{val}"),
+                        }
+
+                        if let Some(warnings) = warning::take_deferred() {
+                            eprint!("{warnings}");
                         }
                     }
                     Err(e) => eprint!("{e}"),
                 }
             }
-            Ok(reedline::Signal::CtrlD) => break,
+            Ok(reedline::Signal::CtrlD) => {
+                if workspace::save_on_exit() {
+                    workspace::save_image(&global_env, workspace::DEFAULT_FILE)?;
+                }
+                break;
+            }
             Ok(reedline::Signal::CtrlC) => continue,
             Err(err) => {
                 println!("REPL Error: {:?}", err);
@@ -78,15 +126,23 @@ where
     Ok(())
 }
 
+/// Read a line of input for a nested `browser()` prompt
+pub fn read_browser_line(prompt: &str) -> Option<String> {
+    let mut line_editor = Reedline::create();
+    match line_editor.read_line(&BrowserPrompt::from(prompt)) {
+        Ok(reedline::Signal::Success(line)) => Some(line),
+        _ => None,
+    }
+}
+
 pub fn eval(input: &str) -> EvalResult {
     let global_env = Rc::new(Environment {
         parent: Some(Environment::from_builtins()),
         ..Default::default()
     });
 
-    let locale = Localization::En;
     let mut stack = CallStack::from(global_env.clone());
-    match locale.parse_input(input) {
+    match options::locale().parse_input(input) {
         Ok(expr) => stack.eval_and_finalize(expr),
         Err(e) => Err(e),
     }
@@ -15,6 +15,10 @@ pub fn wasm_cli_args(warranty: bool, locale: Option<String>) -> Cli {
     Cli {
         locale: FromStr::from_str(&locale.unwrap_or("".to_string())).unwrap_or_default(),
         warranty,
+        profile: None,
+        save: false,
+        no_restore: true,
+        command: None,
     }
 }
 
@@ -52,7 +56,10 @@ pub fn wasm_eval_in(args: &Cli, env: &Rc<Environment>, input: &str) -> Option<St
             match stack.eval_and_finalize(expr) {
                 Err(Signal::Condition(Cond::Terminate)) => None,
                 Ok(val) => Some(format!("{val}")),
-                Err(e) => Some(format!("{e}")),
+                Err(e) => {
+                    stack.record_traceback();
+                    Some(format!("{e}"))
+                }
             }
         }
         Err(Signal::Thunk) => None,
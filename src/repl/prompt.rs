@@ -4,21 +4,25 @@ use reedline::{
     Color, Prompt, PromptEditMode, PromptHistorySearch, PromptHistorySearchStatus, PromptViMode,
 };
 
+use crate::options;
+
 #[derive(Default, Clone)]
 pub struct RPrompt;
 
 impl Prompt for RPrompt {
-    fn render_prompt_left(&self) -> Cow<str> {
+    fn render_prompt_left(&self) -> Cow<'_, str> {
         Cow::Owned("".to_string())
     }
 
-    fn render_prompt_right(&self) -> Cow<str> {
+    fn render_prompt_right(&self) -> Cow<'_, str> {
         Cow::Owned("".to_string())
     }
 
-    fn render_prompt_indicator(&self, edit_mode: PromptEditMode) -> Cow<str> {
+    fn render_prompt_indicator(&self, edit_mode: PromptEditMode) -> Cow<'_, str> {
         match edit_mode {
-            PromptEditMode::Default | PromptEditMode::Emacs => "> ".into(),
+            PromptEditMode::Default | PromptEditMode::Emacs => {
+                options::get_string(options::PROMPT).map_or("> ".into(), Cow::Owned)
+            }
             PromptEditMode::Vi(vi_mode) => match vi_mode {
                 PromptViMode::Normal => "n]".into(),
                 PromptViMode::Insert => "i]".into(),
@@ -27,14 +31,14 @@ impl Prompt for RPrompt {
         }
     }
 
-    fn render_prompt_multiline_indicator(&self) -> Cow<str> {
-        Cow::Borrowed(": ")
+    fn render_prompt_multiline_indicator(&self) -> Cow<'_, str> {
+        options::get_string(options::CONTINUE).map_or(": ".into(), Cow::Owned)
     }
 
     fn render_prompt_history_search_indicator(
         &self,
         history_search: PromptHistorySearch,
-    ) -> Cow<str> {
+    ) -> Cow<'_, str> {
         let prefix = match history_search.status {
             PromptHistorySearchStatus::Passing => "",
             PromptHistorySearchStatus::Failing => "failing ",
@@ -62,3 +66,38 @@ impl Prompt for RPrompt {
         Color::White
     }
 }
+
+/// Prompt used while paused in the browser, such as `Browse[1]> `
+#[derive(Default, Clone)]
+pub struct BrowserPrompt(String);
+
+impl From<&str> for BrowserPrompt {
+    fn from(value: &str) -> Self {
+        BrowserPrompt(value.to_string())
+    }
+}
+
+impl Prompt for BrowserPrompt {
+    fn render_prompt_left(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.0)
+    }
+
+    fn render_prompt_right(&self) -> Cow<'_, str> {
+        Cow::Borrowed("")
+    }
+
+    fn render_prompt_indicator(&self, _edit_mode: PromptEditMode) -> Cow<'_, str> {
+        Cow::Borrowed("")
+    }
+
+    fn render_prompt_multiline_indicator(&self) -> Cow<'_, str> {
+        Cow::Borrowed(": ")
+    }
+
+    fn render_prompt_history_search_indicator(
+        &self,
+        history_search: PromptHistorySearch,
+    ) -> Cow<'_, str> {
+        RPrompt.render_prompt_history_search_indicator(history_search)
+    }
+}
@@ -0,0 +1,367 @@
+/// Formatting and reading times using `strftime`-style conversion
+/// specifications
+///
+use super::{civil_from_days, days_from_civil, days_in_month, weekday, Civil, SECS_PER_DAY};
+use crate::err;
+use crate::lang::Signal;
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+fn format_offset(offset: i64) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+    format!("{sign}{:02}{:02}", offset / 3600, offset % 3600 / 60)
+}
+
+/// The ISO 8601 week-based year and week of the year of a day
+fn iso_week(days: i64) -> (i64, i64) {
+    // weeks belong to the year of their Thursday
+    let thursday = days - (weekday(days) as i64 + 6) % 7 + 3;
+    let (year, _, _) = civil_from_days(thursday);
+    (year, (thursday - days_from_civil(year, 1, 1)) / 7 + 1)
+}
+
+/// Format a time
+///
+/// Supports the conversions `%Y`, `%y`, `%C`, `%G`, `%g`, `%m`, `%d`, `%e`,
+/// `%H`, `%k`, `%I`, `%l`, `%M`, `%S`, `%OSn` (seconds with `n` decimal
+/// places), `%s`, `%p`, `%P`, `%b`, `%h`, `%B`, `%a`, `%A`, `%j`, `%u`, `%w`,
+/// `%U`, `%W`, `%V`, `%Z`, `%z`, `%c`, `%x`, `%X`, `%F`, `%T`, `%D`, `%R`,
+/// `%r`, `%n`, `%t` and `%%`. Other conversions are an error.
+pub fn strftime(time: &Civil, format: &str) -> Result<String, Signal> {
+    let days = time.days();
+    let yday = days - days_from_civil(time.year, 1, 1);
+    let mut out = String::new();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('Y') => out.push_str(&time.year.to_string()),
+            Some('y') => out.push_str(&format!("{:02}", time.year.rem_euclid(100))),
+            Some('C') => out.push_str(&format!("{:02}", time.year.div_euclid(100))),
+            Some('G') => out.push_str(&iso_week(days).0.to_string()),
+            Some('g') => out.push_str(&format!("{:02}", iso_week(days).0.rem_euclid(100))),
+            Some('m') => out.push_str(&format!("{:02}", time.month)),
+            Some('d') => out.push_str(&format!("{:02}", time.day)),
+            Some('e') => out.push_str(&format!("{:2}", time.day)),
+            Some('H') => out.push_str(&format!("{:02}", time.hour)),
+            Some('k') => out.push_str(&format!("{:2}", time.hour)),
+            Some('I') => out.push_str(&format!("{:02}", (time.hour + 11) % 12 + 1)),
+            Some('l') => out.push_str(&format!("{:2}", (time.hour + 11) % 12 + 1)),
+            Some('M') => out.push_str(&format!("{:02}", time.minute)),
+            Some('S') => out.push_str(&format!("{:02}", time.second.floor())),
+            Some('O') if chars.next_if_eq(&'S').is_some() => {
+                match chars.next_if(|c| c.is_ascii_digit()) {
+                    Some(n) => {
+                        let n = n.to_digit(10).unwrap_or(0) as usize;
+                        let width = if n > 0 { n + 3 } else { 2 };
+                        // truncate rather than round, so as not to reach 60
+                        let scale = 10f64.powi(n as i32);
+                        let second = (time.second * scale).floor() / scale;
+                        out.push_str(&format!("{second:0width$.n$}"))
+                    }
+                    None => out.push_str(&format!("{:02}", time.second.floor())),
+                }
+            }
+            Some('s') => {
+                let secs = days * SECS_PER_DAY
+                    + (time.hour * 3600 + time.minute * 60) as i64
+                    + time.second.floor() as i64
+                    - time.offset;
+                out.push_str(&secs.to_string())
+            }
+            Some('p') => out.push_str(if time.hour < 12 { "AM" } else { "PM" }),
+            Some('P') => out.push_str(if time.hour < 12 { "am" } else { "pm" }),
+            Some('b') | Some('h') => out.push_str(&MONTHS[time.month as usize - 1][..3]),
+            Some('B') => out.push_str(MONTHS[time.month as usize - 1]),
+            Some('a') => out.push_str(&WEEKDAYS[weekday(days) as usize][..3]),
+            Some('A') => out.push_str(WEEKDAYS[weekday(days) as usize]),
+            Some('j') => out.push_str(&format!("{:03}", yday + 1)),
+            Some('u') => out.push_str(&((weekday(days) + 6) % 7 + 1).to_string()),
+            Some('w') => out.push_str(&weekday(days).to_string()),
+            Some('U') => {
+                let week = (yday + 7 - weekday(days) as i64) / 7;
+                out.push_str(&format!("{week:02}"))
+            }
+            Some('W') => {
+                let week = (yday + 7 - (weekday(days) as i64 + 6) % 7) / 7;
+                out.push_str(&format!("{week:02}"))
+            }
+            Some('V') => out.push_str(&format!("{:02}", iso_week(days).1)),
+            Some('Z') => out.push_str(&time.zone),
+            Some('z') => out.push_str(&format_offset(time.offset)),
+            Some('c') => out.push_str(&strftime(time, "%a %b %e %H:%M:%S %Y")?),
+            Some('x') | Some('D') => out.push_str(&strftime(time, "%m/%d/%y")?),
+            Some('X') | Some('T') => out.push_str(&strftime(time, "%H:%M:%S")?),
+            Some('F') => out.push_str(&strftime(time, "%Y-%m-%d")?),
+            Some('R') => out.push_str(&strftime(time, "%H:%M")?),
+            Some('r') => out.push_str(&strftime(time, "%I:%M:%S %p")?),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('%') => out.push('%'),
+            Some(c) => return err!(format!("unsupported conversion specification '%{c}'")),
+            None => out.push('%'),
+        }
+    }
+
+    Ok(out)
+}
+
+/// Read a number of at most `width` digits, with an optional sign
+fn number(input: &mut &str, width: usize, signed: bool) -> Option<i64> {
+    let trimmed = input.trim_start();
+    let sign = match trimmed.chars().next() {
+        Some(c @ ('-' | '+')) if signed => Some(c),
+        _ => None,
+    };
+
+    let digits = &trimmed[sign.map_or(0, |_| 1)..];
+    let n = digits
+        .chars()
+        .take(width)
+        .take_while(|c| c.is_ascii_digit())
+        .count();
+
+    if n == 0 {
+        return None;
+    }
+
+    let value: i64 = digits[..n].parse().ok()?;
+    *input = &digits[n..];
+    Some(if sign == Some('-') { -value } else { value })
+}
+
+fn bounded(input: &mut &str, width: usize, range: std::ops::RangeInclusive<i64>) -> Option<u32> {
+    number(input, width, false)
+        .filter(|x| range.contains(x))
+        .map(|x| x as u32)
+}
+
+/// Read one of a set of names, either in full or abbreviated to three
+/// letters, returning its index
+fn name(input: &mut &str, names: &[&str]) -> Option<usize> {
+    let lower = input.to_lowercase();
+    names.iter().enumerate().find_map(|(i, name)| {
+        let name = name.to_lowercase();
+        let len = [name.len(), 3]
+            .into_iter()
+            .find(|len| lower.starts_with(&name[..*len]))?;
+        *input = &input[len..];
+        Some(i)
+    })
+}
+
+fn seconds(input: &mut &str) -> Option<f64> {
+    let trimmed = input.trim_start();
+    let n = trimmed
+        .char_indices()
+        .take_while(|(i, c)| c.is_ascii_digit() || (*c == '.' && *i > 0))
+        .count();
+
+    let value: f64 = trimmed[..n].parse().ok()?;
+    *input = &trimmed[n..];
+    Some(value).filter(|x| (0.0..62.0).contains(x))
+}
+
+fn offset(input: &mut &str) -> Option<i64> {
+    let trimmed = input.trim_start();
+    let sign = match trimmed.chars().next() {
+        Some('+') => 1,
+        Some('-') => -1,
+        Some('Z') => {
+            *input = &trimmed[1..];
+            return Some(0);
+        }
+        _ => return None,
+    };
+
+    let mut rest = &trimmed[1..];
+    let hours = number(&mut rest, 2, false)?;
+    rest = rest.strip_prefix(':').unwrap_or(rest);
+    let minutes = number(&mut rest, 2, false)?;
+    *input = rest;
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Read a time
+///
+/// Returns the time read, and its offset from UTC if given using `%z`.
+/// Supports the conversions `%Y`, `%y`, `%m`, `%d`, `%e`, `%H`, `%I`, `%M`,
+/// `%S`, `%OS`, `%p`, `%b`, `%h`, `%B`, `%a`, `%A`, `%j`, `%z`, `%F`, `%T`,
+/// `%R` and `%%`. Whitespace in the format matches any amount of
+/// whitespace, and characters following the time are ignored.
+pub fn strptime(x: &str, format: &str) -> Option<(Civil, Option<i64>)> {
+    let mut time = Civil::from_date(0);
+    let mut utc_offset = None;
+    let mut pm = None;
+    let mut input = x;
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            input = input.trim_start();
+            continue;
+        }
+
+        if c != '%' {
+            input = input.strip_prefix(c)?;
+            continue;
+        }
+
+        match chars.next()? {
+            'Y' => time.year = number(&mut input, 4, true)?,
+            'y' => {
+                // as in POSIX, 69 to 99 are read as the twentieth century
+                let y = number(&mut input, 2, false)?;
+                time.year = if y < 69 { 2000 + y } else { 1900 + y }
+            }
+            'm' => time.month = bounded(&mut input, 2, 1..=12)?,
+            'd' | 'e' => time.day = bounded(&mut input, 2, 1..=31)?,
+            'H' => time.hour = bounded(&mut input, 2, 0..=24)?,
+            'I' => time.hour = bounded(&mut input, 2, 1..=12)?,
+            'M' => time.minute = bounded(&mut input, 2, 0..=59)?,
+            'S' => time.second = bounded(&mut input, 2, 0..=61)? as f64,
+            'O' => match chars.next()? {
+                'S' => time.second = seconds(&mut input)?,
+                _ => return None,
+            },
+            'p' => {
+                let lower = input.trim_start().to_lowercase();
+                pm = Some(lower.starts_with("pm"));
+                if !lower.starts_with("am") && !lower.starts_with("pm") {
+                    return None;
+                }
+                input = &input.trim_start()[2..];
+            }
+            'b' | 'B' | 'h' => time.month = name(&mut input, &MONTHS)? as u32 + 1,
+            'a' | 'A' => {
+                name(&mut input, &WEEKDAYS)?;
+            }
+            'j' => {
+                let yday = bounded(&mut input, 3, 1..=366)?;
+                let (_, month, day) =
+                    civil_from_days(days_from_civil(time.year, 1, 1) + yday as i64 - 1);
+                time.month = month;
+                time.day = day;
+            }
+            'z' => utc_offset = Some(offset(&mut input)?),
+            'F' => {
+                let (t, _) = strptime(input, "%Y-%m-%d")?;
+                (time.year, time.month, time.day) = (t.year, t.month, t.day);
+                input = input.trim_start().get(10..).unwrap_or("");
+            }
+            'T' => {
+                let (t, _) = strptime(input, "%H:%M:%S")?;
+                (time.hour, time.minute, time.second) = (t.hour, t.minute, t.second);
+                input = input.trim_start().get(8..).unwrap_or("");
+            }
+            'R' => {
+                let (t, _) = strptime(input, "%H:%M")?;
+                (time.hour, time.minute) = (t.hour, t.minute);
+                input = input.trim_start().get(5..).unwrap_or("");
+            }
+            '%' => input = input.strip_prefix('%')?,
+            _ => return None,
+        }
+    }
+
+    match pm {
+        Some(true) if time.hour < 12 => time.hour += 12,
+        Some(false) if time.hour == 12 => time.hour = 0,
+        _ => (),
+    }
+
+    if time.day > days_in_month(time.year, time.month) {
+        return None;
+    }
+
+    if let Some(offset) = utc_offset {
+        time.offset = offset;
+    }
+
+    Some((time, utc_offset))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn times_are_formatted() {
+        let mut time = Civil::from_date(days_from_civil(2024, 3, 5));
+        time.hour = 14;
+        time.minute = 7;
+        time.second = 9.25;
+
+        let f = |format| strftime(&time, format).unwrap();
+        assert_eq!(f("%Y-%m-%d %H:%M:%S"), "2024-03-05 14:07:09");
+        assert_eq!(f("%a %d %b %y, %I%p"), "Tue 05 Mar 24, 02PM");
+        assert_eq!(f("%j %u %OS2 %Z %z"), "065 2 09.25 UTC +0000");
+        assert_eq!(f("%B %e, 100%%"), "March  5, 100%");
+        assert_eq!(f("%C %l%P %s"), "20  2pm 1709647629");
+    }
+
+    #[test]
+    fn weeks_of_the_year_are_formatted() {
+        let week = |y, m, d| {
+            let time = Civil::from_date(days_from_civil(y, m, d));
+            strftime(&time, "%U %W %V %G").unwrap()
+        };
+
+        // a Friday, before the year's first Sunday and Monday
+        assert_eq!(week(2021, 1, 1), "00 00 53 2020");
+        assert_eq!(week(2021, 1, 4), "01 01 01 2021");
+        assert_eq!(week(2024, 12, 30), "52 53 01 2025");
+    }
+
+    #[test]
+    fn unsupported_conversions_are_errors() {
+        let time = Civil::from_date(0);
+        assert!(strftime(&time, "%A %Q").is_err());
+    }
+
+    #[test]
+    fn times_are_read() {
+        let (time, offset) = strptime("2024-03-05 14:07:09.5", "%Y-%m-%d %H:%M:%OS").unwrap();
+        assert_eq!((time.year, time.month, time.day), (2024, 3, 5));
+        assert_eq!((time.hour, time.minute, time.second), (14, 7, 9.5));
+        assert_eq!(offset, None);
+
+        let (time, offset) =
+            strptime("5 March 2024 2:30 pm +0100", "%d %B %Y %I:%M %p %z").unwrap();
+        assert_eq!((time.month, time.hour), (3, 14));
+        assert_eq!(offset, Some(3600));
+
+        assert!(strptime("2024-02-30", "%Y-%m-%d").is_none());
+        assert!(strptime("2024-13-01", "%Y-%m-%d").is_none());
+        assert!(strptime("03/05/2024", "%Y-%m-%d").is_none());
+    }
+}
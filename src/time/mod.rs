@@ -0,0 +1,625 @@
+/// Dates and times
+///
+/// Dates, date-times and time differences are numeric vectors distinguished
+/// by their attributes:
+///
+/// * `Date`, the number of days since 1970-01-01
+/// * `POSIXct`, the number of seconds since 1970-01-01 00:00:00 UTC, with
+///   the time zone used to display it as its `tzone` attribute (where `""`
+///   is the local time zone)
+/// * `difftime`, a duration in the `units` given by its attribute
+///
+/// Calendar calculations use the proleptic Gregorian calendar, and time
+/// zones are resolved from the bundled database in [tz].
+///
+pub mod tz;
+
+mod format;
+pub use format::{strftime, strptime};
+
+use core::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::err;
+use crate::lang::Signal;
+use crate::object::attributes::{Arith, Attributes};
+use crate::object::*;
+use crate::options;
+use tz::Zone;
+
+pub const SECS_PER_DAY: i64 = 86400;
+
+/// The current time, in seconds since the epoch
+pub fn now() -> f64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(now) => now.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    }
+}
+
+/// Days since the epoch of a date in the proleptic Gregorian calendar
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = month as i64;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// The year, month and day of a number of days since the epoch
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// The day of the week of a number of days since the epoch, from Sunday
+pub fn weekday(days: i64) -> u32 {
+    (days + 4).rem_euclid(7) as u32
+}
+
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// A time broken down into its calendar fields
+#[derive(Debug, Clone, PartialEq)]
+pub struct Civil {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: f64,
+    /// The offset from UTC, in seconds
+    pub offset: i64,
+    /// The abbreviated name of the time zone
+    pub zone: String,
+}
+
+impl Civil {
+    pub fn from_date(days: i64) -> Civil {
+        let (year, month, day) = civil_from_days(days);
+        Civil {
+            year,
+            month,
+            day,
+            hour: 0,
+            minute: 0,
+            second: 0.0,
+            offset: 0,
+            zone: "UTC".to_string(),
+        }
+    }
+
+    pub fn from_timestamp(t: f64, zone: &Zone) -> Civil {
+        let secs = t.floor() as i64;
+        let (offset, abbr) = zone.at(secs);
+        let local = secs + offset;
+
+        let mut civil = Civil::from_date(local.div_euclid(SECS_PER_DAY));
+        let time = local.rem_euclid(SECS_PER_DAY);
+        civil.hour = (time / 3600) as u32;
+        civil.minute = (time % 3600 / 60) as u32;
+        civil.second = (time % 60) as f64 + (t - t.floor());
+        civil.offset = offset;
+        civil.zone = abbr.to_string();
+        civil
+    }
+
+    /// Days since the epoch of the date
+    pub fn days(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day)
+    }
+
+    /// Seconds since the epoch of the local time, ignoring its offset
+    pub fn local_seconds(&self) -> f64 {
+        let secs = self.days() * SECS_PER_DAY + self.hour as i64 * 3600 + self.minute as i64 * 60;
+        secs as f64 + self.second
+    }
+
+    /// Seconds since the epoch of the time in a zone
+    pub fn timestamp(&self, zone: &Zone) -> f64 {
+        let local = self.local_seconds();
+        let whole = local.floor();
+        zone.to_utc(whole as i64) as f64 + (local - whole)
+    }
+}
+
+/// The kinds of classed vectors representing times
+#[derive(Debug, Clone, PartialEq)]
+pub enum Class {
+    Date,
+    DateTime(String),
+    Difftime(String),
+}
+
+impl Class {
+    pub fn of(x: &Vector) -> Option<Class> {
+        let attrs = x.attributes();
+        let first = |name: &str, default: &str| {
+            attrs
+                .get(name)
+                .and_then(|v| v.first().cloned())
+                .unwrap_or_else(|| default.to_string())
+        };
+
+        if attrs.inherits("Date") {
+            Some(Class::Date)
+        } else if attrs.inherits("POSIXct") {
+            Some(Class::DateTime(first("tzone", "")))
+        } else if attrs.inherits("difftime") {
+            Some(Class::Difftime(first("units", "days")))
+        } else {
+            None
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Class::Date => "Date",
+            Class::DateTime(_) => "POSIXt",
+            Class::Difftime(_) => "difftime",
+        }
+    }
+
+    fn is_time(&self) -> bool {
+        matches!(self, Class::Date | Class::DateTime(_))
+    }
+}
+
+fn class_of(x: &Obj) -> Option<Class> {
+    match x {
+        Obj::Vector(v) => Class::of(v),
+        _ => None,
+    }
+}
+
+/// The numeric values of a vector
+pub fn values(x: &Vector) -> Vec<OptionNA<f64>> {
+    match x.clone().as_numeric() {
+        Vector::Numeric(v) => v.inner().borrow().clone(),
+        _ => unreachable!(),
+    }
+}
+
+pub fn date(values: Vec<OptionNA<f64>>) -> Vector {
+    let attrs = Attributes::new().with("class", vec!["Date".to_string()]);
+    Vector::from(values).with_attributes(attrs)
+}
+
+pub fn datetime(values: Vec<OptionNA<f64>>, tz: &str) -> Vector {
+    let class = vec!["POSIXct".to_string(), "POSIXt".to_string()];
+    let attrs = Attributes::new()
+        .with("class", class)
+        .with("tzone", vec![tz.to_string()]);
+    Vector::from(values).with_attributes(attrs)
+}
+
+pub fn difftime(values: Vec<OptionNA<f64>>, units: &str) -> Vector {
+    let attrs = Attributes::new()
+        .with("class", vec!["difftime".to_string()])
+        .with("units", vec![units.to_string()]);
+    Vector::from(values).with_attributes(attrs)
+}
+
+/// The number of seconds in a unit of time
+pub fn unit_seconds(units: &str) -> Option<f64> {
+    match units {
+        "secs" => Some(1.0),
+        "mins" => Some(60.0),
+        "hours" => Some(3600.0),
+        "days" => Some(86400.0),
+        "weeks" => Some(604800.0),
+        _ => None,
+    }
+}
+
+/// Convert a time difference to other units
+fn convert_units(x: &Vector, from: &str, to: &str) -> Result<Vector, Signal> {
+    let (Some(from_secs), Some(to_secs)) = (unit_seconds(from), unit_seconds(to)) else {
+        return err!(format!("invalid units '{from}' or '{to}'"));
+    };
+
+    let converted = values(x)
+        .into_iter()
+        .map(|xi| xi.map(|xi| xi * from_secs / to_secs))
+        .collect();
+
+    Ok(difftime(converted, to))
+}
+
+/// Units in which to express a number of seconds, choosing the largest units
+/// in which the smallest difference is at least one
+pub fn auto_units(secs: &[OptionNA<f64>]) -> &'static str {
+    let smallest = secs
+        .iter()
+        .filter_map(|x| match x {
+            OptionNA::Some(x) if x.is_finite() => Some(x.abs()),
+            _ => None,
+        })
+        .fold(f64::INFINITY, f64::min);
+
+    match smallest {
+        x if !x.is_finite() || x < 60.0 => "secs",
+        x if x < 3600.0 => "mins",
+        x if x < 86400.0 => "hours",
+        _ => "days",
+    }
+}
+
+/// Express the difference between two date-times in automatically chosen
+/// units
+pub fn simplify_difference(x: Vector) -> Vector {
+    let secs = values(&x);
+    let units = auto_units(&secs);
+    convert_units(&x, "secs", units).unwrap_or(x)
+}
+
+fn strip(x: Obj) -> Obj {
+    match x {
+        Obj::Vector(v) => Obj::Vector(v.with_attributes(Attributes::new())),
+        x => x,
+    }
+}
+
+/// Check and prepare the operands of an arithmetic operator
+///
+/// Dates and date-times may only be shifted by adding or subtracting
+/// numbers or time differences, which are first converted to days or
+/// seconds respectively. Time differences are converted to the units of the
+/// left operand.
+pub fn operands(op: Arith, lhs: Obj, rhs: Obj) -> Result<(Obj, Obj), Signal> {
+    use Class::*;
+
+    let (lc, rc) = (class_of(&lhs), class_of(&rhs));
+    if lc.is_none() && rc.is_none() {
+        return Ok((lhs, rhs));
+    }
+
+    let sym = match op {
+        Arith::Add => "+",
+        Arith::Sub => "-",
+        Arith::Mul => "*",
+        Arith::Div => "/",
+        Arith::Rem => "%",
+        Arith::Pow => "^",
+    };
+
+    // time differences in the base units of a date or date-time
+    let shift = |x: Obj, time: &Class| -> Result<Obj, Signal> {
+        let (Obj::Vector(v), Some(Difftime(units))) = (&x, class_of(&x)) else {
+            return Ok(x);
+        };
+
+        let base = if *time == Date { "days" } else { "secs" };
+        Ok(strip(Obj::Vector(convert_units(v, &units, base)?)))
+    };
+
+    let unsupported = |class: &Class| {
+        err!(format!(
+            "binary {sym} is not defined for \"{}\" objects",
+            class.name()
+        ))
+    };
+
+    match (op, &lc, &rc) {
+        // differences are converted to the units of the left operand
+        (Arith::Add | Arith::Sub | Arith::Div, Some(Difftime(l)), Some(Difftime(r))) => {
+            let Obj::Vector(v) = &rhs else { unreachable!() };
+            let rhs = Obj::Vector(convert_units(v, r, l)?);
+            Ok((lhs, rhs))
+        }
+        (Arith::Add, Some(l), Some(r)) if l.is_time() && r.is_time() => unsupported(l),
+        (Arith::Add, Some(time), _) if time.is_time() => {
+            let rhs = shift(rhs, time)?;
+            Ok((lhs, rhs))
+        }
+        (Arith::Add, _, Some(time)) if time.is_time() => Ok((strip(shift(lhs, time)?), rhs)),
+        (Arith::Sub, Some(l), Some(r)) if l.is_time() && r.is_time() => {
+            match l == r || (matches!(l, DateTime(_)) && matches!(r, DateTime(_))) {
+                true => Ok((lhs, rhs)),
+                false => err!("incompatible classes for binary -"),
+            }
+        }
+        (Arith::Sub, Some(time), _) if time.is_time() => {
+            let rhs = shift(rhs, time)?;
+            Ok((lhs, rhs))
+        }
+        (Arith::Sub, _, Some(time)) if time.is_time() => err!(format!(
+            "can only subtract from \"{}\" objects",
+            time.name()
+        )),
+        (_, Some(class), _) | (_, _, Some(class)) if class.is_time() => unsupported(class),
+        (Arith::Mul, Some(Difftime(_)), Some(Difftime(_))) => {
+            err!("both arguments of * cannot be \"difftime\" objects")
+        }
+        (Arith::Div, _, Some(Difftime(_))) => {
+            err!("second argument of / cannot be a \"difftime\" object")
+        }
+        _ => Ok((lhs, rhs)),
+    }
+}
+
+/// Check the operand of unary minus
+pub fn negated(x: Obj) -> Result<Obj, Signal> {
+    match class_of(&x) {
+        Some(class) if class.is_time() => err!(format!(
+            "unary - is not defined for \"{}\" objects",
+            class.name()
+        )),
+        _ => Ok(x),
+    }
+}
+
+/// Prepare the operands of a comparison
+///
+/// Character values compared to dates or date-times are read as dates or
+/// date-times, and time differences are converted to common units.
+pub fn comparable(lhs: Obj, rhs: Obj) -> Result<(Obj, Obj), Signal> {
+    let parse = |x: Obj, class: &Class| -> Result<Obj, Signal> {
+        let Obj::Vector(v @ Vector::Character(_)) = &x else {
+            return Ok(x);
+        };
+
+        let strings: Vec<OptionNA<String>> = match v {
+            Vector::Character(v) => v.inner().borrow().clone(),
+            _ => unreachable!(),
+        };
+
+        let parsed = match class {
+            Class::Date => date(parse_dates(&strings, None)?),
+            Class::DateTime(tz) => datetime(parse_datetimes(&strings, None, tz)?, tz),
+            Class::Difftime(_) => return Ok(x),
+        };
+
+        Ok(Obj::Vector(parsed))
+    };
+
+    match (class_of(&lhs), class_of(&rhs)) {
+        (Some(Class::Difftime(l)), Some(Class::Difftime(r))) => {
+            let Obj::Vector(v) = &rhs else { unreachable!() };
+            let rhs = Obj::Vector(convert_units(v, &r, &l)?);
+            Ok((lhs, rhs))
+        }
+        (Some(class), _) => {
+            let rhs = parse(rhs, &class)?;
+            Ok((lhs, rhs))
+        }
+        (_, Some(class)) => Ok((parse(lhs, &class)?, rhs)),
+        _ => Ok((lhs, rhs)),
+    }
+}
+
+/// The time zone of a date-time, where `tz` overrides its own
+pub fn zone(tz: &str) -> Result<Zone, Signal> {
+    Zone::get(tz).or_else(|e| err!(e))
+}
+
+pub const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d"];
+
+pub const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%OS",
+    "%Y/%m/%d %H:%M:%OS",
+    "%Y-%m-%d %H:%M",
+    "%Y/%m/%d %H:%M",
+    "%Y-%m-%d",
+    "%Y/%m/%d",
+];
+
+/// The format to read strings with, chosen as the first of `formats` which
+/// reads the first string that is not `NA`
+fn choose_format<'a>(
+    x: &[OptionNA<String>],
+    format: Option<&'a str>,
+    formats: &[&'a str],
+) -> Result<&'a str, Signal> {
+    if let Some(format) = format {
+        return Ok(format);
+    }
+
+    let first = x.iter().find_map(|xi| match xi {
+        OptionNA::Some(s) => Some(s),
+        OptionNA::NA => None,
+    });
+
+    let Some(first) = first else {
+        return Ok(formats[0]);
+    };
+
+    match formats.iter().find(|f| strptime(first, f).is_some()) {
+        Some(format) => Ok(format),
+        None => err!("character string is not in a standard unambiguous format"),
+    }
+}
+
+/// Read strings as dates, where strings which cannot be read are `NA`
+pub fn parse_dates(
+    x: &[OptionNA<String>],
+    format: Option<&str>,
+) -> Result<Vec<OptionNA<f64>>, Signal> {
+    let format = choose_format(x, format, DATE_FORMATS)?;
+    Ok(x.iter()
+        .map(|xi| match xi {
+            OptionNA::Some(s) => match strptime(s, format) {
+                Some((civil, _)) => OptionNA::Some(civil.days() as f64),
+                None => OptionNA::NA,
+            },
+            OptionNA::NA => OptionNA::NA,
+        })
+        .collect())
+}
+
+/// Read strings as date-times in a time zone, where strings which cannot be
+/// read are `NA`
+pub fn parse_datetimes(
+    x: &[OptionNA<String>],
+    format: Option<&str>,
+    tz: &str,
+) -> Result<Vec<OptionNA<f64>>, Signal> {
+    let zone = zone(tz)?;
+    let format = choose_format(x, format, DATETIME_FORMATS)?;
+    Ok(x.iter()
+        .map(|xi| match xi {
+            OptionNA::Some(s) => match strptime(s, format) {
+                // times with an explicit offset are not local to the zone
+                Some((civil, Some(offset))) => {
+                    OptionNA::Some(civil.local_seconds() - offset as f64)
+                }
+                Some((civil, None)) => OptionNA::Some(civil.timestamp(&zone)),
+                None => OptionNA::NA,
+            },
+            OptionNA::NA => OptionNA::NA,
+        })
+        .collect())
+}
+
+/// The default format of date-times, omitting seconds or times of day when
+/// they are zero for all values
+fn datetime_format(values: &[OptionNA<f64>], zone: &Zone) -> &'static str {
+    let civil: Vec<Civil> = values
+        .iter()
+        .filter_map(|x| match x {
+            OptionNA::Some(x) if x.is_finite() => Some(Civil::from_timestamp(*x, zone)),
+            _ => None,
+        })
+        .collect();
+
+    if civil.iter().any(|c| c.second != 0.0) {
+        "%Y-%m-%d %H:%M:%S"
+    } else if civil.iter().any(|c| c.hour != 0 || c.minute != 0) {
+        "%Y-%m-%d %H:%M"
+    } else {
+        "%Y-%m-%d"
+    }
+}
+
+/// Format dates and date-times as strings
+///
+/// Returns `None` for vectors which are not dates or date-times. When no
+/// format is given, a default format is used, followed by the time zone
+/// abbreviation if `usetz` is set.
+pub fn format(
+    x: &Vector,
+    format: Option<&str>,
+    tz: Option<&str>,
+    usetz: bool,
+) -> Result<Option<Vec<OptionNA<String>>>, Signal> {
+    let values = values(x);
+    let (format, zone) = match Class::of(x) {
+        Some(Class::Date) => (format.unwrap_or("%Y-%m-%d").to_string(), zone("UTC")?),
+        Some(Class::DateTime(own)) => {
+            let zone = zone(tz.unwrap_or(&own))?;
+            let format = format.unwrap_or_else(|| datetime_format(&values, &zone));
+            let format = match usetz {
+                true => format!("{format} %Z"),
+                false => format.to_string(),
+            };
+            (format, zone)
+        }
+        _ => return Ok(None),
+    };
+
+    let is_date = Class::of(x) == Some(Class::Date);
+    let strings = values
+        .iter()
+        .map(|xi| match xi {
+            OptionNA::Some(xi) if xi.is_finite() => {
+                let civil = match is_date {
+                    true => Civil::from_date(xi.floor() as i64),
+                    false => Civil::from_timestamp(*xi, &zone),
+                };
+                Ok(OptionNA::Some(strftime(&civil, &format)?))
+            }
+            _ => Ok(OptionNA::NA),
+        })
+        .collect::<Result<_, Signal>>()?;
+
+    Ok(Some(strings))
+}
+
+/// Format the elements of a time difference with their units
+pub fn format_difftime(x: &Vector) -> Option<Vec<OptionNA<String>>> {
+    let Some(Class::Difftime(units)) = Class::of(x) else {
+        return None;
+    };
+
+    let digits = options::digits();
+    let strings = values(x)
+        .into_iter()
+        .map(|xi| match xi {
+            OptionNA::Some(_) => OptionNA::Some(format!("{:.*?} {units}", digits, xi)),
+            OptionNA::NA => OptionNA::NA,
+        })
+        .collect();
+
+    Some(strings)
+}
+
+/// Display dates, date-times and time differences
+pub fn fmt_time(x: &Vector, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+    if let Some(Class::Difftime(units)) = Class::of(x) {
+        let values = values(x);
+        return Some(match &values[..] {
+            [xi] => {
+                let digits = options::digits();
+                write!(f, "Time difference of {:.*?} {units}", digits, xi)
+            }
+            _ => write!(f, "Time differences in {units}\n{}", Vector::from(values)),
+        });
+    }
+
+    match format(x, None, None, true) {
+        Ok(Some(strings)) if strings.is_empty() => {
+            let class = if Class::of(x) == Some(Class::Date) {
+                "Date"
+            } else {
+                "POSIXct"
+            };
+            Some(write!(f, "{class} of length 0"))
+        }
+        Ok(Some(strings)) => Some(write!(f, "{}", Vector::from(strings))),
+        Ok(None) => None,
+        Err(_) => Some(Err(fmt::Error)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn civil_dates_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(civil_from_days(11017), (2000, 3, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(weekday(0), 4);
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(1900, 2), 28);
+    }
+
+    #[test]
+    fn units_are_chosen_automatically() {
+        let secs = |x: f64| vec![OptionNA::Some(x)];
+        assert_eq!(auto_units(&secs(30.0)), "secs");
+        assert_eq!(auto_units(&secs(90.0)), "mins");
+        assert_eq!(auto_units(&secs(7200.0)), "hours");
+        assert_eq!(auto_units(&secs(-172800.0)), "days");
+    }
+}
@@ -0,0 +1,448 @@
+/// Time zones
+///
+/// Time zones are resolved from a bundled copy of the IANA time zone
+/// database, such that conversions work offline and alike on every
+/// platform. Each zone is read from its compiled (TZif) form, as a history
+/// of the transitions between its local time types followed by the POSIX
+/// `TZ` rule in effect after the last of them.
+///
+/// Names which are not in the database are read as POSIX `TZ` rules
+/// themselves, as in `"EST5EDT,M3.2.0,M11.1.0"`.
+///
+use super::{civil_from_days, days_from_civil, days_in_month, weekday};
+
+/// The day of the year on which daylight saving time starts or ends
+#[derive(Debug, Clone, PartialEq)]
+enum Day {
+    /// `Jn`, the day of the year from 1 to 365, ignoring leap days
+    Julian(u32),
+    /// `n`, the day of the year from 0 to 365
+    Ordinal(u32),
+    /// `Mm.w.d`, day `d` (from Sunday) of week `w` (where 5 is the last) of
+    /// month `m`
+    Month(u32, u32, u32),
+}
+
+/// A transition to or from daylight saving time, at a local time of day
+#[derive(Debug, Clone, PartialEq)]
+struct Transition {
+    day: Day,
+    time: i64,
+}
+
+impl Transition {
+    /// The local time of the transition in a year, in seconds since the epoch
+    fn local(&self, year: i64) -> i64 {
+        let jan1 = days_from_civil(year, 1, 1);
+        let day = match self.day {
+            Day::Julian(n) => {
+                // leap days are not counted
+                let leap = days_in_month(year, 2) == 29 && n >= 60;
+                jan1 + n as i64 - 1 + leap as i64
+            }
+            Day::Ordinal(n) => jan1 + n as i64,
+            Day::Month(m, w, d) => {
+                let first = days_from_civil(year, m, 1);
+                let offset = (d as i64 - weekday(first) as i64).rem_euclid(7);
+                let mut day = first + offset + (w as i64 - 1) * 7;
+                while day >= first + days_in_month(year, m) as i64 {
+                    day -= 7;
+                }
+                day
+            }
+        };
+
+        day * 86400 + self.time
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Dst {
+    abbr: String,
+    offset: i64,
+    start: Transition,
+    end: Transition,
+}
+
+/// A POSIX `TZ` rule, giving standard time and, optionally, the daylight
+/// saving time observed for part of each year
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    abbr: String,
+    offset: i64,
+    dst: Option<Dst>,
+}
+
+impl Rule {
+    /// Whether daylight saving time is in effect at a time in UTC
+    fn is_dst(&self, utc: i64) -> Option<&Dst> {
+        let dst = self.dst.as_ref()?;
+        let (year, ..) = civil_from_days((utc + self.offset).div_euclid(86400));
+
+        // transitions are given in local standard and daylight time respectively
+        let start = dst.start.local(year) - self.offset;
+        let end = dst.end.local(year) - dst.offset;
+        let active = match start < end {
+            true => start <= utc && utc < end,
+            false => utc >= start || utc < end,
+        };
+
+        active.then_some(dst)
+    }
+
+    fn at(&self, utc: i64) -> (i64, &str) {
+        match self.is_dst(utc) {
+            Some(dst) => (dst.offset, &dst.abbr),
+            None => (self.offset, &self.abbr),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zone {
+    pub name: String,
+    // times in UTC at which the local time type changes, and the index of
+    // the type which takes effect
+    transitions: Vec<(i64, usize)>,
+    // offsets from UTC, in seconds, and abbreviations of local time types
+    types: Vec<(i64, String)>,
+    // the rule in effect after the last transition
+    rule: Option<Rule>,
+}
+
+impl Zone {
+    /// Find a time zone by name, where `""` is the local time zone
+    pub fn get(name: &str) -> Result<Zone, String> {
+        if name.is_empty() {
+            return Ok(Zone::local());
+        }
+
+        let zone = match jiff_tzdb::get(name) {
+            Some((_, data)) => tzif(data),
+            None => parse(name).map(|rule| Zone {
+                name: String::new(),
+                transitions: vec![],
+                types: vec![],
+                rule: Some(rule),
+            }),
+        };
+
+        let mut zone = zone.ok_or_else(|| format!("unknown timezone '{name}'"))?;
+        zone.name = name.to_string();
+        Ok(zone)
+    }
+
+    /// The local time zone, given by the `TZ` environment variable or the
+    /// system's configured zone, falling back to UTC
+    pub fn local() -> Zone {
+        let name = std::env::var("TZ").ok().filter(|tz| !tz.is_empty());
+        let name = name.or_else(|| {
+            let link = std::fs::read_link("/etc/localtime").ok()?;
+            let link = link.to_string_lossy().to_string();
+            link.split_once("zoneinfo/")
+                .map(|(_, name)| name.to_string())
+        });
+
+        name.and_then(|name| Zone::get(&name).ok())
+            .unwrap_or_else(|| Zone::get("UTC").expect("UTC is a bundled zone"))
+    }
+
+    /// The offset from UTC, in seconds, and abbreviation of the zone at a
+    /// time in UTC
+    ///
+    /// Times before the first transition take the zone's first local time
+    /// type, which is usually local mean time.
+    pub fn at(&self, utc: i64) -> (i64, &str) {
+        let i = self.transitions.partition_point(|(t, _)| *t <= utc);
+        match &self.rule {
+            Some(rule) if i == self.transitions.len() => rule.at(utc),
+            _ => {
+                let ty = match i {
+                    0 => 0,
+                    i => self.transitions[i - 1].1,
+                };
+
+                self.types
+                    .get(ty)
+                    .map_or((0, "UTC"), |(offset, abbr)| (*offset, abbr))
+            }
+        }
+    }
+
+    /// The time in UTC of a local time in this zone
+    ///
+    /// Local times which are repeated when clocks are set back are taken as
+    /// the earlier of the two, as with daylight saving time, and those
+    /// skipped when clocks are set forward as the offset before the change,
+    /// as with standard time.
+    pub fn to_utc(&self, local: i64) -> i64 {
+        // offsets in effect on either side of any nearby transition
+        let before = self.at(local - 86400).0;
+        let after = self.at(local + 86400).0;
+
+        [before, after]
+            .into_iter()
+            .map(|offset| local - offset)
+            .find(|utc| local - self.at(*utc).0 == *utc)
+            .unwrap_or(local - before)
+    }
+}
+
+/// Read a zone from its compiled TZif form
+///
+/// Versions 2 and later repeat the data with 64-bit times, followed by the
+/// POSIX `TZ` rule for times after the last transition.
+fn tzif(data: &[u8]) -> Option<Zone> {
+    let mut rest = data;
+    let (version, counts) = tzif_header(&mut rest)?;
+    let v1 = tzif_block(&mut rest, &counts, 4)?;
+    if version == 0 {
+        return Some(v1);
+    }
+
+    let (_, counts) = tzif_header(&mut rest)?;
+    let mut zone = tzif_block(&mut rest, &counts, 8)?;
+
+    // the footer rule is enclosed in newlines, and is empty when unknown
+    let footer = std::str::from_utf8(rest).ok()?;
+    let footer = footer.strip_prefix('\n')?.split('\n').next()?;
+    zone.rule = match footer {
+        "" => None,
+        rule => Some(parse(rule)?),
+    };
+
+    Some(zone)
+}
+
+/// The version and record counts of a TZif header
+fn tzif_header(rest: &mut &[u8]) -> Option<(u8, [usize; 6])> {
+    let header = take(rest, 44)?;
+    if &header[..4] != b"TZif" {
+        return None;
+    }
+
+    let version = header[4];
+    let mut counts = [0; 6];
+    for (i, count) in counts.iter_mut().enumerate() {
+        *count = be(&header[20 + i * 4..24 + i * 4]) as usize;
+    }
+
+    Some((version, counts))
+}
+
+/// Read the transitions and local time types of a TZif data block, with
+/// times of `size` bytes
+fn tzif_block(rest: &mut &[u8], counts: &[usize; 6], size: usize) -> Option<Zone> {
+    let [isutcnt, isstdcnt, leapcnt, timecnt, typecnt, charcnt] = *counts;
+
+    let times = take(rest, timecnt * size)?;
+    let indices = take(rest, timecnt)?;
+    let types = take(rest, typecnt * 6)?;
+    let chars = take(rest, charcnt)?;
+    take(rest, leapcnt * (size + 4) + isstdcnt + isutcnt)?;
+
+    let transitions = times
+        .chunks(size)
+        .zip(indices)
+        .map(|(t, i)| (be(t), *i as usize))
+        .collect();
+
+    let types = types
+        .chunks(6)
+        .map(|ty| {
+            let offset = be(&ty[..4]);
+            let abbr = chars.get(ty[5] as usize..).unwrap_or_default();
+            let end = abbr.iter().position(|c| *c == 0).unwrap_or(abbr.len());
+            (offset, String::from_utf8_lossy(&abbr[..end]).to_string())
+        })
+        .collect();
+
+    Some(Zone {
+        name: String::new(),
+        transitions,
+        types,
+        rule: None,
+    })
+}
+
+fn take<'a>(rest: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+    if rest.len() < n {
+        return None;
+    }
+
+    let (taken, remainder) = rest.split_at(n);
+    *rest = remainder;
+    Some(taken)
+}
+
+/// A signed big-endian integer
+fn be(bytes: &[u8]) -> i64 {
+    let unsigned = bytes.iter().fold(0_u64, |n, b| n << 8 | *b as u64);
+    let shift = 64 - 8 * bytes.len() as u32;
+    ((unsigned << shift) as i64) >> shift
+}
+
+/// Parse a POSIX `TZ` rule, as in `"CET-1CEST,M3.5.0,M10.5.0/3"`
+fn parse(rule: &str) -> Option<Rule> {
+    let mut rest = rule;
+    let abbr = abbreviation(&mut rest)?;
+    let offset = -duration(&mut rest)?;
+
+    if rest.is_empty() {
+        return Some(Rule {
+            abbr,
+            offset,
+            dst: None,
+        });
+    }
+
+    // daylight time defaults to an hour ahead of standard time
+    let dst_abbr = abbreviation(&mut rest)?;
+    let dst_offset = match rest.starts_with(',') {
+        true => offset + 3600,
+        false => -duration(&mut rest)?,
+    };
+
+    let rest = rest.strip_prefix(',')?;
+    let (start, end) = rest.split_once(',')?;
+    Some(Rule {
+        abbr,
+        offset,
+        dst: Some(Dst {
+            abbr: dst_abbr,
+            offset: dst_offset,
+            start: transition(start)?,
+            end: transition(end)?,
+        }),
+    })
+}
+
+fn abbreviation(rest: &mut &str) -> Option<String> {
+    let (abbr, remainder) = match rest.strip_prefix('<') {
+        Some(quoted) => {
+            let (abbr, remainder) = quoted.split_once('>')?;
+            (abbr, remainder)
+        }
+        None => {
+            let n = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            rest.split_at(n)
+        }
+    };
+
+    *rest = remainder;
+    (abbr.len() >= 3).then(|| abbr.to_string())
+}
+
+/// A signed duration of the form `[+-]h[:mm[:ss]]`, in seconds
+fn duration(rest: &mut &str) -> Option<i64> {
+    let n = rest
+        .find(|c: char| !(c.is_ascii_digit() || c == ':' || c == '+' || c == '-'))
+        .unwrap_or(rest.len());
+
+    let (text, remainder) = rest.split_at(n);
+    *rest = remainder;
+
+    let (sign, text) = match text.strip_prefix('-') {
+        Some(text) => (-1, text),
+        None => (1, text.strip_prefix('+').unwrap_or(text)),
+    };
+
+    let mut seconds = 0;
+    for (i, part) in text.split(':').enumerate() {
+        let part: i64 = part.parse().ok()?;
+        seconds += part * [3600, 60, 1].get(i)?;
+    }
+
+    Some(sign * seconds)
+}
+
+/// A transition of the form `day[/time]`, where the time defaults to 02:00
+fn transition(rule: &str) -> Option<Transition> {
+    let (day, time) = match rule.split_once('/') {
+        Some((day, mut time)) => (day, duration(&mut time)?),
+        None => (rule, 2 * 3600),
+    };
+
+    let day = if let Some(month) = day.strip_prefix('M') {
+        let parts: Vec<u32> = month
+            .split('.')
+            .map(|p| p.parse().ok())
+            .collect::<Option<_>>()?;
+
+        match parts[..] {
+            [m @ 1..=12, w @ 1..=5, d @ 0..=6] => Day::Month(m, w, d),
+            _ => return None,
+        }
+    } else if let Some(n) = day.strip_prefix('J') {
+        Day::Julian(n.parse().ok()?)
+    } else {
+        Day::Ordinal(day.parse().ok()?)
+    };
+
+    Some(Transition { day, time })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn utc(year: i64, month: u32, day: u32, hour: i64) -> i64 {
+        days_from_civil(year, month, day) * 86400 + hour * 3600
+    }
+
+    #[test]
+    fn daylight_saving_time() {
+        let zone = Zone::get("America/New_York").unwrap();
+        assert_eq!(zone.at(utc(2024, 1, 15, 12)), (-5 * 3600, "EST"));
+        assert_eq!(zone.at(utc(2024, 7, 15, 12)), (-4 * 3600, "EDT"));
+
+        // transitions at 02:00 local time on the second Sunday of March
+        assert_eq!(zone.at(utc(2024, 3, 10, 6)).1, "EST");
+        assert_eq!(zone.at(utc(2024, 3, 10, 7)).1, "EDT");
+    }
+
+    #[test]
+    fn historical_transitions() {
+        // before 2007, daylight saving time started on the first Sunday of April
+        let zone = Zone::get("America/New_York").unwrap();
+        assert_eq!(zone.at(utc(2005, 3, 20, 17)), (-5 * 3600, "EST"));
+        assert_eq!(zone.at(utc(2005, 4, 3, 6)).1, "EST");
+        assert_eq!(zone.at(utc(2005, 4, 3, 7)).1, "EDT");
+
+        // British Standard Time was observed all year from 1968 to 1971
+        let zone = Zone::get("Europe/London").unwrap();
+        assert_eq!(zone.at(utc(1970, 1, 15, 12)), (3600, "BST"));
+
+        // local mean time before the first transition
+        let zone = Zone::get("America/New_York").unwrap();
+        assert_eq!(zone.at(utc(1850, 1, 1, 0)).1, "LMT");
+    }
+
+    #[test]
+    fn southern_hemisphere() {
+        let zone = Zone::get("Australia/Sydney").unwrap();
+        assert_eq!(zone.at(utc(2024, 1, 15, 0)), (11 * 3600, "AEDT"));
+        assert_eq!(zone.at(utc(2024, 7, 15, 0)), (10 * 3600, "AEST"));
+    }
+
+    #[test]
+    fn local_times_are_converted() {
+        let zone = Zone::get("Europe/London").unwrap();
+        let local = utc(2024, 7, 1, 9);
+        assert_eq!(zone.to_utc(local), utc(2024, 7, 1, 8));
+
+        // repeated and skipped times
+        let zone = Zone::get("America/Chicago").unwrap();
+        assert_eq!(zone.to_utc(utc(2024, 11, 3, 1)), utc(2024, 11, 3, 6));
+        assert_eq!(zone.to_utc(utc(2024, 3, 10, 2)), utc(2024, 3, 10, 8));
+    }
+
+    #[test]
+    fn rules_are_parsed() {
+        let zone = Zone::get("<+0545>-5:45").unwrap();
+        assert_eq!(zone.at(0), (5 * 3600 + 45 * 60, "+0545"));
+        assert!(Zone::get("Not/A_Zone").is_err());
+    }
+}
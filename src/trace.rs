@@ -0,0 +1,216 @@
+/// Execution Tracing
+///
+/// Closures can be traced using `trace()`, evaluating a tracer before the
+/// body of each call and counting the number of traced calls. When the
+/// `verbose_calls` option is set, every closure call is logged upon entry
+/// and exit along with its arguments and the time spent evaluating it.
+///
+/// Neither is common, so both are skipped using a single flag unless some
+/// function is traced or calls are logged.
+///
+use std::cell::{Cell, RefCell};
+use std::time::Instant;
+
+use crate::context::Context;
+use crate::lang::{CallStack, EvalResult, Signal};
+use crate::object::{Environment, Expr, ExprList, List, Obj};
+
+/// Name of the option enabling logging of closure calls
+pub const VERBOSE_CALLS: &str = "verbose_calls";
+
+#[derive(Debug, Clone)]
+pub enum Tracer {
+    // print the traced call
+    Print,
+    // evaluate an expression in the frame of the traced call
+    Expr(Expr),
+    // call a function without arguments
+    Function(Obj),
+}
+
+struct Traced {
+    f: Obj,
+    tracer: Tracer,
+    calls: usize,
+}
+
+thread_local! {
+    static TRACED: RefCell<Vec<Traced>> = const { RefCell::new(vec![]) };
+
+    // whether calls are logged, following the `verbose_calls` option
+    static VERBOSE: Cell<bool> = const { Cell::new(false) };
+
+    // whether any function is traced or calls are logged
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+}
+
+fn update_enabled() {
+    let traced = TRACED.with(|t| !t.borrow().is_empty());
+    ENABLED.with(|e| e.set(traced || VERBOSE.with(Cell::get)));
+}
+
+/// Log closure calls, as the `verbose_calls` option is set
+pub fn set_verbose_calls(verbose: bool) {
+    VERBOSE.with(|v| v.set(verbose));
+    update_enabled();
+}
+
+/// Trace calls to a function, replacing any existing tracer
+pub fn trace(f: Obj, tracer: Tracer) {
+    TRACED.with(|t| {
+        let mut t = t.borrow_mut();
+        t.retain(|traced| traced.f != f);
+        t.push(Traced {
+            f,
+            tracer,
+            calls: 0,
+        });
+    });
+
+    update_enabled();
+}
+
+/// Stop tracing a function, returning the number of traced calls
+pub fn untrace(f: &Obj) -> Option<usize> {
+    let calls = TRACED.with(|t| {
+        let mut t = t.borrow_mut();
+        let i = t.iter().position(|traced| &traced.f == f)?;
+        Some(t.remove(i).calls)
+    });
+
+    update_enabled();
+    calls
+}
+
+/// Evaluate the tracer of a traced function
+///
+/// Called once a function's arguments have been matched in its new frame.
+pub fn run_tracer(f: &Obj, stack: &mut CallStack) -> Result<(), Signal> {
+    if !ENABLED.with(Cell::get) {
+        return Ok(());
+    }
+
+    let tracer = TRACED.with(|t| {
+        let mut t = t.borrow_mut();
+        let traced = t.iter_mut().find(|traced| &traced.f == f)?;
+        traced.calls += 1;
+        Some(traced.tracer.clone())
+    });
+
+    match tracer {
+        None => Ok(()),
+        Some(Tracer::Print) => {
+            println!("trace: {}", stack.last_frame().call);
+            Ok(())
+        }
+        Some(Tracer::Expr(expr)) => stack.eval_and_finalize(expr).map(|_| ()),
+        Some(Tracer::Function(tracer)) => {
+            // bind tracer in a child environment of the traced call's frame
+            let env = std::rc::Rc::new(Environment {
+                parent: Some(stack.env()),
+                ..Default::default()
+            });
+
+            let name = String::from(".tracer");
+            env.insert(name.clone(), tracer);
+            let call = Expr::Call(Box::new(Expr::Symbol(name)), ExprList::new());
+            Obj::Closure(call, env).force(stack).map(|_| ())
+        }
+    }
+}
+
+/// A call logged on entry, to be logged again on exit
+pub struct LoggedCall {
+    label: String,
+    depth: usize,
+    start: Instant,
+}
+
+/// Log entry into a closure, if logging calls
+///
+/// Arguments are logged as they were provided, before they are evaluated.
+pub fn log_entry(args: &List, stack: &CallStack) -> Option<LoggedCall> {
+    if !ENABLED.with(Cell::get) || !VERBOSE.with(Cell::get) {
+        return None;
+    }
+
+    let name = match &stack.last_frame().call {
+        Expr::Call(what, _) => match what.as_ref() {
+            Expr::Symbol(s) | Expr::String(s) => s.clone(),
+            _ => String::from("<anonymous>"),
+        },
+        _ => String::from("<anonymous>"),
+    };
+
+    let args = args
+        .values
+        .borrow()
+        .iter()
+        .map(|(k, v)| {
+            let v = match v {
+                Obj::Closure(expr, _) => expr.to_string(),
+                v => {
+                    // drop the index prefix from single line vector output
+                    let s = v.to_string();
+                    let s = s.trim_end();
+                    match s.strip_prefix("[1] ") {
+                        Some(value) if !value.contains('\n') => value.to_string(),
+                        _ => s.to_string(),
+                    }
+                }
+            };
+
+            match k {
+                Some(k) => format!("{k} = {v}"),
+                None => v,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let depth = stack.frames.len().saturating_sub(2);
+    let label = format!("{name}({args})");
+    eprintln!("{}-> {label}", "  ".repeat(depth));
+
+    Some(LoggedCall {
+        label,
+        depth,
+        start: Instant::now(),
+    })
+}
+
+/// Log exit from a closure with the time spent evaluating it
+pub fn log_exit(call: LoggedCall, result: &EvalResult) {
+    let indent = "  ".repeat(call.depth);
+    let elapsed = call.start.elapsed().as_secs_f64() * 1000.0;
+    let status = match result {
+        Err(Signal::Error(_)) => " (error)",
+        _ => "",
+    };
+    eprintln!("{indent}<- {} [{elapsed:.3}ms]{status}", call.label);
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+
+    use super::{trace, untrace, Tracer, ENABLED};
+    use crate::object::Obj;
+    use crate::repl::eval;
+
+    #[test]
+    fn calls_are_only_hooked_while_tracing_or_logging() {
+        let enabled = || ENABLED.with(Cell::get);
+        assert!(!enabled());
+
+        trace(Obj::Null, Tracer::Print);
+        assert!(enabled());
+        untrace(&Obj::Null);
+        assert!(!enabled());
+
+        eval("options(verbose_calls = TRUE)").unwrap();
+        assert!(enabled());
+        eval("options(verbose_calls = NULL)").unwrap();
+        assert!(!enabled());
+    }
+}
@@ -63,3 +63,13 @@ macro_rules! r_expect {
         }
     }};
 }
+
+/// A path to a new temporary file with the given extension, for tests which
+/// write to the filesystem
+#[cfg(test)]
+pub fn temp_file(ext: &str) -> String {
+    crate::repl::eval(&format!("tempfile(fileext = '{ext}')"))
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
@@ -0,0 +1,47 @@
+/// Warnings
+///
+/// Warnings are handled according to the `warn` option:
+///
+/// * negative values ignore warnings
+/// * `0` defers warnings until the current top level evaluation completes
+/// * `1` prints warnings as they occur
+/// * `2` or greater converts warnings into errors
+///
+use std::cell::RefCell;
+
+use crate::error::Error;
+use crate::lang::Signal;
+use crate::options;
+
+thread_local! {
+    static DEFERRED: RefCell<Vec<String>> = const { RefCell::new(vec![]) };
+}
+
+/// Emit a warning
+pub fn warn(msg: impl Into<String>) -> Result<(), Signal> {
+    let msg = msg.into();
+    match options::get_integer(options::WARN).unwrap_or(0) {
+        i if i < 0 => (),
+        0 => DEFERRED.with(|w| w.borrow_mut().push(msg)),
+        1 => eprintln!("Warning: {msg}"),
+        _ => return Err(Error::Other(format!("(converted from warning) {msg}")).into()),
+    }
+
+    Ok(())
+}
+
+/// Take any deferred warnings, formatted for display
+pub fn take_deferred() -> Option<String> {
+    let warnings = DEFERRED.with(|w| w.take());
+    match warnings.len() {
+        0 => None,
+        1 => Some(format!("Warning message:\n{}\n", warnings[0])),
+        _ => {
+            let mut msg = String::from("Warning messages:\n");
+            for (i, warning) in warnings.iter().enumerate() {
+                msg.push_str(&format!("{}: {warning}\n", i + 1));
+            }
+            Some(msg)
+        }
+    }
+}
@@ -0,0 +1,80 @@
+/// Workspaces
+///
+/// Sets of bindings are saved from an environment to a file using
+/// `save()`, and loaded into another using `load()`, in the serialization
+/// format of `serialize()`. Closures that capture the environment they were
+/// saved from capture the environment they are loaded into instead.
+///
+/// The REPL restores the global environment from `.RData` when it starts,
+/// and saves it again on exit when started with `--save` or when quit using
+/// `q(save = "yes")`.
+///
+use std::cell::Cell;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::err;
+use crate::lang::Signal;
+use crate::object::{Environment, Snapshot};
+
+/// The file holding the workspace of a session
+pub const DEFAULT_FILE: &str = ".RData";
+
+thread_local! {
+    static SAVE_ON_EXIT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Set whether the workspace is saved when the session ends
+pub fn set_save_on_exit(save: bool) {
+    SAVE_ON_EXIT.with(|s| s.set(save))
+}
+
+/// Whether the workspace is saved when the session ends
+pub fn save_on_exit() -> bool {
+    SAVE_ON_EXIT.with(|s| s.get())
+}
+
+/// Save bindings of an environment to a file
+pub fn save(env: &Rc<Environment>, names: &[String], file: &str) -> Result<(), Signal> {
+    for name in names {
+        if !env.values.borrow().contains_key(name) {
+            return err!(format!("object '{name}' not found"));
+        }
+    }
+
+    let bytes = Snapshot::from_bindings(env, names).serialize()?;
+    match std::fs::write(file, bytes) {
+        Ok(_) => Ok(()),
+        Err(e) => err!(format!("cannot write file '{file}': {e}")),
+    }
+}
+
+/// Save all bindings of an environment to a file
+pub fn save_image(env: &Rc<Environment>, file: &str) -> Result<(), Signal> {
+    let mut names: Vec<String> = env.values.borrow().keys().cloned().collect();
+    names.sort();
+    save(env, &names, file)
+}
+
+/// Load bindings saved to a file into an environment, returning their names
+pub fn load(env: &Rc<Environment>, file: &str) -> Result<Vec<String>, Signal> {
+    let bytes = match std::fs::read(file) {
+        Ok(bytes) => bytes,
+        Err(e) => return err!(format!("cannot open file '{file}': {e}")),
+    };
+
+    match Snapshot::unserialize(&bytes)?.restore_into(env.clone()) {
+        Some(names) => Ok(names),
+        None => err!(format!("file '{file}' does not contain saved bindings")),
+    }
+}
+
+/// Restore the workspace of a previous session, if one was saved
+pub fn restore(env: &Rc<Environment>) -> Result<bool, Signal> {
+    if !Path::new(DEFAULT_FILE).exists() {
+        return Ok(false);
+    }
+
+    load(env, DEFAULT_FILE)?;
+    Ok(true)
+}